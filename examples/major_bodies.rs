@@ -0,0 +1,84 @@
+//! Generates the entries of the `MajorBody` enum in `src/request/bodies.rs` from a saved
+//! Horizons major-body list, so new satellites can be added by refreshing the list rather
+//! than editing the enum by hand.
+//!
+//! Save the result of a `MajorBodyList` request (`COMMAND='MB'`) to a file, then run
+//!
+//! ```text
+//! cargo run --example major_bodies -- mb.txt > entries.txt
+//! ```
+//!
+//! and paste the entries into the `bodies!` invocation. Review the diff: names that
+//! Horizons changed will rename variants, which is a breaking change.
+
+use std::{env, fs, process::ExitCode};
+
+use horizons::response::bodies::MajorBodyRecord;
+
+/// `Jupiter Barycenter` -> `JupiterBary`, `S/2010 J 1` -> `S2010J1`
+fn variant_name(record: &MajorBodyRecord) -> Option<String> {
+    let name = if record.name.is_empty() {
+        record.designation.as_deref()?
+    } else {
+        record.name.as_str()
+    };
+
+    let (name, bary) = match name.strip_suffix(" Barycenter") {
+        Some(system) => (system, true),
+        None => (name, false),
+    };
+
+    let mut variant: String = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect();
+
+    if bary {
+        variant.push_str("Bary");
+    }
+
+    // Identifiers cannot start with a digit
+    variant
+        .starts_with(|c: char| c.is_ascii_alphabetic())
+        .then_some(variant)
+}
+
+fn main() -> ExitCode {
+    let path = match env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: major_bodies <saved MB list>");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let records = match fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| MajorBodyRecord::parse_list(&text).map_err(|e| e.to_string()))
+    {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("{path}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Spacecraft and other negative codes belong to `Spacecraft`
+    let mut bodies: Vec<(u32, String)> = records
+        .iter()
+        .filter_map(|record| Some((u32::try_from(record.code).ok()?, variant_name(record)?)))
+        .collect();
+    bodies.sort_unstable();
+
+    for (code, name) in bodies {
+        println!("        {name} = {code},");
+    }
+
+    ExitCode::SUCCESS
+}
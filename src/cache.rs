@@ -0,0 +1,212 @@
+//! Responses stored on disk by the endpoint and [`canonical form`](Query::canonicalize) of
+//! their query, so that repeated identical queries need not be sent again.
+//!
+//! Each response is a file named by a hash of the endpoint and query. It holds a
+//! [`CACHE_VERSION`] header on its first line, the endpoint and query string on its second
+//! (to tell apart queries with the same hash), and then the response. Files of another
+//! version are misses, and are replaced when the response is stored again.
+
+#![allow(clippy::must_use_candidate, clippy::missing_errors_doc)]
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::request::{params::ApiEndpoint, Query};
+
+const EXTENSION: &str = "txt";
+
+/// Version of the format of cache files, increased when it changes
+pub const CACHE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskCache {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+/// FNV-1a, which unlike the std hashers is stable across Rust versions and platforms
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// First line of a cache file, with its newline
+fn header() -> String {
+    format!("horizons-cache {CACHE_VERSION}\n")
+}
+
+/// The endpoint and query string, which never contain a newline
+fn key(endpoint: &ApiEndpoint, query: &Query) -> String {
+    format!("{} {}", endpoint.as_str(), query.canonicalize())
+}
+
+impl DiskCache {
+    /// A cache in `dir`, created when a response is first stored, whose files are removed
+    /// oldest first to stay under `max_bytes`
+    pub fn new(dir: impl Into<PathBuf>, max_bytes: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            max_bytes,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.{EXTENSION}", fnv1a(key.as_bytes())))
+    }
+
+    /// The stored response to `query` sent to `endpoint`, if any
+    pub fn get(&self, endpoint: &ApiEndpoint, query: &Query) -> io::Result<Option<String>> {
+        let key = key(endpoint, query);
+
+        match fs::read_to_string(self.path(&key)) {
+            Ok(contents) => Ok(contents
+                .strip_prefix(&header())
+                .and_then(|rest| rest.split_once('\n'))
+                .filter(|(stored, _)| *stored == key)
+                .map(|(_, response)| response.to_owned())),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Stores `response` to `query` sent to `endpoint`, then evicts the oldest responses
+    /// over the size limit
+    pub fn insert(&self, endpoint: &ApiEndpoint, query: &Query, response: &str) -> io::Result<()> {
+        let key = key(endpoint, query);
+
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.path(&key), format!("{}{key}\n{response}", header()))?;
+
+        self.evict()
+    }
+
+    /// Removes every stored response
+    pub fn clear(&self) -> io::Result<()> {
+        for (path, ..) in self.entries()? {
+            fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Files of the cache with their size and modification time
+    fn entries(&self) -> io::Result<Vec<(PathBuf, u64, SystemTime)>> {
+        let dir = match fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error),
+        };
+
+        let mut entries = Vec::new();
+
+        for entry in dir {
+            let path = entry?.path();
+
+            if path
+                .extension()
+                .map_or(false, |extension| extension == EXTENSION)
+            {
+                let metadata = fs::metadata(&path)?;
+                entries.push((path, metadata.len(), metadata.modified()?));
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn evict(&self) -> io::Result<()> {
+        let mut entries = self.entries()?;
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+
+        entries.sort_by_key(|(_, _, modified)| *modified);
+
+        for (path, size, _) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+
+            fs::remove_file(path)?;
+            total -= size;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::{
+        cache::{key, DiskCache, CACHE_VERSION},
+        request::{
+            bodies::MajorBody,
+            ephemeris::{StepSizeUnit, TimeSpec},
+            params::ApiEndpoint,
+            presets::state_vectors,
+        },
+        TestResult,
+    };
+
+    #[test]
+    fn test_disk_cache() -> TestResult {
+        let dir = std::env::temp_dir().join(format!("horizons-cache-{}", std::process::id()));
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let query = |days| {
+            state_vectors(
+                MajorBody::Mars,
+                MajorBody::Sun,
+                TimeSpec::bounded((1, StepSizeUnit::Days), start, start + Duration::days(days)),
+            )
+        };
+        let (ten, twenty) = (query(10)?, query(20)?);
+
+        let cache = DiskCache::new(&dir, 1 << 20);
+        let api = ApiEndpoint::default();
+        assert_eq!(None, cache.get(&api, &ten)?);
+
+        cache.insert(&api, &ten, "$$SOE\nten\n$$EOE")?;
+        cache.insert(&api, &twenty, "$$SOE\ntwenty\n$$EOE")?;
+        assert_eq!(Some("$$SOE\nten\n$$EOE".to_owned()), cache.get(&api, &ten)?);
+        assert_eq!(
+            Some("$$SOE\ntwenty\n$$EOE".to_owned()),
+            cache.get(&api, &twenty)?
+        );
+
+        // A mirror has entries of its own
+        let mirror = ApiEndpoint::new("http://localhost:8080/api/horizons.api");
+        assert_eq!(None, cache.get(&mirror, &ten)?);
+
+        // Files of another version are misses
+        let path = cache.path(&key(&api, &ten));
+        let contents = std::fs::read_to_string(&path)?;
+        assert!(contents.starts_with(&format!("horizons-cache {CACHE_VERSION}\n")));
+        std::fs::write(
+            &path,
+            contents.replacen("horizons-cache 1", "horizons-cache 0", 1),
+        )?;
+        assert_eq!(None, cache.get(&api, &ten)?);
+        assert!(cache.get(&api, &twenty)?.is_some());
+
+        // Room for the larger response only
+        let largest = cache.entries()?.iter().map(|(_, size, _)| *size).max();
+        let small = DiskCache::new(&dir, largest.unwrap_or_default());
+        small.insert(&api, &twenty, "$$SOE\ntwenty\n$$EOE")?;
+        assert_eq!(1, small.entries()?.len());
+
+        cache.clear()?;
+        assert_eq!(None, cache.get(&api, &twenty)?);
+        std::fs::remove_dir(&dir)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1378 @@
+//! Sending queries with an HTTP stack of the caller's choice. Implement [`HttpTransport`]
+//! for it (or pass a closure), and [`Client`] builds the request and decodes the
+//! response, which the parsers in [`response`](crate::response) then take.
+
+#![allow(clippy::must_use_candidate, clippy::missing_errors_doc)]
+
+use std::{
+    fmt::{Debug, Formatter, Result as FmtResult},
+    io::{self, BufRead, BufReader, Cursor, Read},
+    string::FromUtf8Error,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+use crate::{
+    cache::DiskCache,
+    lookup::{Lookup, LookupError, LookupMatch, LookupResult},
+    request::{params::ApiEndpoint, Command, Query, TooLarge},
+    response::{
+        bodies::MajorBodyRecord,
+        busy::{detect_busy, ServerBusy},
+        echo::{verify_echo, MismatchedEcho},
+        ephemeris::{
+            vectors::{RecordStream, StreamError, VectorTable},
+            ParseError,
+        },
+        staged::{staged_files, DownloadedFile},
+    },
+};
+
+/// The HTTP requests of a [`Client`]
+pub trait HttpTransport {
+    type Error;
+
+    /// Sends a GET request for `url`, or a POST request of `body` if there is one, of
+    /// content type [`FORM_CONTENT_TYPE`], and returns the body of the response
+    ///
+    /// [`FORM_CONTENT_TYPE`]: crate::request::file::FORM_CONTENT_TYPE
+    fn send(&self, url: &str, body: Option<&[u8]>) -> Result<Vec<u8>, Self::Error>;
+
+    /// Sends a request like [`send`](Self::send) through `proxy`, or returns `None` if the
+    /// transport cannot use proxies, as by default
+    fn send_via(
+        &self,
+        proxy: &Proxy,
+        url: &str,
+        body: Option<&[u8]>,
+    ) -> Option<Result<Vec<u8>, Self::Error>> {
+        let _ = (proxy, url, body);
+        None
+    }
+
+    /// Sends a request like [`send`](Self::send) and returns the body of the response to be
+    /// read as it arrives, or `None` if the transport cannot stream, as by default
+    fn send_streaming(
+        &self,
+        url: &str,
+        body: Option<&[u8]>,
+    ) -> Option<Result<Box<dyn Read>, Self::Error>> {
+        let _ = (url, body);
+        None
+    }
+}
+
+impl<F, E> HttpTransport for F
+where
+    F: Fn(&str, Option<&[u8]>) -> Result<Vec<u8>, E>,
+{
+    type Error = E;
+
+    fn send(&self, url: &str, body: Option<&[u8]>) -> Result<Vec<u8>, E> {
+        self(url, body)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ClientError<E> {
+    #[error("{0}")]
+    Transport(E),
+    #[error("Response is not UTF-8: {0}")]
+    InvalidUtf8(#[from] FromUtf8Error),
+    #[error("Cache: {0}")]
+    Cache(#[from] io::Error),
+    #[error("{0}")]
+    Parse(#[from] ParseError),
+    #[error("{0}")]
+    MismatchedEcho(#[from] MismatchedEcho),
+    #[error("The transport cannot send requests through a proxy")]
+    ProxyUnsupported,
+    #[error("{0}")]
+    Stream(#[from] StreamError),
+    #[error("{0}")]
+    ServerBusy(#[from] ServerBusy),
+    #[error("{0}")]
+    Lookup(#[from] LookupError),
+    #[error("{0}")]
+    TooLarge(#[from] TooLarge),
+    /// The target of the query matches these major bodies
+    #[error("{} major bodies match the target", .0.len())]
+    Ambiguous(Vec<LookupMatch>),
+}
+
+impl<E> ClientError<E> {
+    /// Whether a later request for the same query may succeed, which the [`RetryPolicy`] of
+    /// a client retries
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::ServerBusy(_))
+    }
+}
+
+/// An HTTP(S) proxy for the requests of a [`Client`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proxy {
+    url: String,
+    no_proxy: Vec<String>,
+}
+
+/// Host of `url`, e.g. `ssd.jpl.nasa.gov`
+fn host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+
+    host.split(':').next().unwrap_or_default()
+}
+
+impl Proxy {
+    /// The proxy at `url`, e.g. `http://proxy.example.edu:3128`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            no_proxy: Vec::new(),
+        }
+    }
+
+    /// The proxy of the `HTTPS_PROXY` or `ALL_PROXY` environment variables (or their
+    /// lowercase forms), bypassed for the hosts in `NO_PROXY`
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .or_else(|_| std::env::var(name.to_ascii_lowercase()))
+                .ok()
+                .filter(|value| !value.is_empty())
+        };
+
+        let proxy = Self::new(var("HTTPS_PROXY").or_else(|| var("ALL_PROXY"))?);
+
+        Some(match var("NO_PROXY") {
+            Some(hosts) => proxy.bypass(hosts.split(',').map(str::trim)),
+            None => proxy,
+        })
+    }
+
+    /// Reaches `hosts` directly: each matches itself and its subdomains, and `*` every
+    /// host
+    #[must_use]
+    pub fn bypass<S: Into<String>>(mut self, hosts: impl IntoIterator<Item = S>) -> Self {
+        self.no_proxy.extend(hosts.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Whether a request for `url` goes through the proxy
+    pub fn applies_to(&self, url: &str) -> bool {
+        let host = host(url).to_ascii_lowercase();
+
+        !self.no_proxy.iter().any(|bypassed| {
+            let bypassed = bypassed.trim_start_matches('.').to_ascii_lowercase();
+
+            bypassed == "*" || host == bypassed || host.ends_with(&format!(".{bypassed}"))
+        })
+    }
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq)]
+#[error("{0} is not a positive, finite number of requests per second")]
+pub struct InvalidRate(pub f64);
+
+/// Longest wait of [`RateLimiter::acquire`], however many requests are queued
+const MAX_WAIT: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A token bucket limiting how often requests are sent. Clones share the bucket, so one
+/// limiter can pace all the clients of a program.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    /// Requests per second
+    rate: f64,
+    burst: f64,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Negative when requests are waiting for tokens
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// At most `burst` requests at once, then `requests_per_second` on average
+    pub fn new(requests_per_second: f64, burst: u32) -> Result<Self, InvalidRate> {
+        if !(requests_per_second.is_finite() && requests_per_second > 0.) {
+            return Err(InvalidRate(requests_per_second));
+        }
+
+        let burst = f64::from(burst.max(1));
+
+        Ok(Self {
+            rate: requests_per_second,
+            burst,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: burst,
+                updated: Instant::now(),
+            })),
+        })
+    }
+
+    /// Takes a token, blocking until one is available, or for at most a day
+    pub fn acquire(&self) {
+        let wait = self.reserve(Instant::now());
+
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// Takes a token at `now`, returning how long to wait before using it
+    fn reserve(&self, now: Instant) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst) - 1.;
+        bucket.updated = bucket.updated.max(now);
+
+        if bucket.tokens >= 0. {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((-bucket.tokens / self.rate).min(MAX_WAIT.as_secs_f64()))
+        }
+    }
+}
+
+/// How often a [`Client`] sends a query again after a [retryable](ClientError::is_retryable)
+/// failure, waiting twice as long before each attempt as before the last
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// At most `max_attempts` requests per query, the second after `backoff`
+    pub fn new(max_attempts: u32, backoff: Duration) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            backoff,
+        }
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// How long to wait after failed attempt number `attempt`, counting from 1, or `None`
+    /// if it was the last
+    fn delay(&self, attempt: u32) -> Option<Duration> {
+        (attempt < self.max_attempts)
+            .then(|| self.backoff.saturating_mul(1 << (attempt - 1).min(16)))
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, the second after ten seconds
+    fn default() -> Self {
+        Self::new(3, Duration::from_secs(10))
+    }
+}
+
+/// How far a fetch has got, reported after each request to the hook of
+/// [`Client::with_progress`]. Transports return whole bodies, so bytes are counted per
+/// request rather than as they arrive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Received so far by this fetch
+    pub bytes: u64,
+    /// Requests completed, including responses served from the cache
+    pub completed: usize,
+    /// Requests the fetch makes in all, more than one for the parts of
+    /// [`Client::fetch_vectors`]
+    pub total: usize,
+}
+
+#[derive(Clone)]
+struct ProgressHook(Arc<dyn Fn(Progress) + Send + Sync>);
+
+impl Debug for ProgressHook {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("ProgressHook")
+    }
+}
+
+/// Picks the target among the matches of an ambiguous search, for [`Client::resolve`] and
+/// [`Client::fetch`]
+type Pick = dyn Fn(&[LookupMatch]) -> Option<Command> + Send + Sync;
+
+#[derive(Clone)]
+struct Disambiguation(Arc<Pick>);
+
+impl Debug for Disambiguation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("Disambiguation")
+    }
+}
+
+/// Longest URL of a GET request; longer queries are posted to the file API instead
+pub const MAX_URL_LEN: usize = 4096;
+
+#[derive(Debug, Clone)]
+pub struct Client<T> {
+    transport: T,
+    endpoint: ApiEndpoint,
+    file_endpoint: ApiEndpoint,
+    lookup_endpoint: ApiEndpoint,
+    limiter: Option<RateLimiter>,
+    cache: Option<DiskCache>,
+    check_echo: bool,
+    proxy: Option<Proxy>,
+    progress: Option<ProgressHook>,
+    retry: Option<RetryPolicy>,
+    disambiguation: Option<Disambiguation>,
+    max_records: Option<u64>,
+}
+
+impl<T: HttpTransport> Client<T> {
+    /// A client of the official API
+    pub fn new(transport: T) -> Self {
+        Self::with_endpoint(transport, ApiEndpoint::default())
+    }
+
+    pub fn with_endpoint(transport: T, endpoint: ApiEndpoint) -> Self {
+        Self {
+            transport,
+            endpoint,
+            file_endpoint: ApiEndpoint::file(),
+            lookup_endpoint: ApiEndpoint::lookup(),
+            limiter: None,
+            cache: None,
+            check_echo: false,
+            proxy: None,
+            progress: None,
+            retry: None,
+            disambiguation: None,
+            max_records: None,
+        }
+    }
+
+    /// Paces requests with `limiter`, which may be shared with other clients
+    #[must_use]
+    pub fn rate_limited(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    /// Serves repeated queries from `cache`, and stores the responses to new ones in it,
+    /// keyed by the [`endpoint`](Self::endpoint) of the client
+    #[must_use]
+    pub fn with_cache(mut self, cache: DiskCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Compares the settings echoed in each response with those of its query, failing with
+    /// [`ClientError::MismatchedEcho`] where Horizons read them differently; see
+    /// [`verify_echo`]
+    #[must_use]
+    pub fn with_echo_check(mut self) -> Self {
+        self.check_echo = true;
+        self
+    }
+
+    /// Sends requests to the API, the file API and the Lookup API under `base`, e.g. a mirror or a test
+    /// server at `http://localhost:8080`, instead of [`ApiEndpoint::HORIZONS`]
+    #[must_use]
+    pub fn with_base_url(mut self, base: &str) -> Self {
+        let base = base.trim_end_matches('/');
+        self.endpoint = ApiEndpoint::new(format!("{base}/api/horizons.api"));
+        self.file_endpoint = ApiEndpoint::new(format!("{base}/api/horizons_file.api"));
+        self.lookup_endpoint = ApiEndpoint::new(format!("{base}/api/horizons_lookup.api"));
+        self
+    }
+
+    /// Sends queries again as `policy` allows when Horizons is busy, instead of failing with
+    /// [`ClientError::ServerBusy`] at once
+    #[must_use]
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Calls `pick` when a search of [`Client::resolve`] matches several objects, e.g. to
+    /// prompt the user of a CLI, and continues with the target it returns. The search
+    /// fails as ambiguous if `pick` returns `None`.
+    ///
+    /// `pick` is also called with the candidates Horizons lists when the target of a fetched
+    /// query matches several major bodies, and the query is sent again once for the target
+    /// it returns. Without a pick, such fetches fail with [`ClientError::Ambiguous`].
+    #[must_use]
+    pub fn with_disambiguation(
+        mut self,
+        pick: impl Fn(&[LookupMatch]) -> Option<Command> + Send + Sync + 'static,
+    ) -> Self {
+        self.disambiguation = Some(Disambiguation(Arc::new(pick)));
+        self
+    }
+
+    /// Fails with [`ClientError::TooLarge`] before sending a query that would return more
+    /// than `max_records` records, as estimated by [`Query::check_size`]. The limit applies
+    /// to the whole span of [`Client::fetch_vectors`], however it is split.
+    #[must_use]
+    pub fn with_max_records(mut self, max_records: u64) -> Self {
+        self.max_records = Some(max_records);
+        self
+    }
+
+    /// Calls `hook` after each request, e.g. to update a progress bar
+    #[must_use]
+    pub fn with_progress(mut self, hook: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        self.progress = Some(ProgressHook(Arc::new(hook)));
+        self
+    }
+
+    /// Sends requests through `proxy`, e.g. [`Proxy::from_env`]. The transport must
+    /// implement [`HttpTransport::send_via`].
+    #[must_use]
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Sends queries with URLs longer than [`MAX_URL_LEN`] to `endpoint`, instead of
+    /// [`ApiEndpoint::HORIZONS_FILE`]
+    #[must_use]
+    pub fn with_file_endpoint(mut self, endpoint: ApiEndpoint) -> Self {
+        self.file_endpoint = endpoint;
+        self
+    }
+
+    pub fn endpoint(&self) -> &ApiEndpoint {
+        &self.endpoint
+    }
+
+    pub fn file_endpoint(&self) -> &ApiEndpoint {
+        &self.file_endpoint
+    }
+
+    pub fn lookup_endpoint(&self) -> &ApiEndpoint {
+        &self.lookup_endpoint
+    }
+
+    /// Sends `lookup` to the Lookup API. Responses are not cached.
+    pub fn lookup(&self, lookup: &Lookup) -> Result<LookupResult, ClientError<T::Error>> {
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire();
+        }
+
+        let body = self.send(&lookup.to_url(&self.lookup_endpoint), None)?;
+
+        Ok(LookupResult::from_json(&String::from_utf8(body)?)?)
+    }
+
+    /// The target that `search` names, picked by the hook of
+    /// [`Client::with_disambiguation`] if several objects match
+    pub fn resolve(&self, search: &str) -> Result<Command, ClientError<T::Error>> {
+        let result = self.lookup(&Lookup::new(search))?;
+
+        Ok(match &self.disambiguation {
+            Some(Disambiguation(pick)) => result.resolve_with(|matches| pick(matches)),
+            None => result.resolve(),
+        }?)
+    }
+
+    /// Fetches `query` for the target that `search` names, see [`Client::resolve`]
+    pub fn fetch_named(
+        &self,
+        search: &str,
+        query: &Query,
+    ) -> Result<String, ClientError<T::Error>> {
+        let mut query = query.clone();
+        query.set_command(self.resolve(search)?);
+
+        self.fetch(&query)
+    }
+
+    /// Submits `query` and returns the text of the response. Queries too long for a URL,
+    /// such as those with thousands of epochs, are posted to the file API as
+    /// [`Query::to_form`].
+    pub fn fetch(&self, query: &Query) -> Result<String, ClientError<T::Error>> {
+        self.check_size(query)?;
+        let response = self.fetch_one(query)?;
+
+        self.report(Progress {
+            bytes: response.len() as u64,
+            completed: 1,
+            total: 1,
+        });
+
+        Ok(response)
+    }
+
+    /// Fetches `query`, or else the query for the target picked among the candidates of an
+    /// ambiguous response
+    fn fetch_one(&self, query: &Query) -> Result<String, ClientError<T::Error>> {
+        match self.fetch_exact(query) {
+            Err(ClientError::Ambiguous(matches)) => {
+                let picked = match &self.disambiguation {
+                    Some(Disambiguation(pick)) => pick(&matches),
+                    None => None,
+                };
+
+                match picked {
+                    Some(command) => {
+                        let mut query = query.clone();
+                        query.set_command(command);
+                        self.fetch_exact(&query)
+                    }
+                    None => Err(ClientError::Ambiguous(matches)),
+                }
+            }
+            result => result,
+        }
+    }
+
+    fn fetch_exact(&self, query: &Query) -> Result<String, ClientError<T::Error>> {
+        if let Some(cache) = &self.cache {
+            if let Some(response) = cache.get(&self.endpoint, query)? {
+                self.check(query, &response)?;
+                return Ok(response);
+            }
+        }
+
+        let mut attempt = 1;
+        let response = loop {
+            match self.attempt(query) {
+                Err(error) if error.is_retryable() => {
+                    match self.retry.and_then(|retry| retry.delay(attempt)) {
+                        Some(delay) => std::thread::sleep(delay),
+                        None => return Err(error),
+                    }
+
+                    attempt += 1;
+                }
+                result => break result?,
+            }
+        };
+
+        if let Some(cache) = &self.cache {
+            cache.insert(&self.endpoint, query, &response)?;
+        }
+
+        Ok(response)
+    }
+
+    /// Sends `query` once, failing if Horizons is busy or the target is ambiguous
+    fn attempt(&self, query: &Query) -> Result<String, ClientError<T::Error>> {
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire();
+        }
+
+        let (url, form) = self.request(query);
+        let body = self.send(&url, form.as_deref())?;
+        let response = String::from_utf8(body)?;
+
+        if let Some(busy) = detect_busy(&response) {
+            return Err(busy.into());
+        }
+
+        if let Some(candidates) = MajorBodyRecord::parse_ambiguous(&response) {
+            return Err(ClientError::Ambiguous(
+                candidates.into_iter().map(Into::into).collect(),
+            ));
+        }
+
+        self.check(query, &response)?;
+
+        Ok(response)
+    }
+
+    /// Streams the records of a vectors query as the response arrives, if the transport
+    /// implements [`HttpTransport::send_streaming`] and no proxy is in the way, or else
+    /// from the whole response. The cache, echo check, retry policy and progress hook are not
+    /// used.
+    pub fn fetch_stream(
+        &self,
+        query: &Query,
+    ) -> Result<RecordStream<Box<dyn BufRead>>, ClientError<T::Error>> {
+        self.check_size(query)?;
+
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire();
+        }
+
+        let (url, form) = self.request(query);
+        let direct = self
+            .proxy
+            .as_ref()
+            .map_or(true, |proxy| !proxy.applies_to(&url));
+
+        let reader: Box<dyn BufRead> = match direct
+            .then(|| self.transport.send_streaming(&url, form.as_deref()))
+            .flatten()
+        {
+            Some(body) => Box::new(BufReader::new(body.map_err(ClientError::Transport)?)),
+            None => Box::new(Cursor::new(self.send(&url, form.as_deref())?)),
+        };
+
+        RecordStream::new(reader).map_err(|error| match error {
+            StreamError::ServerBusy(busy) => ClientError::ServerBusy(busy),
+            error => error.into(),
+        })
+    }
+
+    /// The URL to send `query` to, and the form to post if it is too long for a GET
+    fn request(&self, query: &Query) -> (String, Option<Vec<u8>>) {
+        let url = query.to_url(&self.endpoint);
+
+        if url.len() <= MAX_URL_LEN {
+            (url, None)
+        } else {
+            (
+                self.file_endpoint.as_str().to_owned(),
+                Some(query.to_form()),
+            )
+        }
+    }
+
+    fn send(&self, url: &str, body: Option<&[u8]>) -> Result<Vec<u8>, ClientError<T::Error>> {
+        let sent = match &self.proxy {
+            Some(proxy) if proxy.applies_to(url) => self
+                .transport
+                .send_via(proxy, url, body)
+                .ok_or(ClientError::ProxyUnsupported)?,
+            _ => self.transport.send(url, body),
+        };
+
+        sent.map_err(ClientError::Transport)
+    }
+
+    fn report(&self, progress: Progress) {
+        if let Some(ProgressHook(hook)) = &self.progress {
+            hook(progress);
+        }
+    }
+
+    fn check_size(&self, query: &Query) -> Result<(), TooLarge> {
+        match self.max_records {
+            Some(max_records) => query.check_size(max_records),
+            None => Ok(()),
+        }
+    }
+
+    fn check(&self, query: &Query, response: &str) -> Result<(), MismatchedEcho> {
+        if self.check_echo {
+            verify_echo(query, response)?;
+        }
+
+        Ok(())
+    }
+
+    /// Fetches and parses a vectors query. Horizons rejects tables longer than its line
+    /// limit, so a span of more than `max_records` records is fetched in parts (see
+    /// [`Query::split`]) and merged into one table.
+    pub fn fetch_vectors(
+        &self,
+        query: &Query,
+        max_records: u64,
+    ) -> Result<VectorTable, ClientError<T::Error>> {
+        self.check_size(query)?;
+
+        let chunks = query.split(max_records);
+        let total = chunks.len().max(1);
+        let mut bytes = 0;
+
+        let mut fetch = |completed, chunk| -> Result<_, ClientError<T::Error>> {
+            let response = self.fetch_one(chunk)?;
+            bytes += response.len() as u64;
+            self.report(Progress {
+                bytes,
+                completed,
+                total,
+            });
+
+            Ok(VectorTable::parse(&response)?)
+        };
+
+        let mut chunks = chunks.iter();
+        let mut table = fetch(1, chunks.next().unwrap_or(query))?;
+
+        for (i, chunk) in chunks.enumerate() {
+            table.append(fetch(i + 2, chunk)?);
+        }
+
+        Ok(table)
+    }
+
+    /// Downloads the files that Horizons staged for `response`, see [`staged_files`]. The
+    /// progress hook is called as each file arrives if the transport can stream, and once
+    /// it has arrived otherwise; [`Progress::total`] counts the files.
+    pub fn download_staged(
+        &self,
+        response: &str,
+    ) -> Result<Vec<DownloadedFile>, ClientError<T::Error>> {
+        let files = staged_files(response);
+        let total = files.len();
+        let mut bytes = 0;
+
+        files
+            .into_iter()
+            .enumerate()
+            .map(|(completed, file)| {
+                let data = self.download(&file.download_url(), bytes, completed, total)?;
+                bytes += data.len() as u64;
+
+                Ok(DownloadedFile { file, data })
+            })
+            .collect()
+    }
+
+    /// Downloads `url`, after `completed` of `total` files of `before` bytes in all
+    fn download(
+        &self,
+        url: &str,
+        before: u64,
+        completed: usize,
+        total: usize,
+    ) -> Result<Vec<u8>, ClientError<T::Error>> {
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire();
+        }
+
+        let direct = self
+            .proxy
+            .as_ref()
+            .map_or(true, |proxy| !proxy.applies_to(url));
+        let report = |received: usize, completed| {
+            self.report(Progress {
+                bytes: before + received as u64,
+                completed,
+                total,
+            });
+        };
+
+        let data = match direct
+            .then(|| self.transport.send_streaming(url, None))
+            .flatten()
+        {
+            Some(body) => {
+                let mut body = body.map_err(ClientError::Transport)?;
+                let mut data = Vec::new();
+                let mut chunk = vec![0; 1 << 16];
+
+                loop {
+                    let read = body.read(&mut chunk).map_err(StreamError::Io)?;
+                    if read == 0 {
+                        break data;
+                    }
+
+                    data.extend_from_slice(&chunk[..read]);
+                    report(data.len(), completed);
+                }
+            }
+            None => self.send(url, None)?,
+        };
+
+        report(data.len(), completed + 1);
+
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        io::Read,
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+
+    use chrono::{Duration as ChronoDuration, TimeZone, Utc};
+
+    use crate::{
+        cache::DiskCache,
+        client::{
+            Client, ClientError, HttpTransport, InvalidRate, Proxy, RateLimiter, RetryPolicy,
+            MAX_WAIT,
+        },
+        lookup::{LookupError, LookupMatch},
+        request::{
+            bodies::MajorBody,
+            ephemeris::{StepSizeUnit, TimeSpec},
+            params::ApiEndpoint,
+            presets::state_vectors,
+            Body, TooLarge,
+        },
+        response::ephemeris::vectors::{tests::JUPITER_SSB, VectorTable},
+        TestResult,
+    };
+
+    #[test]
+    fn test_client() -> TestResult {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let query = state_vectors(
+            MajorBody::Mars,
+            MajorBody::Sun,
+            TimeSpec::from_list([start]),
+        )?;
+
+        let sent = RefCell::new(Vec::new());
+        let client = Client::with_endpoint(
+            |url: &str, body: Option<&[u8]>| -> Result<Vec<u8>, &'static str> {
+                assert_eq!(None, body);
+                sent.borrow_mut().push(url.to_owned());
+                Ok(b"$$SOE\n$$EOE".to_vec())
+            },
+            ApiEndpoint::new("http://localhost:8080/api"),
+        );
+
+        assert_eq!("$$SOE\n$$EOE", client.fetch(&query)?);
+        assert_eq!(
+            vec![query.to_url(&ApiEndpoint::new("http://localhost:8080/api"))],
+            sent.into_inner()
+        );
+
+        let failing = Client::new(|_: &str, _: Option<&[u8]>| Err("timed out"));
+        assert!(matches!(
+            failing.fetch(&query),
+            Err(ClientError::Transport("timed out"))
+        ));
+
+        let binary = Client::new(|_: &str, _: Option<&[u8]>| Ok::<_, ()>(vec![0xff]));
+        assert!(matches!(
+            binary.fetch(&query),
+            Err(ClientError::InvalidUtf8(_))
+        ));
+
+        let checked = Client::new(|_: &str, _: Option<&[u8]>| {
+            Ok::<_, &str>(b"Center body name: Earth (399)\n$$SOE\n$$EOE".to_vec())
+        });
+        assert!(checked.fetch(&query).is_ok());
+        assert!(matches!(
+            checked.with_echo_check().fetch(&query),
+            Err(ClientError::MismatchedEcho(_))
+        ));
+
+        // Too many epochs for a URL
+        let epochs = (0..2000).map(|day| start + ChronoDuration::days(day));
+        let long = state_vectors(MajorBody::Mars, MajorBody::Sun, TimeSpec::from_list(epochs))?;
+        let posted = RefCell::new(None);
+        let client = Client::new(|url: &str, body: Option<&[u8]>| {
+            posted.replace(Some((url.to_owned(), body.map(<[u8]>::to_vec))));
+            Ok::<_, &str>(Vec::new())
+        });
+
+        client.fetch(&long)?;
+        assert_eq!(
+            Some((ApiEndpoint::HORIZONS_FILE.to_owned(), Some(long.to_form()))),
+            posted.into_inner()
+        );
+
+        // Served from the cache the second time
+        let dir = std::env::temp_dir().join(format!("horizons-client-{}", std::process::id()));
+        let sends = Cell::new(0);
+        let cached = Client::new(|_: &str, _: Option<&[u8]>| {
+            sends.set(sends.get() + 1);
+            Ok::<_, &str>(b"$$SOE\n$$EOE".to_vec())
+        })
+        .with_cache(DiskCache::new(&dir, 1 << 20));
+
+        assert_eq!(cached.fetch(&query)?, cached.fetch(&query)?);
+        assert_eq!(1, sends.get());
+        std::fs::remove_dir_all(&dir)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fetch_vectors() -> TestResult {
+        let start = Utc.with_ymd_and_hms(2022, 8, 28, 0, 0, 0).unwrap();
+        let query = state_vectors(
+            MajorBody::Jupiter,
+            MajorBody::SolarSystemBary,
+            TimeSpec::bounded(
+                (1, StepSizeUnit::Days),
+                start,
+                start + ChronoDuration::days(2),
+            ),
+        )?;
+
+        // Each part starts on the day the one before ends
+        let sent = RefCell::new(Vec::new());
+        let client = Client::new(|url: &str, _: Option<&[u8]>| {
+            let day = f64::from(u32::try_from(sent.borrow().len()).unwrap_or_default());
+            sent.borrow_mut().push(url.to_owned());
+
+            let records = [day, day + 1.]
+                .iter()
+                .map(|offset| {
+                    format!(
+                        "{} = A.D. 2022-Aug-28 00:00:00.0000 TDB\n X = 1.0E+00 Y = 2.0E+00 Z = 3.0E+00\n",
+                        2_459_819.5 + offset
+                    )
+                })
+                .collect::<Vec<_>>()
+                .concat();
+            Ok::<_, &str>(format!("$$SOE\n{records}$$EOE\n").into_bytes())
+        });
+
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let reported = Arc::clone(&reports);
+        let client = client.with_progress(move |progress| {
+            reported.lock().unwrap().push(progress);
+        });
+
+        let table = client.fetch_vectors(&query, 2)?;
+        assert_eq!(2, sent.borrow().len());
+        assert_eq!(
+            vec![(1, 2), (2, 2)],
+            reports
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|progress| (progress.completed, progress.total))
+                .collect::<Vec<_>>()
+        );
+        let bytes: Vec<u64> = reports.lock().unwrap().iter().map(|p| p.bytes).collect();
+        assert!(0 < bytes[0] && bytes[0] < bytes[1], "{bytes:?}");
+        assert_eq!(
+            vec![2_459_819.5, 2_459_820.5, 2_459_821.5],
+            table
+                .records
+                .iter()
+                .map(|record| record.jd)
+                .collect::<Vec<_>>()
+        );
+
+        // Short enough for one request
+        client.fetch_vectors(&query, 3)?;
+        assert_eq!(3, sent.borrow().len());
+        assert_eq!(
+            Some((1, 1)),
+            reports
+                .lock()
+                .unwrap()
+                .last()
+                .map(|progress| (progress.completed, progress.total))
+        );
+
+        Ok(())
+    }
+
+    /// Records the proxy of each request
+    struct Proxied(RefCell<Vec<Option<String>>>);
+
+    impl HttpTransport for Proxied {
+        type Error = &'static str;
+
+        fn send(&self, _: &str, _: Option<&[u8]>) -> Result<Vec<u8>, Self::Error> {
+            self.0.borrow_mut().push(None);
+            Ok(Vec::new())
+        }
+
+        fn send_via(
+            &self,
+            proxy: &Proxy,
+            _: &str,
+            _: Option<&[u8]>,
+        ) -> Option<Result<Vec<u8>, Self::Error>> {
+            self.0.borrow_mut().push(Some(proxy.url().to_owned()));
+            Some(Ok(Vec::new()))
+        }
+    }
+
+    /// Streams the body given to it
+    struct Streaming(&'static str);
+
+    impl HttpTransport for Streaming {
+        type Error = &'static str;
+
+        fn send(&self, _: &str, _: Option<&[u8]>) -> Result<Vec<u8>, Self::Error> {
+            Err("should stream")
+        }
+
+        fn send_streaming(
+            &self,
+            _: &str,
+            _: Option<&[u8]>,
+        ) -> Option<Result<Box<dyn Read>, Self::Error>> {
+            Some(Ok(Box::new(self.0.as_bytes())))
+        }
+    }
+
+    #[test]
+    fn test_fetch_stream() -> TestResult {
+        let query = state_vectors(
+            MajorBody::Jupiter,
+            MajorBody::SolarSystemBary,
+            TimeSpec::from_list([Utc.with_ymd_and_hms(2022, 8, 28, 0, 0, 0).unwrap()]),
+        )?;
+        let expected = VectorTable::parse(JUPITER_SSB)?.records;
+
+        let streamed = Client::new(Streaming(JUPITER_SSB)).fetch_stream(&query)?;
+        assert_eq!(expected, streamed.collect::<Result<Vec<_>, _>>()?);
+
+        // Buffered by transports that cannot stream
+        let buffered =
+            Client::new(|_: &str, _: Option<&[u8]>| Ok::<_, &str>(JUPITER_SSB.as_bytes().to_vec()));
+        let records = buffered.fetch_stream(&query)?;
+        assert_eq!(expected, records.collect::<Result<Vec<_>, _>>()?);
+
+        let busy = Client::new(Streaming("Query queue full. Try again later.\n"));
+        assert!(matches!(
+            busy.fetch_stream(&query),
+            Err(error @ ClientError::ServerBusy(_)) if error.is_retryable()
+        ));
+
+        Ok(())
+    }
+
+    /// Streams each downloaded file in two parts
+    struct Staging(RefCell<Vec<String>>);
+
+    impl HttpTransport for Staging {
+        type Error = &'static str;
+
+        fn send(&self, _: &str, _: Option<&[u8]>) -> Result<Vec<u8>, Self::Error> {
+            Err("should stream")
+        }
+
+        fn send_streaming(
+            &self,
+            url: &str,
+            _: Option<&[u8]>,
+        ) -> Option<Result<Box<dyn Read>, Self::Error>> {
+            self.0.borrow_mut().push(url.to_owned());
+            Some(Ok(Box::new(b"DAF/".chain(&b"SPK"[..]))))
+        }
+    }
+
+    #[test]
+    fn test_download_staged() -> TestResult {
+        let response = "
+ Retrieve the files within one hour from
+   ftp://ssd.jpl.nasa.gov/pub/ssd/wld1.bsp
+   ftp://ssd.jpl.nasa.gov/pub/ssd/wld2.bsp
+";
+
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let reported = Arc::clone(&reports);
+        let client = Client::new(Staging(RefCell::new(Vec::new()))).with_progress(move |p| {
+            reported
+                .lock()
+                .unwrap()
+                .push((p.bytes, p.completed, p.total));
+        });
+
+        let files = client.download_staged(response)?;
+        assert_eq!(
+            vec![
+                "https://ssd.jpl.nasa.gov/ftp/ssd/wld1.bsp",
+                "https://ssd.jpl.nasa.gov/ftp/ssd/wld2.bsp",
+            ],
+            *client.transport.0.borrow()
+        );
+        assert_eq!(2, files.len());
+        assert_eq!("wld2.bsp", files[1].file.name());
+        assert_eq!(b"DAF/SPK".to_vec(), files[1].data);
+
+        // As each part arrives, then once each file is complete
+        assert_eq!(
+            vec![
+                (4, 0, 2),
+                (7, 0, 2),
+                (7, 1, 2),
+                (11, 1, 2),
+                (14, 1, 2),
+                (14, 2, 2)
+            ],
+            *reports.lock().unwrap()
+        );
+
+        // Whole bodies from transports that cannot stream
+        let client = Client::new(|url: &str, _: Option<&[u8]>| Ok::<_, &str>(url.into()));
+        let files = client.download_staged(response)?;
+        assert_eq!(
+            b"https://ssd.jpl.nasa.gov/ftp/ssd/wld1.bsp".to_vec(),
+            files[0].data
+        );
+
+        assert!(client.download_staged("$$SOE\n$$EOE")?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_proxy() -> TestResult {
+        let proxy = Proxy::new("http://proxy.example.edu:3128").bypass(["localhost", ".nasa.gov"]);
+
+        assert!(proxy.applies_to("https://example.org/api"));
+        assert!(!proxy.applies_to("http://localhost:8080/api/horizons.api"));
+        assert!(!proxy.applies_to("https://ssd.jpl.nasa.gov/api/horizons.api?format=text"));
+        assert!(proxy.applies_to("https://notnasa.gov/"));
+        assert!(!Proxy::new("http://proxy")
+            .bypass(["*"])
+            .applies_to("https://a.b"));
+
+        let query = state_vectors(
+            MajorBody::Mars,
+            MajorBody::Sun,
+            TimeSpec::from_list([Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap()]),
+        )?;
+
+        let client = Client::new(Proxied(RefCell::new(Vec::new())))
+            .with_base_url("https://mirror.example.org/")
+            .with_proxy(proxy);
+        assert_eq!(
+            "https://mirror.example.org/api/horizons.api",
+            client.endpoint().as_str()
+        );
+        assert_eq!(
+            "https://mirror.example.org/api/horizons_file.api",
+            client.file_endpoint().as_str()
+        );
+        assert_eq!(
+            "https://mirror.example.org/api/horizons_lookup.api",
+            client.lookup_endpoint().as_str()
+        );
+
+        client.fetch(&query)?;
+        let local = client.with_base_url("http://localhost:8080");
+        local.fetch(&query)?;
+        assert_eq!(
+            vec![Some("http://proxy.example.edu:3128".to_owned()), None],
+            local.transport.0.into_inner()
+        );
+
+        // Closures cannot use proxies
+        let closure = Client::new(|_: &str, _: Option<&[u8]>| Ok::<_, &str>(Vec::new()))
+            .with_proxy(Proxy::new("http://proxy"));
+        assert!(matches!(
+            closure.fetch(&query),
+            Err(ClientError::ProxyUnsupported)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_disambiguation() -> TestResult {
+        let query = state_vectors(
+            MajorBody::Mars,
+            MajorBody::Sun,
+            TimeSpec::from_list([Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap()]),
+        )?;
+
+        let sent = RefCell::new(Vec::new());
+        let server = |url: &str, _: Option<&[u8]>| {
+            sent.borrow_mut().push(url.to_owned());
+            let body: &[u8] = if url.starts_with(ApiEndpoint::HORIZONS_LOOKUP) {
+                br#"{"count":2,"result":[
+                {"name":"Jupiter Barycenter","type":"planet","pdes":null,"spkid":"5","alias":[]},
+                {"name":"Jupiter","type":"planet","pdes":null,"spkid":"599","alias":[]}]}"#
+            } else {
+                b"$$SOE\n$$EOE"
+            };
+            Ok::<_, &str>(body.to_vec())
+        };
+
+        assert!(matches!(
+            Client::new(&server).fetch_named("Jupiter", &query),
+            Err(ClientError::Lookup(LookupError::Ambiguous(2)))
+        ));
+
+        let picked = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&picked);
+        let client = Client::new(&server).with_disambiguation(move |matches| {
+            seen.lock()
+                .unwrap()
+                .extend(matches.iter().map(|m| m.spk_id));
+            matches.last().map(LookupMatch::command)
+        });
+
+        sent.borrow_mut().clear();
+        assert_eq!("$$SOE\n$$EOE", client.fetch_named("Jupiter", &query)?);
+        assert_eq!(vec![5, 599], *picked.lock().unwrap());
+
+        let sent = sent.into_inner();
+        assert_eq!(
+            format!("{}?sstr=Jupiter&format=json", ApiEndpoint::HORIZONS_LOOKUP),
+            sent[0]
+        );
+        assert!(sent[1].contains("command=599&"), "{}", sent[1]);
+
+        // Picked among the candidates of an ambiguous fetch and sent again
+        let query = state_vectors(
+            Body::Custom("Mars*".to_owned()),
+            MajorBody::Sun,
+            TimeSpec::from_list([Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap()]),
+        )?;
+
+        let sent = RefCell::new(Vec::new());
+        let server = |url: &str, _: Option<&[u8]>| {
+            sent.borrow_mut().push(url.to_owned());
+            let body: &[u8] = if url.contains("command=499&") {
+                b"$$SOE\n$$EOE"
+            } else {
+                b"
+ Multiple major-bodies match string \"MARS*\"
+
+  ID#      Name                               Designation  IAU/aliases/other
+  -------  ---------------------------------- -----------  -------------------
+        4  Mars Barycenter
+      499  Mars
+
+   Number of matches =   2. Use ID# to make unique selection.
+"
+            };
+            Ok::<_, &str>(body.to_vec())
+        };
+
+        assert!(matches!(
+            Client::new(&server).fetch(&query),
+            Err(ClientError::Ambiguous(matches)) if matches.len() == 2
+        ));
+
+        let client = Client::new(&server).with_disambiguation(|matches| {
+            matches
+                .iter()
+                .find(|m| m.name == "Mars")
+                .map(LookupMatch::command)
+        });
+
+        sent.borrow_mut().clear();
+        assert_eq!("$$SOE\n$$EOE", client.fetch(&query)?);
+        assert_eq!(2, sent.borrow().len());
+
+        let client = Client::new(&server).with_disambiguation(|_| None);
+        assert!(matches!(
+            client.fetch(&query),
+            Err(ClientError::Ambiguous(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_records() -> TestResult {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let query = state_vectors(
+            MajorBody::Mars,
+            MajorBody::Sun,
+            TimeSpec::bounded(
+                (10, StepSizeUnit::Minutes),
+                start,
+                start + ChronoDuration::days(10),
+            ),
+        )?;
+
+        let sends = Cell::new(0);
+        let server = |_: &str, _: Option<&[u8]>| {
+            sends.set(sends.get() + 1);
+            Ok::<_, &str>(JUPITER_SSB.as_bytes().to_vec())
+        };
+
+        let limited = Client::new(&server).with_max_records(1000);
+        let too_large = TooLarge {
+            estimated: 1441,
+            max: 1000,
+        };
+
+        assert!(matches!(
+            limited.fetch(&query),
+            Err(ClientError::TooLarge(error)) if error == too_large
+        ));
+        assert!(matches!(
+            limited.fetch_stream(&query),
+            Err(ClientError::TooLarge(error)) if error == too_large
+        ));
+        assert!(matches!(
+            limited.fetch_vectors(&query, 100),
+            Err(ClientError::TooLarge(error)) if error == too_large
+        ));
+        assert_eq!(0, sends.get());
+
+        Client::new(&server).with_max_records(1441).fetch(&query)?;
+        assert_eq!(1, sends.get());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_retry() -> TestResult {
+        let query = state_vectors(
+            MajorBody::Mars,
+            MajorBody::Sun,
+            TimeSpec::from_list([Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap()]),
+        )?;
+
+        // Busy for the first two requests
+        let sent = Cell::new(0);
+        let server = |_: &str, _: Option<&[u8]>| {
+            sent.set(sent.get() + 1);
+            let body: &[u8] = if sent.get() <= 2 {
+                b"Query queue full -- try again later"
+            } else {
+                b"$$SOE\n$$EOE"
+            };
+            Ok::<_, &str>(body.to_vec())
+        };
+
+        let once = Client::new(&server);
+        assert!(matches!(
+            once.fetch(&query),
+            Err(error @ ClientError::ServerBusy(_)) if error.is_retryable()
+        ));
+
+        let patient = Client::new(&server).with_retry(RetryPolicy::new(3, Duration::ZERO));
+        assert_eq!("$$SOE\n$$EOE", patient.fetch(&query)?);
+        assert_eq!(3, sent.get());
+
+        sent.set(0);
+        let impatient = Client::new(&server).with_retry(RetryPolicy::new(2, Duration::ZERO));
+        assert!(impatient.fetch(&query).is_err());
+        assert_eq!(2, sent.get());
+
+        let policy = RetryPolicy::new(4, Duration::from_secs(1));
+        assert_eq!(
+            [Some(1), Some(2), Some(4), None],
+            [1, 2, 3, 4].map(|attempt| policy.delay(attempt).map(|delay| delay.as_secs()))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rate_limiter() -> TestResult {
+        let limiter = RateLimiter::new(2., 2)?;
+        let shared = limiter.clone();
+        let now = Instant::now();
+
+        assert_eq!(Duration::ZERO, limiter.reserve(now));
+        assert_eq!(Duration::ZERO, shared.reserve(now));
+        assert_eq!(Duration::from_millis(500), limiter.reserve(now));
+        assert_eq!(Duration::from_secs(1), shared.reserve(now));
+
+        // Two seconds at 2 per second repay the two reservations above and refill the
+        // bucket
+        let later = now + Duration::from_secs(2);
+        assert_eq!(Duration::ZERO, limiter.reserve(later));
+        assert_eq!(Duration::ZERO, limiter.reserve(later));
+        assert_eq!(Duration::from_millis(500), limiter.reserve(later));
+
+        for rate in [0., -1., f64::NAN, f64::INFINITY] {
+            assert!(matches!(RateLimiter::new(rate, 1), Err(InvalidRate(_))));
+        }
+
+        // A day for the second request, rather than about 3e292 years
+        let slow = RateLimiter::new(1e-300, 1)?;
+        assert_eq!(Duration::ZERO, slow.reserve(now));
+        assert_eq!(MAX_WAIT, slow.reserve(now));
+
+        Ok(())
+    }
+}
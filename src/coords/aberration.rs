@@ -0,0 +1,95 @@
+//! Annual aberration, from the classical formulae with the constant of aberration
+//! κ = 20.49552" (Meeus, Astronomical Algorithms, ch. 23).
+
+use chrono::{DateTime, Utc};
+
+use super::{centuries_since_j2000, nutation::true_obliquity, Equatorial};
+
+/// Constant of aberration, in arcseconds
+const KAPPA: f64 = 20.495_52;
+
+/// Shift in (RA, Dec) due to annual aberration at `coord`, in arcseconds
+pub fn annual(coord: Equatorial, epoch: &DateTime<Utc>) -> (f64, f64) {
+    let t = centuries_since_j2000(epoch);
+
+    // Eccentricity and longitude of perihelion of the Earth's orbit
+    let e = 0.016_708_634 - t * (0.000_042_037 + t * 0.000_000_126_7);
+    let pi = (102.937_35 + t * (1.719_46 + t * 0.000_46)).to_radians();
+
+    // True geometric longitude of the Sun
+    let l0 = 280.466_46 + t * (36_000.769_83 + t * 0.000_303_2);
+    let m = (357.529_11 + t * (35_999.050_29 - t * 0.000_153_7)).to_radians();
+    let c = (1.914_602 - t * (0.004_817 + t * 0.000_014)) * m.sin()
+        + (0.019_993 - t * 0.000_101) * (2. * m).sin()
+        + 0.000_289 * (3. * m).sin();
+    let sun = (l0 + c).to_radians();
+
+    let eps = (true_obliquity(epoch) / 3600.).to_radians();
+    let (ra, dec) = (coord.ra.to_radians(), coord.dec.to_radians());
+
+    let d_ra = (-KAPPA * (ra.cos() * sun.cos() * eps.cos() + ra.sin() * sun.sin())
+        + e * KAPPA * (ra.cos() * pi.cos() * eps.cos() + ra.sin() * pi.sin()))
+        / dec.cos();
+
+    let d_dec = -KAPPA
+        * (sun.cos() * eps.cos() * (eps.tan() * dec.cos() - ra.sin() * dec.sin())
+            + ra.cos() * dec.sin() * sun.sin())
+        + e * KAPPA
+            * (pi.cos() * eps.cos() * (eps.tan() * dec.cos() - ra.sin() * dec.sin())
+                + ra.cos() * dec.sin() * pi.sin());
+
+    (d_ra, d_dec)
+}
+
+/// Adds annual aberration to geometric coordinates
+pub fn apply(coord: Equatorial, epoch: &DateTime<Utc>) -> Equatorial {
+    let (d_ra, d_dec) = annual(coord, epoch);
+
+    Equatorial::new(
+        (coord.ra + d_ra / 3600.).rem_euclid(360.),
+        coord.dec + d_dec / 3600.,
+    )
+}
+
+/// Removes annual aberration from apparent coordinates
+pub fn remove(coord: Equatorial, epoch: &DateTime<Utc>) -> Equatorial {
+    // The shift depends on the geometric position itself; each iteration shrinks the
+    // error by a factor of ~10⁴, so a few are plenty
+    let mut geometric = coord;
+
+    for _ in 0..3 {
+        let (d_ra, d_dec) = annual(geometric, epoch);
+
+        geometric = Equatorial::new(
+            (coord.ra - d_ra / 3600.).rem_euclid(360.),
+            coord.dec - d_dec / 3600.,
+        );
+    }
+
+    geometric
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::coords::{aberration, nutation, Equatorial};
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_apparent_place() {
+        // Meeus, Astronomical Algorithms, example 23.a (theta Persei)
+        let epoch = Utc.with_ymd_and_hms(2028, 11, 13, 4, 33, 36).unwrap();
+        let mean = Equatorial::new(41.547_214, 49.348_483);
+
+        let (d_ra, d_dec) = aberration::annual(mean, &epoch);
+        assert!((d_ra - 30.045).abs() < 0.01, "{d_ra}");
+        assert!((d_dec - 6.697).abs() < 0.01, "{d_dec}");
+
+        let apparent = aberration::apply(nutation::mean_to_true(mean, &epoch), &epoch);
+        assert!((apparent.ra - 41.559_958).abs() < 1e-4, "{apparent:?}");
+        assert!((apparent.dec - 49.352_069).abs() < 1e-4, "{apparent:?}");
+
+        let back = nutation::true_to_mean(aberration::remove(apparent, &epoch), &epoch);
+        assert!((back.ra - mean.ra).abs() < 1e-8, "{back:?}");
+        assert!((back.dec - mean.dec).abs() < 1e-8, "{back:?}");
+    }
+}
@@ -0,0 +1,144 @@
+//! The constellation containing a position, from the IAU boundaries of Delporte (1930) as
+//! tabulated by Roman (1987, PASP 99, 695).
+//!
+//! The boundaries are not embedded in the crate: load them from `data.dat` of the CDS
+//! catalogue VI/42 with [`Boundaries::parse`]. The boundaries run along the meridians and
+//! parallels of B1875.0, so positions are precessed to that equinox before the lookup.
+
+#![allow(clippy::missing_errors_doc)]
+
+use thiserror::Error;
+
+use super::{precession, Equatorial};
+use crate::request::ephemeris::from_julian_date;
+
+/// B1875.0, as a Julian date
+const B1875: f64 = 2_405_889.258_550_475;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Invalid constellation boundary line `{0}`")]
+pub struct InvalidBoundaryLine(pub String);
+
+/// A band of right ascension above a declination, in B1875.0 coordinates
+#[derive(Debug, Clone, PartialEq)]
+struct Zone {
+    /// Hours
+    ra_low: f64,
+    /// Hours
+    ra_high: f64,
+    /// Degrees
+    dec_low: f64,
+    constellation: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Boundaries {
+    /// By decreasing lower declination, so that the first zone containing a position is
+    /// the one it is in
+    zones: Vec<Zone>,
+}
+
+impl Boundaries {
+    /// Reads lines of lower and upper right ascension in hours, lower declination in
+    /// degrees and constellation abbreviation, e.g. ` 0.0000 24.0000  88.0000 UMi`
+    pub fn parse(text: &str) -> Result<Self, InvalidBoundaryLine> {
+        let mut zones = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let invalid = || InvalidBoundaryLine(line.to_owned());
+                let mut fields = line.split_whitespace();
+                let mut number = || -> Result<f64, InvalidBoundaryLine> {
+                    fields
+                        .next()
+                        .and_then(|f| f.parse().ok())
+                        .ok_or_else(invalid)
+                };
+
+                let (ra_low, ra_high, dec_low) = (number()?, number()?, number()?);
+                let constellation = fields.next().ok_or_else(invalid)?.to_owned();
+
+                Ok(Zone {
+                    ra_low,
+                    ra_high,
+                    dec_low,
+                    constellation,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Stable, to keep the order of zones with the same lower declination
+        zones.sort_by(|a, b| b.dec_low.total_cmp(&a.dec_low));
+
+        Ok(Self { zones })
+    }
+
+    /// Abbreviation of the constellation containing ICRF (J2000) coordinates, or `None`
+    /// if the boundaries do not cover the position
+    pub fn constellation(&self, coord: Equatorial) -> Option<&str> {
+        let epoch = from_julian_date(B1875)?;
+
+        self.constellation_b1875(precession::from_j2000(coord, &epoch))
+    }
+
+    /// Abbreviation of the constellation containing coordinates referred to the mean
+    /// equinox of B1875.0
+    pub fn constellation_b1875(&self, coord: Equatorial) -> Option<&str> {
+        let ra = coord.ra.rem_euclid(360.) / 15.;
+
+        self.zones
+            .iter()
+            .find(|zone| coord.dec >= zone.dec_low && zone.ra_low <= ra && ra < zone.ra_high)
+            .map(|zone| zone.constellation.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        coords::{
+            constellation::{Boundaries, InvalidBoundaryLine},
+            Equatorial,
+        },
+        TestResult,
+    };
+
+    /// The northernmost zones of Roman's table
+    const NORTH: &str = " 0.0000 24.0000  88.0000 UMi
+ 8.0000 14.5000  86.5000 UMi
+21.0000 23.0000  86.1667 UMi
+18.0000 21.0000  86.0000 UMi
+ 0.0000  8.0000  85.0000 CEP
+";
+
+    #[test]
+    fn test_constellation() -> TestResult {
+        let boundaries = Boundaries::parse(NORTH)?;
+
+        // Polaris
+        assert_eq!(
+            Some("UMi"),
+            boundaries.constellation(Equatorial::new(37.954_56, 89.264_11))
+        );
+
+        assert_eq!(
+            Some("UMi"),
+            boundaries.constellation_b1875(Equatorial::new(150., 87.))
+        );
+        assert_eq!(
+            Some("CEP"),
+            boundaries.constellation_b1875(Equatorial::new(30., 86.))
+        );
+        assert_eq!(
+            None,
+            boundaries.constellation_b1875(Equatorial::new(150., 86.))
+        );
+
+        assert_eq!(
+            Err(InvalidBoundaryLine(" 0.0000 UMi".to_owned())),
+            Boundaries::parse(" 0.0000 UMi")
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+//! Galactic and supergalactic coordinates of ICRF positions, e.g. to keep survey fields
+//! away from the galactic plane.
+//!
+//! Galactic coordinates use the ICRS orientation of the Hipparcos catalogue (ESA 1997), and
+//! supergalactic coordinates the definition of de Vaucouleurs et al. (1991).
+
+use super::{apply, transpose, Equatorial, Matrix3};
+
+/// Rotation from the ICRS to galactic coordinates
+const ICRS_TO_GALACTIC: Matrix3 = [
+    [
+        -0.054_875_560_416_215_4,
+        -0.873_437_090_234_885,
+        -0.483_835_015_548_713_2,
+    ],
+    [
+        0.494_109_427_875_583_7,
+        -0.444_829_629_960_011_2,
+        0.746_982_244_497_219,
+    ],
+    [
+        -0.867_666_149_019_004_7,
+        -0.198_076_373_431_201_5,
+        0.455_983_776_175_066_9,
+    ],
+];
+
+/// Rotation from galactic to supergalactic coordinates
+const GALACTIC_TO_SUPERGALACTIC: Matrix3 = [
+    [-0.735_742_574_804_374_9, 0.677_261_296_413_894_3, 0.],
+    [
+        -0.074_553_778_365_233_66,
+        -0.080_991_471_306_976_73,
+        0.993_922_590_398_084_3,
+    ],
+    [
+        0.673_145_302_109_207_6,
+        0.731_271_165_816_964_5,
+        0.110_081_262_224_781_9,
+    ],
+];
+
+/// Galactic longitude and latitude, in degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Galactic {
+    pub l: f64,
+    pub b: f64,
+}
+
+/// Supergalactic longitude and latitude, in degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Supergalactic {
+    pub sgl: f64,
+    pub sgb: f64,
+}
+
+/// Longitude in [0, 360) and latitude of a unit vector, in degrees
+fn angles([x, y, z]: [f64; 3]) -> (f64, f64) {
+    (
+        y.atan2(x).to_degrees().rem_euclid(360.),
+        z.atan2(x.hypot(y)).to_degrees(),
+    )
+}
+
+fn unit_vector(longitude: f64, latitude: f64) -> [f64; 3] {
+    Equatorial::new(longitude, latitude).to_unit_vector()
+}
+
+impl Galactic {
+    /// Of ICRF (J2000) coordinates, such as Horizons' astrometric RA/Dec
+    pub fn from_equatorial(coord: Equatorial) -> Self {
+        let (l, b) = angles(apply(&ICRS_TO_GALACTIC, coord.to_unit_vector()));
+        Self { l, b }
+    }
+
+    pub fn to_equatorial(self) -> Equatorial {
+        let (ra, dec) = angles(apply(
+            &transpose(&ICRS_TO_GALACTIC),
+            unit_vector(self.l, self.b),
+        ));
+        Equatorial::new(ra, dec)
+    }
+
+    pub fn to_supergalactic(self) -> Supergalactic {
+        let (sgl, sgb) = angles(apply(
+            &GALACTIC_TO_SUPERGALACTIC,
+            unit_vector(self.l, self.b),
+        ));
+        Supergalactic { sgl, sgb }
+    }
+}
+
+impl Supergalactic {
+    pub fn from_equatorial(coord: Equatorial) -> Self {
+        Galactic::from_equatorial(coord).to_supergalactic()
+    }
+
+    pub fn to_galactic(self) -> Galactic {
+        let (l, b) = angles(apply(
+            &transpose(&GALACTIC_TO_SUPERGALACTIC),
+            unit_vector(self.sgl, self.sgb),
+        ));
+        Galactic { l, b }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::coords::{
+        galactic::{Galactic, Supergalactic},
+        Equatorial,
+    };
+
+    #[test]
+    fn test_galactic() {
+        // Galactic center and north pole in the ICRS
+        let center = Galactic::from_equatorial(Equatorial::new(266.404_99, -28.936_17));
+        assert!(center.l.min(360. - center.l) < 1e-4 && center.b.abs() < 1e-4);
+
+        let pole = Galactic::from_equatorial(Equatorial::new(192.859_48, 27.128_25));
+        assert!((pole.b - 90.).abs() < 1e-4);
+
+        let vega = Equatorial::new(279.234_73, 38.783_69);
+        let galactic = Galactic::from_equatorial(vega);
+        assert!((galactic.l - 67.448).abs() < 1e-3 && (galactic.b - 19.237).abs() < 1e-3);
+
+        let back = galactic.to_equatorial();
+        assert!((back.ra - vega.ra).abs() < 1e-9 && (back.dec - vega.dec).abs() < 1e-9);
+
+        // The supergalactic north pole and origin, in galactic coordinates
+        let pole = Galactic { l: 47.37, b: 6.32 }.to_supergalactic();
+        assert!((pole.sgb - 90.).abs() < 1e-4);
+
+        let origin = Galactic { l: 137.37, b: 0. }.to_supergalactic();
+        assert!(origin.sgl.min(360. - origin.sgl) < 1e-4 && origin.sgb.abs() < 1e-4);
+
+        let round_trip = Supergalactic::from_equatorial(vega).to_galactic();
+        assert!((round_trip.l - galactic.l).abs() < 1e-9);
+    }
+}
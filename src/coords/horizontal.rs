@@ -0,0 +1,273 @@
+//! Azimuth and altitude of apparent equatorial coordinates for an observer on the Earth,
+//! with optional atmospheric refraction, to cross-check Horizons' airless and refracted
+//! Alt/Az.
+//!
+//! Sidereal time follows Meeus (Astronomical Algorithms, ch. 12), with UTC in place of UT1,
+//! and refraction either of the formulae of ch. 16.
+
+use chrono::{DateTime, Utc};
+
+use super::{
+    centuries_since_j2000,
+    nutation::{nutation, true_obliquity},
+    Equatorial,
+};
+use crate::request::ephemeris::julian_date;
+
+/// Altitude in degrees below which no refraction is applied, as the formulae diverge
+const REFRACTION_LIMIT: f64 = -1.;
+
+/// Rotation rate of the Earth relative to the equinox, in degrees per hour
+const SIDEREAL_RATE: f64 = 360.985_647_366_29 / 24.;
+
+/// Azimuth measured from north through east, and altitude, in degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Horizontal {
+    pub azimuth: f64,
+    pub altitude: f64,
+}
+
+/// Conditions at the observer for the refraction formulae
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Atmosphere {
+    /// Degrees Celsius
+    pub temperature: f64,
+    /// Millibars
+    pub pressure: f64,
+}
+
+impl Default for Atmosphere {
+    /// 10 °C and 1010 mbar, the conditions the formulae are given for
+    fn default() -> Self {
+        Self {
+            temperature: 10.,
+            pressure: 1010.,
+        }
+    }
+}
+
+impl Atmosphere {
+    fn scale(self) -> f64 {
+        self.pressure / 1010. * 283. / (273. + self.temperature)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefractionModel {
+    /// Bennett (1982), in terms of the apparent altitude; accurate to 0.07′
+    Bennett,
+    /// Sæmundsson (1986), in terms of the true (airless) altitude; consistent with
+    /// Bennett to 0.1′
+    Saemundsson,
+}
+
+impl RefractionModel {
+    /// Bennett's formula, in degrees
+    fn bennett(apparent: f64) -> f64 {
+        1. / (apparent + 7.31 / (apparent + 4.4)).to_radians().tan() / 60.
+    }
+
+    /// Sæmundsson's formula, in degrees
+    fn saemundsson(altitude: f64) -> f64 {
+        1.02 / (altitude + 10.3 / (altitude + 5.11)).to_radians().tan() / 60.
+    }
+
+    /// Refraction in degrees of light from the true (airless) `altitude` in degrees
+    pub fn refraction(self, altitude: f64, atmosphere: Atmosphere) -> f64 {
+        self.apparent_altitude(altitude, atmosphere) - altitude
+    }
+
+    /// Apparent altitude of a true (airless) `altitude`, both in degrees
+    pub fn apparent_altitude(self, altitude: f64, atmosphere: Atmosphere) -> f64 {
+        if altitude < REFRACTION_LIMIT {
+            return altitude;
+        }
+
+        match self {
+            Self::Saemundsson => altitude + Self::saemundsson(altitude) * atmosphere.scale(),
+            Self::Bennett => fixed_point(altitude, |apparent| {
+                altitude + Self::bennett(apparent) * atmosphere.scale()
+            }),
+        }
+    }
+
+    /// True (airless) altitude of an apparent `altitude`, both in degrees
+    pub fn true_altitude(self, apparent: f64, atmosphere: Atmosphere) -> f64 {
+        if apparent < REFRACTION_LIMIT {
+            return apparent;
+        }
+
+        match self {
+            Self::Bennett => apparent - Self::bennett(apparent) * atmosphere.scale(),
+            Self::Saemundsson => fixed_point(apparent, |altitude| {
+                apparent - Self::saemundsson(altitude) * atmosphere.scale()
+            }),
+        }
+    }
+}
+
+/// Iterates `f` from `x` to a fixed point. Refraction changes by well under a degree per
+/// degree of altitude, so this converges even at the horizon.
+fn fixed_point(mut x: f64, f: impl Fn(f64) -> f64) -> f64 {
+    for _ in 0..50 {
+        let next = f(x);
+        if (next - x).abs() < 1e-12 {
+            return next;
+        }
+        x = next;
+    }
+
+    x
+}
+
+/// Greenwich apparent sidereal time, in degrees
+pub fn sidereal_time(epoch: &DateTime<Utc>) -> f64 {
+    let t = centuries_since_j2000(epoch);
+    let mean = 280.460_618_37
+        + 360.985_647_366_29 * (julian_date(epoch) - 2_451_545.)
+        + t * t * (0.000_387_933 - t / 38_710_000.);
+
+    let equation_of_equinoxes =
+        nutation(epoch).longitude * (true_obliquity(epoch) / 3600.).to_radians().cos() / 3600.;
+
+    (mean + equation_of_equinoxes).rem_euclid(360.)
+}
+
+/// Local hour angle of `coord` at east `longitude`, in radians
+fn hour_angle(coord: Equatorial, epoch: &DateTime<Utc>, longitude: f64) -> f64 {
+    (sidereal_time(epoch) + longitude - coord.ra).to_radians()
+}
+
+/// Parallactic angle in degrees of apparent equatorial coordinates of date, for an
+/// observer at east `longitude` and `latitude` in degrees: the angle at the target from
+/// the direction of the celestial pole to that of the zenith, positive west of the meridian
+pub fn parallactic_angle(
+    coord: Equatorial,
+    epoch: &DateTime<Utc>,
+    longitude: f64,
+    latitude: f64,
+) -> f64 {
+    let hour_angle = hour_angle(coord, epoch, longitude);
+    let (dec, lat) = (coord.dec.to_radians(), latitude.to_radians());
+
+    hour_angle
+        .sin()
+        .atan2(lat.tan() * dec.cos() - dec.sin() * hour_angle.cos())
+        .to_degrees()
+}
+
+/// Rate at which the field of an alt-az telescope rotates while tracking `coord`, in
+/// degrees per hour, i.e. the rate of change of the [`parallactic_angle`]. It grows without
+/// bound towards the zenith.
+pub fn field_rotation_rate(
+    coord: Equatorial,
+    epoch: &DateTime<Utc>,
+    longitude: f64,
+    latitude: f64,
+) -> f64 {
+    let Horizontal { azimuth, altitude } =
+        Horizontal::from_equatorial(coord, epoch, longitude, latitude);
+
+    -SIDEREAL_RATE * latitude.to_radians().cos() * azimuth.to_radians().cos()
+        / altitude.to_radians().cos()
+}
+
+impl Horizontal {
+    /// Airless position of apparent equatorial coordinates of date (e.g. from
+    /// [`apparent_from_j2000`](super::apparent_from_j2000)), for an observer at geodetic
+    /// east `longitude` and `latitude` in degrees
+    pub fn from_equatorial(
+        coord: Equatorial,
+        epoch: &DateTime<Utc>,
+        longitude: f64,
+        latitude: f64,
+    ) -> Self {
+        let hour_angle = hour_angle(coord, epoch, longitude);
+        let (dec, lat) = (coord.dec.to_radians(), latitude.to_radians());
+
+        let azimuth = (-dec.cos() * hour_angle.sin())
+            .atan2(dec.sin() * lat.cos() - dec.cos() * hour_angle.cos() * lat.sin());
+        let altitude = (lat.sin() * dec.sin() + lat.cos() * dec.cos() * hour_angle.cos()).asin();
+
+        Self {
+            azimuth: azimuth.to_degrees().rem_euclid(360.),
+            altitude: altitude.to_degrees(),
+        }
+    }
+
+    /// The position raised by refraction
+    #[must_use]
+    pub fn refracted(self, model: RefractionModel, atmosphere: Atmosphere) -> Self {
+        Self {
+            altitude: model.apparent_altitude(self.altitude, atmosphere),
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::coords::{
+        horizontal::{
+            field_rotation_rate, parallactic_angle, sidereal_time, Atmosphere, Horizontal,
+            RefractionModel,
+        },
+        Equatorial,
+    };
+
+    #[test]
+    fn test_horizontal() {
+        // Meeus, Astronomical Algorithms, example 13.b (Venus from Washington)
+        let epoch = Utc.with_ymd_and_hms(1987, 4, 10, 19, 21, 0).unwrap();
+        let venus = Equatorial::new(347.319_34, -6.719_892);
+        let position = Horizontal::from_equatorial(venus, &epoch, -77.065_56, 38.921_39);
+
+        assert!((position.azimuth - 248.033_7).abs() < 1e-3, "{position:?}");
+        assert!((position.altitude - 15.124_9).abs() < 1e-3, "{position:?}");
+
+        // Meeus, example 16.a: 28.754′ at an apparent altitude of 0.5°
+        let atmosphere = Atmosphere::default();
+        let bennett = RefractionModel::Bennett;
+        let altitude = bennett.true_altitude(0.5, atmosphere);
+        assert!((0.5 - altitude - 28.754 / 60.).abs() < 1e-4, "{altitude}");
+        assert!((bennett.apparent_altitude(altitude, atmosphere) - 0.5).abs() < 1e-9);
+
+        let saemundsson = RefractionModel::Saemundsson.refraction(altitude, atmosphere);
+        assert!((saemundsson * 60. - 28.754).abs() < 0.1, "{saemundsson}");
+
+        // Colder, denser air refracts more
+        let cold = Atmosphere {
+            temperature: -20.,
+            pressure: 1030.,
+        };
+        assert!(bennett.refraction(10., cold) > bennett.refraction(10., atmosphere));
+        assert!(bennett.refraction(-5., atmosphere).abs() < f64::EPSILON);
+
+        let refracted = position.refracted(bennett, atmosphere);
+        assert!(refracted.altitude > position.altitude);
+        assert!((position.azimuth - refracted.azimuth).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_field_rotation() {
+        let epoch = Utc.with_ymd_and_hms(2023, 3, 1, 4, 0, 0).unwrap();
+        let (longitude, latitude) = (-116.89, 35.43);
+
+        // On the meridian, south of the zenith
+        let transiting = Equatorial::new(sidereal_time(&epoch) + longitude, 10.);
+        assert!(parallactic_angle(transiting, &epoch, longitude, latitude).abs() < 1e-9);
+
+        let coord = Equatorial::new(sidereal_time(&epoch) + longitude - 30., 20.);
+        // Two hours west of the meridian
+        assert!(parallactic_angle(coord, &epoch, longitude, latitude) > 0.);
+
+        let minute = Duration::minutes(1);
+        let rate = (parallactic_angle(coord, &(epoch + minute), longitude, latitude)
+            - parallactic_angle(coord, &(epoch - minute), longitude, latitude))
+            * 30.;
+        let expected = field_rotation_rate(coord, &epoch, longitude, latitude);
+        assert!((rate - expected).abs() < 1e-3, "{rate} {expected}");
+    }
+}
@@ -0,0 +1,125 @@
+//! Client-side transformations of the angular quantities returned by Horizons
+
+#![allow(clippy::must_use_candidate)]
+
+pub mod aberration;
+pub mod constellation;
+pub mod galactic;
+pub mod horizontal;
+pub mod nutation;
+pub mod observability;
+pub mod precession;
+pub mod schedule;
+
+use chrono::{DateTime, Utc};
+
+use crate::request::ephemeris::julian_date;
+
+/// Right ascension and declination, in degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Equatorial {
+    pub ra: f64,
+    pub dec: f64,
+}
+
+impl Equatorial {
+    pub fn new(ra: f64, dec: f64) -> Self {
+        Self { ra, dec }
+    }
+
+    /// Angular distance to `other`, in degrees
+    pub fn separation(self, other: Self) -> f64 {
+        let ([ax, ay, az], [bx, by, bz]) = (self.to_unit_vector(), other.to_unit_vector());
+        let cross = [ay * bz - az * by, az * bx - ax * bz, ax * by - ay * bx];
+        let cross = cross.iter().map(|c| c * c).sum::<f64>().sqrt();
+
+        cross.atan2(ax * bx + ay * by + az * bz).to_degrees()
+    }
+
+    pub(crate) fn to_unit_vector(self) -> [f64; 3] {
+        let (ra, dec) = (self.ra.to_radians(), self.dec.to_radians());
+        [dec.cos() * ra.cos(), dec.cos() * ra.sin(), dec.sin()]
+    }
+
+    fn from_unit_vector([x, y, z]: [f64; 3]) -> Self {
+        Self {
+            ra: y.atan2(x).to_degrees().rem_euclid(360.),
+            dec: z.atan2(x.hypot(y)).to_degrees(),
+        }
+    }
+}
+
+/// Approximates Horizons' apparent RA/Dec from its astrometric (J2000) RA/Dec, by applying
+/// precession, nutation and annual aberration.
+///
+/// Gravitational light deflection and diurnal aberration are not modelled, so expect
+/// differences from Horizons of up to about a second of arc.
+pub fn apparent_from_j2000(coord: Equatorial, epoch: &DateTime<Utc>) -> Equatorial {
+    aberration::apply(
+        nutation::mean_to_true(precession::from_j2000(coord, epoch), epoch),
+        epoch,
+    )
+}
+
+/// Obliquity of the "Ecliptic of J2000.0" frame of Horizons vector and element tables,
+/// in arcseconds
+pub const J2000_OBLIQUITY: f64 = 84_381.448;
+
+/// Rotates a vector from the ICRF to the ecliptic of J2000
+pub fn equatorial_to_ecliptic(v: [f64; 3]) -> [f64; 3] {
+    apply(&rot_x(arcsec_to_rad(J2000_OBLIQUITY)), v)
+}
+
+/// Rotates a vector from the ecliptic of J2000 to the ICRF
+pub fn ecliptic_to_equatorial(v: [f64; 3]) -> [f64; 3] {
+    apply(&rot_x(-arcsec_to_rad(J2000_OBLIQUITY)), v)
+}
+
+type Matrix3 = [[f64; 3]; 3];
+
+fn mul(a: &Matrix3, b: &Matrix3) -> Matrix3 {
+    let mut out = [[0.; 3]; 3];
+
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+
+    out
+}
+
+fn apply(m: &Matrix3, v: [f64; 3]) -> [f64; 3] {
+    [0, 1, 2].map(|i| (0..3).map(|k| m[i][k] * v[k]).sum())
+}
+
+fn transpose(m: &Matrix3) -> Matrix3 {
+    [0, 1, 2].map(|i| [0, 1, 2].map(|j| m[j][i]))
+}
+
+/// Rotation of the coordinate axes about x by `angle` radians
+fn rot_x(angle: f64) -> Matrix3 {
+    let (s, c) = angle.sin_cos();
+    [[1., 0., 0.], [0., c, s], [0., -s, c]]
+}
+
+/// Rotation of the coordinate axes about y by `angle` radians
+fn rot_y(angle: f64) -> Matrix3 {
+    let (s, c) = angle.sin_cos();
+    [[c, 0., -s], [0., 1., 0.], [s, 0., c]]
+}
+
+/// Rotation of the coordinate axes about z by `angle` radians
+fn rot_z(angle: f64) -> Matrix3 {
+    let (s, c) = angle.sin_cos();
+    [[c, s, 0.], [-s, c, 0.], [0., 0., 1.]]
+}
+
+/// Julian centuries since J2000.0
+fn centuries_since_j2000(epoch: &DateTime<Utc>) -> f64 {
+    (julian_date(epoch) - 2_451_545.) / 36_525.
+}
+
+fn arcsec_to_rad(arcsec: f64) -> f64 {
+    (arcsec / 3600.).to_radians()
+}
@@ -0,0 +1,76 @@
+//! Nutation in longitude and obliquity.
+//!
+//! Uses the four largest terms of the IAU 1980 series (Meeus, Astronomical Algorithms,
+//! ch. 22), accurate to about 0.5" in longitude and 0.1" in obliquity.
+
+use chrono::{DateTime, Utc};
+
+use super::{
+    apply, arcsec_to_rad, centuries_since_j2000, mul, rot_x, rot_z, transpose, Equatorial, Matrix3,
+};
+
+/// Nutation angles, in arcseconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Nutation {
+    /// Nutation in longitude, Δψ
+    pub longitude: f64,
+    /// Nutation in obliquity, Δε
+    pub obliquity: f64,
+}
+
+pub fn nutation(epoch: &DateTime<Utc>) -> Nutation {
+    let t = centuries_since_j2000(epoch);
+
+    // Longitude of the Moon's ascending node, and mean longitudes of the Sun and Moon
+    let omega = (125.044_52 - 1_934.136_261 * t).to_radians();
+    let sun = (280.466_5 + 36_000.769_8 * t).to_radians();
+    let moon = (218.316_5 + 481_267.881_3 * t).to_radians();
+
+    Nutation {
+        longitude: -17.20 * omega.sin() - 1.32 * (2. * sun).sin() - 0.23 * (2. * moon).sin()
+            + 0.21 * (2. * omega).sin(),
+        obliquity: 9.20 * omega.cos() + 0.57 * (2. * sun).cos() + 0.10 * (2. * moon).cos()
+            - 0.09 * (2. * omega).cos(),
+    }
+}
+
+/// IAU 2006 mean obliquity of the ecliptic, in arcseconds
+pub fn mean_obliquity(epoch: &DateTime<Utc>) -> f64 {
+    let t = centuries_since_j2000(epoch);
+
+    84_381.406
+        + t * (-46.836_769
+            + t * (-0.000_183_1 + t * (0.002_003_40 + t * (-0.000_000_576 + t * -0.000_000_043_4))))
+}
+
+/// True obliquity of the ecliptic ε₀ + Δε, in arcseconds
+pub fn true_obliquity(epoch: &DateTime<Utc>) -> f64 {
+    mean_obliquity(epoch) + nutation(epoch).obliquity
+}
+
+fn matrix(epoch: &DateTime<Utc>) -> Matrix3 {
+    let Nutation {
+        longitude,
+        obliquity,
+    } = nutation(epoch);
+    let mean = mean_obliquity(epoch);
+
+    mul(
+        &mul(
+            &rot_x(-arcsec_to_rad(mean + obliquity)),
+            &rot_z(-arcsec_to_rad(longitude)),
+        ),
+        &rot_x(arcsec_to_rad(mean)),
+    )
+}
+
+/// Converts coordinates referred to the mean equator and equinox of `epoch` to the true
+/// equator and equinox
+pub fn mean_to_true(coord: Equatorial, epoch: &DateTime<Utc>) -> Equatorial {
+    Equatorial::from_unit_vector(apply(&matrix(epoch), coord.to_unit_vector()))
+}
+
+/// Inverse of [`mean_to_true`]
+pub fn true_to_mean(coord: Equatorial, epoch: &DateTime<Utc>) -> Equatorial {
+    Equatorial::from_unit_vector(apply(&transpose(&matrix(epoch)), coord.to_unit_vector()))
+}
@@ -0,0 +1,277 @@
+//! Airmass and a simple score of how well a target can be observed, to rank targets over a
+//! night from the altitudes and positions of the target, the Sun and the Moon.
+
+use super::Equatorial;
+use crate::response::ephemeris::vectors::EPOCH_TOLERANCE;
+
+/// Separation from the Moon below which [`moon_penalty`] is 1, in degrees
+const MOON_PENALTY_LIMIT: f64 = 10.;
+
+/// Formulae for the relative air mass at an apparent altitude
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AirmassModel {
+    /// Pickering (2002, DIO 12, 1)
+    Pickering,
+    /// Kasten and Young (1989, Applied Optics 28, 4735)
+    KastenYoung,
+}
+
+impl AirmassModel {
+    /// Air mass at the apparent `altitude` in degrees relative to that at the zenith, or
+    /// `None` below the horizon
+    pub fn airmass(self, altitude: f64) -> Option<f64> {
+        if altitude < 0. {
+            return None;
+        }
+
+        Some(match self {
+            Self::Pickering => {
+                1. / (altitude + 244. / (165. + 47. * altitude.powf(1.1)))
+                    .to_radians()
+                    .sin()
+            }
+            Self::KastenYoung => {
+                1. / (altitude.to_radians().sin() + 0.505_72 * (altitude + 6.079_95).powf(-1.636_4))
+            }
+        })
+    }
+}
+
+/// How dark the sky must be, by the altitude of the Sun
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Twilight {
+    /// The Sun below the horizon
+    Sunset,
+    /// The Sun 6° below the horizon
+    Civil,
+    /// The Sun 12° below the horizon
+    Nautical,
+    /// The Sun 18° below the horizon
+    Astronomical,
+}
+
+impl Twilight {
+    /// Highest altitude of the Sun, in degrees
+    pub fn sun_altitude(self) -> f64 {
+        match self {
+            // The upper limb, with standard refraction
+            Self::Sunset => -0.833,
+            Self::Civil => -6.,
+            Self::Nautical => -12.,
+            Self::Astronomical => -18.,
+        }
+    }
+}
+
+/// The circumstances of a target at one epoch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Conditions {
+    /// Apparent altitude of the target, in degrees
+    pub altitude: f64,
+    /// Altitude of the Sun, in degrees
+    pub sun_altitude: f64,
+    /// Angular distance between the target and the Moon, in degrees, e.g. from
+    /// [`Equatorial::separation`](super::Equatorial::separation)
+    pub moon_separation: f64,
+}
+
+/// Limits outside of which a target is not observed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Constraints {
+    pub model: AirmassModel,
+    pub max_airmass: f64,
+    /// Degrees
+    pub min_moon_separation: f64,
+    pub twilight: Twilight,
+}
+
+impl Default for Constraints {
+    /// Air mass up to 2 (30° altitude), 30° from the Moon, in astronomical darkness
+    fn default() -> Self {
+        Self {
+            model: AirmassModel::KastenYoung,
+            max_airmass: 2.,
+            min_moon_separation: 30.,
+            twilight: Twilight::Astronomical,
+        }
+    }
+}
+
+impl Constraints {
+    /// The inverse air mass if all constraints are met, else 0: 1 for a target at the
+    /// zenith, down to `1 / max_airmass`
+    pub fn score(&self, conditions: &Conditions) -> f64 {
+        match self.model.airmass(conditions.altitude) {
+            Some(airmass)
+                if airmass <= self.max_airmass
+                    && conditions.moon_separation >= self.min_moon_separation
+                    && conditions.sun_altitude <= self.twilight.sun_altitude() =>
+            {
+                1. / airmass
+            }
+            _ => 0.,
+        }
+    }
+
+    /// Mean [`score`](Self::score) of each target over the epochs of a night, highest
+    /// first. Targets never observable are left out.
+    pub fn rank<K>(
+        &self,
+        targets: impl IntoIterator<Item = (K, Vec<Conditions>)>,
+    ) -> Vec<(K, f64)> {
+        let mut ranked: Vec<_> = targets
+            .into_iter()
+            .filter_map(|(target, night)| {
+                let total: f64 = night.iter().map(|conditions| self.score(conditions)).sum();
+
+                #[allow(clippy::cast_precision_loss)]
+                (total > 0.).then(|| (target, total / night.len() as f64))
+            })
+            .collect();
+
+        ranked.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        ranked
+    }
+}
+
+/// The Moon at one epoch, as seen by the observer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Moon {
+    /// Degrees
+    pub altitude: f64,
+    /// Illuminated fraction of the disk, from 0 to 1
+    pub illumination: f64,
+}
+
+/// Angular separation between a target and the Moon at each epoch present in both lists
+/// of `(Julian date, position)`, in degrees
+pub fn moon_separations(
+    target: &[(f64, Equatorial)],
+    moon: &[(f64, Equatorial)],
+) -> Vec<(f64, f64)> {
+    target
+        .iter()
+        .filter_map(|&(jd, position)| {
+            let &(_, moon) = moon
+                .iter()
+                .find(|(other, _)| (other - jd).abs() < EPOCH_TOLERANCE)?;
+
+            Some((jd, position.separation(moon)))
+        })
+        .collect()
+}
+
+/// A crude measure from 0 to 1 of how much moonlight brightens the sky at a separation
+/// from the Moon in degrees: the Rayleigh and Mie scattering function of Krisciunas and
+/// Schaefer (1991, PASP 103, 1033) relative to its value 10° from the Moon, scaled by the
+/// illuminated fraction, and 0 when the Moon is down
+pub fn moon_penalty(separation: f64, moon: Moon) -> f64 {
+    fn scattering(separation: f64) -> f64 {
+        10_f64.powf(5.36) * (1.06 + separation.to_radians().cos().powi(2))
+            + 10_f64.powf(6.15 - separation / 40.)
+    }
+
+    if moon.altitude < 0. {
+        return 0.;
+    }
+
+    let relative = scattering(separation.max(MOON_PENALTY_LIMIT)) / scattering(MOON_PENALTY_LIMIT);
+    moon.illumination.clamp(0., 1.) * relative
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::coords::{
+        observability::{
+            moon_penalty, moon_separations, AirmassModel, Conditions, Constraints, Moon,
+        },
+        Equatorial,
+    };
+
+    #[test]
+    fn test_airmass() {
+        for model in [AirmassModel::Pickering, AirmassModel::KastenYoung] {
+            let zenith = model.airmass(90.).unwrap();
+            assert!((zenith - 1.).abs() < 1e-3, "{model:?} {zenith}");
+
+            let thirty = model.airmass(30.).unwrap();
+            assert!((thirty - 2.).abs() < 0.01, "{model:?} {thirty}");
+
+            // About 38 at the horizon, rather than the infinity of the plane-parallel sec z
+            let horizon = model.airmass(0.).unwrap();
+            assert!((35.0..41.).contains(&horizon), "{model:?} {horizon}");
+
+            assert_eq!(None, model.airmass(-1.));
+        }
+
+        let a = Equatorial::new(10., 20.);
+        assert!((a.separation(Equatorial::new(10., 50.)) - 30.).abs() < 1e-9);
+        assert!((a.separation(Equatorial::new(190., -20.)) - 180.).abs() < 1e-9);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_rank() {
+        let constraints = Constraints::default();
+        let at = |altitude, sun_altitude, moon_separation| Conditions {
+            altitude,
+            sun_altitude,
+            moon_separation,
+        };
+
+        assert_eq!(0., constraints.score(&at(60., -10., 90.)));
+        assert_eq!(0., constraints.score(&at(60., -20., 10.)));
+        assert_eq!(0., constraints.score(&at(20., -20., 90.)));
+
+        let ranked = constraints.rank([
+            ("low", vec![at(35., -20., 90.), at(40., -20., 90.)]),
+            ("daytime", vec![at(80., 10., 90.)]),
+            ("high", vec![at(80., -20., 90.), at(85., -20., 90.)]),
+        ]);
+
+        assert_eq!(
+            vec!["high", "low"],
+            ranked.iter().map(|(target, _)| *target).collect::<Vec<_>>()
+        );
+        assert!(ranked[0].1 > 0.98 && ranked[1].1 < 0.7);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_moon_penalty() {
+        let separations = moon_separations(
+            &[
+                (1., Equatorial::new(10., 0.)),
+                (2., Equatorial::new(20., 0.)),
+            ],
+            &[
+                (2., Equatorial::new(50., 0.)),
+                (3., Equatorial::new(60., 0.)),
+            ],
+        );
+        assert_eq!(1, separations.len());
+        assert!((separations[0].1 - 30.).abs() < 1e-9);
+
+        let full = Moon {
+            altitude: 40.,
+            illumination: 1.,
+        };
+        assert_eq!(1., moon_penalty(5., full));
+
+        let near = moon_penalty(30., full);
+        let far = moon_penalty(120., full);
+        assert!(0. < far && far < near && near < 1., "{near} {far}");
+
+        let half = Moon {
+            illumination: 0.5,
+            ..full
+        };
+        assert!((moon_penalty(30., half) - near / 2.).abs() < 1e-12);
+
+        let down = Moon {
+            altitude: -5.,
+            ..full
+        };
+        assert_eq!(0., moon_penalty(30., down));
+    }
+}
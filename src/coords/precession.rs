@@ -0,0 +1,71 @@
+//! IAU 2006 precession of equatorial coordinates.
+//!
+//! Horizons reports astrometric RA/Dec referred to the ICRF (J2000). Mounts and catalogues
+//! using the equinox of date need these positions precessed to the epoch of observation.
+//! The difference between UTC and TT is negligible at this level and is ignored.
+
+use chrono::{DateTime, Utc};
+
+use super::{
+    arcsec_to_rad, centuries_since_j2000, mul, rot_y, rot_z, transpose, Equatorial, Matrix3,
+};
+
+/// Precession matrix from J2000 to the mean equator and equinox of `epoch`,
+/// using the equatorial precession angles of Capitaine et al. (2003) adopted by IAU 2006
+fn matrix(epoch: &DateTime<Utc>) -> Matrix3 {
+    let t = centuries_since_j2000(epoch);
+
+    let zeta = 2.650_545
+        + t * (2_306.083_227
+            + t * (0.298_849_9 + t * (0.018_018_28 + t * (-0.000_005_971 + t * -0.000_000_317_3))));
+    let z = -2.650_545
+        + t * (2_306.077_181
+            + t * (1.092_734_8 + t * (0.018_268_37 + t * (-0.000_028_596 + t * -0.000_000_290_4))));
+    let theta = t
+        * (2_004.191_903
+            + t * (-0.429_493_4
+                + t * (-0.041_822_64 + t * (-0.000_007_089 + t * -0.000_000_127_4))));
+
+    mul(
+        &mul(&rot_z(-arcsec_to_rad(z)), &rot_y(arcsec_to_rad(theta))),
+        &rot_z(-arcsec_to_rad(zeta)),
+    )
+}
+
+/// Precesses J2000 coordinates to the mean equinox of `epoch`
+pub fn from_j2000(coord: Equatorial, epoch: &DateTime<Utc>) -> Equatorial {
+    Equatorial::from_unit_vector(super::apply(&matrix(epoch), coord.to_unit_vector()))
+}
+
+/// Precesses coordinates referred to the mean equinox of `epoch` back to J2000
+pub fn to_j2000(coord: Equatorial, epoch: &DateTime<Utc>) -> Equatorial {
+    Equatorial::from_unit_vector(super::apply(
+        &transpose(&matrix(epoch)),
+        coord.to_unit_vector(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::coords::{
+        precession::{from_j2000, to_j2000},
+        Equatorial,
+    };
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_precession() {
+        // Meeus, Astronomical Algorithms, example 21.b (theta Persei). Meeus uses the IAU 1976
+        // angles, whose precession rate differs from IAU 2006 by ~0.3" per century
+        let epoch = Utc.with_ymd_and_hms(2028, 11, 13, 4, 33, 36).unwrap();
+        let j2000 = Equatorial::new(41.054_063, 49.227_750);
+
+        let of_date = from_j2000(j2000, &epoch);
+        assert!((of_date.ra - 41.547_214).abs() < 5e-5, "{of_date:?}");
+        assert!((of_date.dec - 49.348_483).abs() < 2e-5, "{of_date:?}");
+
+        let back = to_j2000(of_date, &epoch);
+        assert!((back.ra - j2000.ra).abs() < 1e-10, "{back:?}");
+        assert!((back.dec - j2000.dec).abs() < 1e-10, "{back:?}");
+    }
+}
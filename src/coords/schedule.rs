@@ -0,0 +1,235 @@
+//! A greedy scheduler for one night: from the start of the night, repeatedly observe the
+//! remaining target with the best mean [`score`](Constraints::score) over its exposure,
+//! counting the time to slew to it, until no target fits in the rest of the night.
+
+use super::{
+    horizontal::Horizontal,
+    observability::{Conditions, Constraints},
+    Equatorial,
+};
+use crate::response::ephemeris::vectors::EPOCH_TOLERANCE;
+
+const SECONDS_PER_DAY: f64 = 86_400.;
+
+/// A target at one epoch of the night
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub conditions: Conditions,
+    pub position: Horizontal,
+}
+
+/// A target to schedule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request<K> {
+    pub target: K,
+    /// Seconds
+    pub exposure: f64,
+    /// At each epoch of the night
+    pub samples: Vec<Sample>,
+}
+
+/// How quickly the telescope moves between targets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Telescope {
+    /// Degrees per second, along the great circle between the two positions
+    pub slew_rate: f64,
+    /// Seconds from the end of a slew to the start of an exposure
+    pub settle_time: f64,
+}
+
+impl Telescope {
+    /// Seconds to move from `from` to `to` and settle
+    pub fn slew_time(self, from: Horizontal, to: Horizontal) -> f64 {
+        let distance = Equatorial::new(from.azimuth, from.altitude)
+            .separation(Equatorial::new(to.azimuth, to.altitude));
+
+        distance / self.slew_rate + self.settle_time
+    }
+}
+
+/// An exposure of a [`Schedule`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slot<K> {
+    pub target: K,
+    /// Julian date
+    pub start: f64,
+    /// Julian date
+    pub end: f64,
+    /// Mean score over the exposure
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule<K> {
+    /// In order of time
+    pub slots: Vec<Slot<K>>,
+    /// Targets that did not fit
+    pub unscheduled: Vec<K>,
+}
+
+/// Schedules `requests` over the Julian dates `epochs`, at which all requests have
+/// samples. An exposure is only scheduled if all constraints are met at every epoch it
+/// covers, including those just before its start and just after its end.
+pub fn schedule<K>(
+    epochs: &[f64],
+    requests: Vec<Request<K>>,
+    constraints: &Constraints,
+    telescope: Telescope,
+) -> Schedule<K> {
+    let first_index = |jd: f64| {
+        epochs
+            .iter()
+            .position(|&epoch| epoch >= jd - EPOCH_TOLERANCE)
+    };
+
+    let mut remaining = requests;
+    let mut slots = Vec::new();
+    let mut now = epochs.first().copied().unwrap_or_default();
+    let mut pointing: Option<Horizontal> = None;
+
+    while let Some(index) = first_index(now) {
+        let candidates = remaining.iter().enumerate().filter_map(|(i, request)| {
+            let position = request.samples.get(index)?.position;
+            let slew = pointing.map_or(0., |from| telescope.slew_time(from, position));
+            let start = now + slew / SECONDS_PER_DAY;
+            let end = start + request.exposure / SECONDS_PER_DAY;
+
+            // The epochs around the exposure, from the last at or before its start to the
+            // first at or after its end
+            let from = epochs
+                .iter()
+                .rposition(|&epoch| epoch <= start + EPOCH_TOLERANCE)?;
+            let to = first_index(end)?;
+            let scores: Vec<f64> = request
+                .samples
+                .get(from..=to)?
+                .iter()
+                .map(|sample| constraints.score(&sample.conditions))
+                .collect();
+
+            if scores.contains(&0.) {
+                return None;
+            }
+
+            #[allow(clippy::cast_precision_loss)]
+            let score = scores.iter().sum::<f64>() / scores.len() as f64;
+            Some((i, start, end, score))
+        });
+
+        match candidates.max_by(|(.., a), (.., b)| a.total_cmp(b)) {
+            Some((i, start, end, score)) => {
+                let request = remaining.remove(i);
+                pointing = first_index(end)
+                    .and_then(|index| request.samples.get(index))
+                    .map(|sample| sample.position);
+                now = end;
+
+                slots.push(Slot {
+                    target: request.target,
+                    start,
+                    end,
+                    score,
+                });
+            }
+            // Nothing fits now; wait for the next epoch
+            None => match epochs.get(index + 1) {
+                Some(&next) => now = now.max(next),
+                None => break,
+            },
+        }
+    }
+
+    Schedule {
+        slots,
+        unscheduled: remaining
+            .into_iter()
+            .map(|request| request.target)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::coords::{
+        horizontal::Horizontal,
+        observability::{Conditions, Constraints},
+        schedule::{schedule, Request, Sample, Telescope},
+    };
+
+    /// Epochs of one hour from JD 0
+    fn hours(n: u32) -> Vec<f64> {
+        (0..n).map(|i| f64::from(i) / 24.).collect()
+    }
+
+    fn request(target: &'static str, exposure: f64, altitudes: &[f64]) -> Request<&'static str> {
+        Request {
+            target,
+            exposure,
+            samples: altitudes
+                .iter()
+                .map(|&altitude| Sample {
+                    conditions: Conditions {
+                        altitude,
+                        sun_altitude: -30.,
+                        moon_separation: 90.,
+                    },
+                    position: Horizontal {
+                        azimuth: 180.,
+                        altitude,
+                    },
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_schedule() {
+        let epochs = hours(6);
+        let telescope = Telescope {
+            slew_rate: 1.,
+            settle_time: 0.,
+        };
+
+        let plan = schedule(
+            &epochs,
+            vec![
+                // Rises late
+                request("east", 3600., &[10., 10., 10., 50., 60., 70.]),
+                // Sets early
+                request("west", 3600., &[80., 70., 20., 10., 10., 10.]),
+                // Never high enough
+                request("south", 3600., &[20.; 6]),
+                // Longer than the night
+                request("long", 10. * 3600., &[80.; 6]),
+            ],
+            &Constraints::default(),
+            telescope,
+        );
+
+        assert_eq!(
+            vec!["west", "east"],
+            plan.slots
+                .iter()
+                .map(|slot| slot.target)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(vec!["south", "long"], plan.unscheduled);
+
+        assert!(plan.slots[0].start.abs() < 1e-9);
+        // East waits until it is above 30°, then slews 20° from where west ended
+        assert!((plan.slots[1].start - (3. / 24. + 20. / 86_400.)).abs() < 1e-9);
+        assert!(plan.slots.iter().all(|slot| slot.score > 0.5));
+
+        // The slew from 70° to 50° altitude delays the start of the next exposure
+        let plan = schedule(
+            &hours(4),
+            vec![
+                request("first", 3600., &[80., 70., 60., 50.]),
+                request("second", 1800., &[50., 50., 50., 50.]),
+            ],
+            &Constraints::default(),
+            telescope,
+        );
+        assert!((plan.slots[1].start - (1. / 24. + 20. / 86_400.)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,192 @@
+//! Minimal JSON reader for the small documents returned by the Horizons APIs
+
+use std::{iter::Peekable, str::Chars};
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        let mut chars = s.chars().peekable();
+        let value = parse_value(&mut chars)?;
+
+        skip_whitespace(&mut chars);
+        chars.peek().is_none().then_some(value)
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+type Input<'a> = Peekable<Chars<'a>>;
+
+fn skip_whitespace(chars: &mut Input) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn parse_value(chars: &mut Input) -> Option<Json> {
+    skip_whitespace(chars);
+
+    match *chars.peek()? {
+        '{' => parse_object(chars),
+        '[' => parse_array(chars),
+        '"' => parse_string(chars).map(Json::String),
+        't' => parse_literal(chars, "true", Json::Bool(true)),
+        'f' => parse_literal(chars, "false", Json::Bool(false)),
+        'n' => parse_literal(chars, "null", Json::Null),
+        _ => parse_number(chars),
+    }
+}
+
+fn parse_literal(chars: &mut Input, literal: &str, value: Json) -> Option<Json> {
+    literal
+        .chars()
+        .all(|c| chars.next() == Some(c))
+        .then_some(value)
+}
+
+fn parse_number(chars: &mut Input) -> Option<Json> {
+    let mut number = String::new();
+
+    while let Some(c) = chars.next_if(|c| matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E')) {
+        number.push(c);
+    }
+
+    number.parse().ok().map(Json::Number)
+}
+
+fn parse_string(chars: &mut Input) -> Option<String> {
+    if chars.next()? != '"' {
+        return None;
+    }
+
+    let mut out = String::new();
+
+    loop {
+        match chars.next()? {
+            '"' => return Some(out),
+            '\\' => match chars.next()? {
+                'n' => out.push('\n'),
+                't' => out.push('\t'),
+                'r' => out.push('\r'),
+                'b' => out.push('\u{8}'),
+                'f' => out.push('\u{c}'),
+                'u' => {
+                    let code: String = chars.by_ref().take(4).collect();
+                    out.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+                }
+                c => out.push(c),
+            },
+            c => out.push(c),
+        }
+    }
+}
+
+fn parse_array(chars: &mut Input) -> Option<Json> {
+    chars.next();
+    let mut values = Vec::new();
+
+    skip_whitespace(chars);
+    if chars.next_if_eq(&']').is_some() {
+        return Some(Json::Array(values));
+    }
+
+    loop {
+        values.push(parse_value(chars)?);
+        skip_whitespace(chars);
+
+        match chars.next()? {
+            ',' => {}
+            ']' => return Some(Json::Array(values)),
+            _ => return None,
+        }
+    }
+}
+
+fn parse_object(chars: &mut Input) -> Option<Json> {
+    chars.next();
+    let mut fields = Vec::new();
+
+    skip_whitespace(chars);
+    if chars.next_if_eq(&'}').is_some() {
+        return Some(Json::Object(fields));
+    }
+
+    loop {
+        skip_whitespace(chars);
+        let key = parse_string(chars)?;
+
+        skip_whitespace(chars);
+        if chars.next()? != ':' {
+            return None;
+        }
+
+        fields.push((key, parse_value(chars)?));
+        skip_whitespace(chars);
+
+        match chars.next()? {
+            ',' => {}
+            '}' => return Some(Json::Object(fields)),
+            _ => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::Json;
+
+    #[test]
+    fn test_json() {
+        let json = Json::parse(
+            r#" {"count": 2, "ok": true, "none": null,
+                "list": ["a\"b", "é\/", -1.5e3, {}, []]} "#,
+        )
+        .unwrap();
+
+        assert_eq!(Some(&Json::Number(2.)), json.get("count"));
+        assert_eq!(Some(&Json::Bool(true)), json.get("ok"));
+        assert_eq!(Some(&Json::Null), json.get("none"));
+        assert_eq!(
+            Some(
+                &[
+                    Json::String("a\"b".to_owned()),
+                    Json::String("é/".to_owned()),
+                    Json::Number(-1500.),
+                    Json::Object(vec![]),
+                    Json::Array(vec![]),
+                ][..]
+            ),
+            json.get("list").and_then(Json::as_array)
+        );
+
+        assert_eq!(None, Json::parse(r#"{"a": 1"#));
+        assert_eq!(None, Json::parse(r#"{"a": 1} x"#));
+        assert_eq!(None, Json::parse("[1 2]"));
+    }
+}
@@ -1,18 +1,97 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(clippy::pedantic)]
 
+#[cfg(feature = "std")]
+pub mod cache;
+#[cfg(feature = "std")]
+pub mod client;
+#[cfg(feature = "std")]
+pub mod coords;
+#[cfg(feature = "std")]
+pub mod lookup;
+#[cfg(feature = "std")]
+pub mod prelude;
+#[cfg(feature = "std")]
 pub mod request;
 pub mod response;
+#[cfg(feature = "std")]
+pub mod units;
+#[cfg(feature = "std")]
+pub mod vcr;
 
-pub(crate) mod units;
+#[cfg(feature = "std")]
+pub(crate) mod json;
+
+/// An optional Cargo feature of the crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Feature {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Whether the crate was built with the feature
+    pub enabled: bool,
+}
+
+/// The optional features of the crate and whether each is enabled, e.g. for the `--version`
+/// output of a CLI. APIs behind a disabled feature are absent at compile time.
+#[must_use]
+pub fn features() -> &'static [Feature] {
+    &[
+        Feature {
+            name: "std",
+            description: "Everything but the allocation-free `RecordBatch` parser",
+            enabled: cfg!(feature = "std"),
+        },
+        Feature {
+            name: "uom",
+            description: "Dimensioned quantities and Horizons units for the `uom` crate",
+            enabled: cfg!(feature = "uom"),
+        },
+    ]
+}
+
+/// Names of the enabled [`features`], e.g. for bug reports
+pub fn enabled_features() -> impl Iterator<Item = &'static str> {
+    features()
+        .iter()
+        .filter(|feature| feature.enabled)
+        .map(|feature| feature.name)
+}
 
 #[cfg(test)]
 pub(crate) type TestResult<T = ()> = Result<T, Box<dyn std::error::Error>>;
 
+#[cfg(feature = "std")]
 mod macros;
 
+#[cfg(feature = "std")]
 #[allow(clippy::wildcard_imports)]
 pub(crate) use macros::*;
 
 #[cfg(feature = "uom")]
 #[macro_use]
 extern crate uom;
+
+#[cfg(test)]
+mod tests {
+    use crate::{enabled_features, features};
+
+    #[test]
+    fn test_features() {
+        let uom = features()
+            .iter()
+            .find(|feature| feature.name == "uom")
+            .unwrap();
+        assert_eq!(cfg!(feature = "uom"), uom.enabled);
+
+        // The same features as `features`
+        assert_eq!(
+            features()
+                .iter()
+                .filter(|feature| feature.enabled)
+                .map(|feature| feature.name)
+                .collect::<Vec<_>>(),
+            enabled_features().collect::<Vec<_>>()
+        );
+        assert!(enabled_features().any(|name| name == "std"));
+    }
+}
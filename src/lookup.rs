@@ -0,0 +1,306 @@
+//! Types for the Horizons Lookup API (`api/horizons_lookup.api`), which maps names and
+//! designations to SPK-IDs.
+//!
+//! Serialize a [`Lookup`] as the query string of a GET request, then parse the JSON body
+//! with [`LookupResult::from_json`], or let [`Client::resolve`](crate::client::Client::resolve)
+//! do both.
+
+#![allow(clippy::must_use_candidate, clippy::missing_errors_doc)]
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    json::Json,
+    request::{
+        bodies::{MajorBody, Spacecraft, SpkId},
+        params::{query_string, ApiEndpoint},
+        Body, Command,
+    },
+    response::bodies::MajorBodyRecord,
+};
+
+/// Restricts a lookup to one kind of object
+#[repr(u8)]
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LookupGroup {
+    #[serde(rename = "ast")]
+    Asteroids,
+    #[serde(rename = "com")]
+    Comets,
+    #[serde(rename = "pln")]
+    Planets,
+    #[serde(rename = "sat")]
+    Satellites,
+    #[serde(rename = "sct")]
+    Spacecraft,
+    #[serde(rename = "mb")]
+    MajorBodies,
+    #[serde(rename = "sb")]
+    SmallBodies,
+}
+
+impl LookupGroup {
+    /// The value of the `group` parameter
+    fn code(self) -> &'static str {
+        match self {
+            Self::Asteroids => "ast",
+            Self::Comets => "com",
+            Self::Planets => "pln",
+            Self::Satellites => "sat",
+            Self::Spacecraft => "sct",
+            Self::MajorBodies => "mb",
+            Self::SmallBodies => "sb",
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Lookup {
+    sstr: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<LookupGroup>,
+    format: &'static str,
+}
+
+impl Lookup {
+    /// Searches names, designations and SPK-IDs for `search`
+    pub fn new(search: impl Into<String>) -> Self {
+        Self {
+            sstr: search.into(),
+            group: None,
+            format: "json",
+        }
+    }
+
+    pub fn group(&mut self, group: LookupGroup) -> &mut Self {
+        self.group = Some(group);
+        self
+    }
+
+    /// Complete GET URL of the lookup against `base`, usually [`ApiEndpoint::lookup`]
+    pub fn to_url(&self, base: &ApiEndpoint) -> String {
+        let mut params = vec![("sstr".to_owned(), self.sstr.clone())];
+        if let Some(group) = self.group {
+            params.push(("group".to_owned(), group.code().to_owned()));
+        }
+        params.push(("format".to_owned(), self.format.to_owned()));
+
+        let base = base.as_str();
+        let separator = if base.contains('?') { '&' } else { '?' };
+        format!("{base}{separator}{}", query_string(&params))
+    }
+}
+
+impl ApiEndpoint {
+    /// The official Lookup API at JPL
+    pub fn lookup() -> Self {
+        Self::new(Self::HORIZONS_LOOKUP)
+    }
+}
+
+/// One object matching a [`Lookup`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LookupMatch {
+    pub name: String,
+    /// e.g. `asteroid`, `comet`, `planet`, `satellite` or `spacecraft`
+    pub kind: String,
+    /// Primary designation, if any
+    pub designation: Option<String>,
+    pub spk_id: i64,
+    pub aliases: Vec<String>,
+}
+
+impl LookupMatch {
+    /// Converts the match into a query target, preferring the typed bodies
+    pub fn command(&self) -> Command {
+        if let Ok(body) = MajorBody::try_from(self.spk_id) {
+            return body.into();
+        }
+
+        if let Ok(spacecraft) = Spacecraft::try_from(self.spk_id) {
+            return spacecraft.into();
+        }
+
+        match self.small_body_id() {
+            Some(id) => id.into(),
+            None => Body::Custom(self.spk_id.to_string()).into(),
+        }
+    }
+
+    /// The SPK-ID of an asteroid or comet match
+    pub fn small_body_id(&self) -> Option<SpkId> {
+        match self.kind.as_str() {
+            "asteroid" | "comet" => SpkId::try_from(self.spk_id).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// A candidate of an ambiguous ephemeris query, as `major body` or `spacecraft`
+impl From<MajorBodyRecord> for LookupMatch {
+    fn from(record: MajorBodyRecord) -> Self {
+        let kind = if record.code < 0 {
+            "spacecraft"
+        } else {
+            "major body"
+        };
+
+        Self {
+            name: record.name,
+            kind: kind.to_owned(),
+            designation: record.designation,
+            spk_id: record.code,
+            aliases: record
+                .aliases
+                .map(|aliases| aliases.split(',').map(|a| a.trim().to_owned()).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LookupError {
+    #[error("Response is not valid JSON")]
+    InvalidJson,
+    #[error("Invalid match in response: {0}")]
+    InvalidMatch(String),
+    #[error("No object matches the search")]
+    NotFound,
+    #[error("{0} objects match the search")]
+    Ambiguous(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LookupResult {
+    pub matches: Vec<LookupMatch>,
+}
+
+impl LookupResult {
+    pub fn from_json(json: &str) -> Result<Self, LookupError> {
+        let json = Json::parse(json).ok_or(LookupError::InvalidJson)?;
+
+        let matches = match json.get("result").and_then(Json::as_array) {
+            Some(results) => results.iter().map(parse_match).collect::<Result<_, _>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(Self { matches })
+    }
+
+    /// The target of the only match
+    pub fn resolve(&self) -> Result<Command, LookupError> {
+        match self.matches.as_slice() {
+            [] => Err(LookupError::NotFound),
+            [only] => Ok(only.command()),
+            matches => Err(LookupError::Ambiguous(matches.len())),
+        }
+    }
+
+    /// Like [`LookupResult::resolve`], but lets `pick` choose among several matches, e.g.
+    /// by prompting the user of a CLI. The search stays ambiguous if `pick` returns `None`.
+    pub fn resolve_with(
+        &self,
+        pick: impl FnOnce(&[LookupMatch]) -> Option<Command>,
+    ) -> Result<Command, LookupError> {
+        match self.resolve() {
+            Err(LookupError::Ambiguous(count)) => {
+                pick(&self.matches).ok_or(LookupError::Ambiguous(count))
+            }
+            resolved => resolved,
+        }
+    }
+}
+
+fn parse_match(json: &Json) -> Result<LookupMatch, LookupError> {
+    let invalid = |field: &str| LookupError::InvalidMatch(format!("missing `{field}`"));
+    let string = |field| json.get(field).and_then(Json::as_str);
+
+    let spk_id = string("spkid").ok_or_else(|| invalid("spkid"))?;
+
+    Ok(LookupMatch {
+        name: string("name").ok_or_else(|| invalid("name"))?.to_owned(),
+        kind: string("type").ok_or_else(|| invalid("type"))?.to_owned(),
+        designation: string("pdes").map(str::to_owned),
+        spk_id: spk_id
+            .parse()
+            .map_err(|_| LookupError::InvalidMatch(format!("invalid spkid `{spk_id}`")))?,
+        aliases: json
+            .get("alias")
+            .and_then(Json::as_array)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|alias| alias.as_str().map(str::to_owned))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lookup::{Lookup, LookupError, LookupGroup, LookupMatch, LookupResult},
+        request::{
+            bodies::{MajorBody, SmallBody},
+            params::ApiEndpoint,
+            Command,
+        },
+        TestResult,
+    };
+
+    #[test]
+    fn test_lookup_query() -> TestResult {
+        assert_eq!(
+            "sstr=Ceres&group=ast&format=json",
+            serde_urlencoded::to_string(Lookup::new("Ceres").group(LookupGroup::Asteroids))?
+        );
+        assert_eq!(
+            "https://ssd.jpl.nasa.gov/api/horizons_lookup.api?sstr=1+Ceres&group=ast&format=json",
+            Lookup::new("1 Ceres")
+                .group(LookupGroup::Asteroids)
+                .to_url(&ApiEndpoint::lookup())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lookup_result() -> TestResult {
+        let result = LookupResult::from_json(
+            r#"{"signature":{"version":"1.1","source":"NASA/JPL Horizons Lookup API"},
+            "count":1,"result":[{"name":"1 Ceres (A801 AA)","type":"asteroid",
+            "pdes":"1","spkid":"2000001","alias":["A801 AA","A899 OF"]}]}"#,
+        )?;
+
+        assert_eq!(vec!["A801 AA", "A899 OF"], result.matches[0].aliases);
+        assert_eq!(
+            Command::from(SmallBody::designation("2000001")),
+            result.resolve()?
+        );
+
+        let result = LookupResult::from_json(
+            r#"{"count":2,"result":[
+            {"name":"Jupiter Barycenter","type":"planet","pdes":null,"spkid":"5","alias":[]},
+            {"name":"Jupiter","type":"planet","pdes":null,"spkid":"599","alias":[]}]}"#,
+        )?;
+
+        assert_eq!(Err(LookupError::Ambiguous(2)), result.resolve());
+        assert_eq!(
+            Command::from(MajorBody::Jupiter),
+            result.resolve_with(|matches| matches.last().map(LookupMatch::command))?
+        );
+        assert_eq!(
+            Err(LookupError::Ambiguous(2)),
+            result.resolve_with(|_| None)
+        );
+        assert_eq!(
+            Command::from(MajorBody::Jupiter),
+            result.matches[1].command()
+        );
+        assert_eq!(
+            Err(LookupError::NotFound),
+            LookupResult::from_json(r#"{"count":0}"#)?.resolve()
+        );
+
+        Ok(())
+    }
+}
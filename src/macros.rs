@@ -32,4 +32,14 @@ macro_rules! impl_from_for_inner_enum {
     };
 }
 
-pub(crate) use {impl_from_for_inner_enum, impl_from_int_for_enum};
+/// Fails to compile unless every type is `Send + Sync`
+macro_rules! assert_send_sync {
+    ($($ty:ty),* $(,)?) => {
+        const _: fn() = || {
+            fn assert_send_sync<T: Send + Sync>() {}
+            $(assert_send_sync::<$ty>();)*
+        };
+    };
+}
+
+pub(crate) use {assert_send_sync, impl_from_for_inner_enum, impl_from_int_for_enum};
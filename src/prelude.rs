@@ -0,0 +1,15 @@
+//! The types most programs need, for a single `use horizons::prelude::*;`
+
+pub use crate::{
+    client::{Client, HttpTransport},
+    request::{
+        bodies::{MajorBody, SmallBody, Spacecraft},
+        ephemeris::{OutUnits, StepSizeUnit, TimeSpec},
+        params::ApiEndpoint,
+        presets, Center, Command, Query, QueryBuilderError,
+    },
+    response::ephemeris::{
+        vectors::{StateVectorRecord, VectorTable},
+        TableMeta, Vector3D,
+    },
+};
@@ -1,3 +1,13 @@
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    str::FromStr,
+};
+
+use crate::{
+    coords::Equatorial,
+    request::ephemeris::{julian_date, Format},
+};
+use chrono::{DateTime, Utc};
 use thiserror::Error;
 
 #[repr(transparent)]
@@ -5,12 +15,29 @@ use thiserror::Error;
 #[error("{0} is not a valid body identifier")]
 pub struct InvalidBodyCode(pub i64);
 
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{0:?} is not a known body name")]
+pub struct InvalidBodyName(pub String);
+
+/// Lowercase ASCII alphanumerics of `name`, so that `S/2010 J 1` matches `S2010J1`
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
 macro_rules! impl_try_from_int {
     ($name:ident $varname:ident [$($int:ty)+] $match:tt) => {
         $(
             impl TryFrom<$int> for $name {
                 type Error = InvalidBodyCode;
 
+                #[allow(
+                    clippy::cast_lossless,
+                    clippy::cast_possible_truncation,
+                    clippy::cast_possible_wrap
+                )]
                 fn try_from($varname: $int) -> Result<Self, Self::Error> {
                     $match
                 }
@@ -20,7 +47,12 @@ macro_rules! impl_try_from_int {
 }
 
 macro_rules! bodies {
-    ($(#[$attr:meta])* [repr $repr:ty] $name:ident { $($variant:ident = $code:literal,)* }) => {
+    (
+        $(#[$attr:meta])*
+        [repr $repr:ty]
+        [from $($int:ty)+]
+        $name:ident { $($variant:ident = $code:literal,)* }
+    ) => {
         #[repr($repr)]
         $(#[$attr])*
         pub enum $name {
@@ -35,8 +67,28 @@ macro_rules! bodies {
             }
         }
 
+        impl $name {
+            const VARIANTS: &'static [Self] = &[$(Self::$variant,)*];
+
+            /// Every variant, in declaration order
+            pub fn iter() -> impl Iterator<Item = Self> {
+                Self::VARIANTS.iter().copied()
+            }
+
+            /// Matches a normalized name against the variant names
+            fn from_variant_name(normalized: &str) -> Option<Self> {
+                $(
+                    if normalized == normalize_name(stringify!($variant)) {
+                        return Some(Self::$variant);
+                    }
+                )*
+
+                None
+            }
+        }
+
         impl_try_from_int! {
-            $name num [u32 u64 u128 usize i32 i64 i128 isize] {
+            $name num [$($int)+] {
                 match num {
                     $($code => Ok(Self::$variant),)*
                     _ => Err(InvalidBodyCode(num as i64))
@@ -48,11 +100,14 @@ macro_rules! bodies {
     };
 }
 
+// The entries can be regenerated from a saved Horizons `MB` list with
+// `cargo run --example major_bodies`
 bodies! {
     /// Unnamed objects are in the format S+Designation, e.g. S2010J1
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
     #[allow(non_camel_case_types)]
     [repr u32]
+    [from u32 u64 u128 usize i32 i64 i128 isize]
     MajorBody {
         // Sun and Solar barycenter
         SolarSystemBary = 0, Sun = 10,
@@ -140,6 +195,183 @@ bodies! {
     }
 }
 
+impl MajorBody {
+    /// The barycenters of the Solar System and of each planetary system
+    pub fn is_barycenter(&self) -> bool {
+        u32::from(self) < 10
+    }
+
+    /// The planets and Pluto, whose NAIF codes end in 99
+    pub fn is_planet(&self) -> bool {
+        let code = u32::from(self);
+        (199..=999).contains(&code) && code % 100 == 99
+    }
+
+    /// The Earth-Moon and Sun-(Earth-Moon barycenter) Lagrange points
+    pub fn is_lagrange_point(&self) -> bool {
+        matches!(u32::from(self), 31..=35 | 3011..=3015)
+    }
+
+    /// Whether this is a natural satellite of `primary`, which may be the planet or its
+    /// system barycenter
+    pub fn is_moon_of(&self, primary: MajorBody) -> bool {
+        let system = match u32::from(primary) {
+            code @ 1..=9 => code,
+            code if primary.is_planet() => code / 100,
+            _ => return false,
+        };
+
+        self.moon_system() == Some(system)
+    }
+
+    /// Barycenter of the planetary system this body belongs to, e.g. `JupiterBary` for
+    /// `Europa`. The Sun and the barycenters themselves belong to the Solar System
+    /// barycenter; the Lagrange points belong to no system.
+    pub fn system_barycenter(&self) -> Option<MajorBody> {
+        let code = match u32::from(self) {
+            0 | 10 => 0,
+            code @ 1..=9 => return Self::try_from(code).ok(),
+            code if self.is_planet() => code / 100,
+            _ => self.moon_system()?,
+        };
+
+        Self::try_from(code).ok()
+    }
+
+    /// Body this one orbits: the planet for moons, and the Sun for planets and system
+    /// barycenters. `None` for the Sun, the Solar System barycenter and the Lagrange points.
+    pub fn primary(&self) -> Option<MajorBody> {
+        if self.is_planet() || (1..=9).contains(&u32::from(self)) {
+            Some(Self::Sun)
+        } else {
+            Self::try_from(self.moon_system()? * 100 + 99).ok()
+        }
+    }
+
+    /// System index of a natural satellite: the leading digit of its NAIF code
+    fn moon_system(self) -> Option<u32> {
+        match u32::from(self) {
+            code @ 100..=999 if code % 100 != 99 => Some(code / 100),
+            code @ 10_000..=99_999 => Some(code / 10_000),
+            _ => None,
+        }
+    }
+}
+
+/// GM of the Sun in km³/s², from DE440
+pub(crate) const GM_SUN: f64 = 132_712_440_041.279_42;
+
+impl MajorBody {
+    /// Gravitational parameter GM in km³/s², from the DE440 planetary ephemeris.
+    ///
+    /// Only available for the Sun, the Earth and Moon, and the system barycenters,
+    /// whose GM includes all the bodies of the system.
+    pub fn gm(&self) -> Option<f64> {
+        match self {
+            Self::Sun => Some(GM_SUN),
+            Self::MercuryBary | Self::Mercury => Some(22_031.868_551),
+            Self::VenusBary | Self::Venus => Some(324_858.592),
+            Self::EarthMoonBary => Some(403_503.235_502),
+            Self::Earth => Some(398_600.435_507),
+            Self::Moon => Some(4_902.800_118),
+            Self::MarsBary => Some(42_828.375_816),
+            Self::JupiterBary => Some(126_712_764.1),
+            Self::SaturnBary => Some(37_940_584.841_8),
+            Self::UranusBary => Some(5_794_556.4),
+            Self::NeptuneBary => Some(6_836_527.100_58),
+            Self::PlutoBary => Some(975.5),
+            _ => None,
+        }
+    }
+
+    /// Direction of the north pole of rotation in the ICRF, from the IAU WGCCRE 2015 report.
+    ///
+    /// These are the values at J2000; the poles precess slowly, by less than 0.1° per
+    /// century for the planets. Only available for the planets, Pluto and the Moon.
+    pub fn north_pole(&self) -> Option<Equatorial> {
+        let (ra, dec) = match self {
+            Self::Mercury => (281.010_3, 61.415_5),
+            Self::Venus => (272.76, 67.16),
+            Self::Earth => (0., 90.),
+            Self::Moon => (269.994_9, 66.539_2),
+            Self::Mars => (317.269_202, 54.432_516),
+            Self::Jupiter => (268.056_595, 64.495_303),
+            Self::Saturn => (40.589, 83.537),
+            Self::Uranus => (257.311, -15.175),
+            Self::Neptune => (299.36, 43.46),
+            Self::Pluto => (132.993, -6.163),
+            _ => return None,
+        };
+
+        Some(Equatorial::new(ra, dec))
+    }
+
+    /// Mean radius in km, from the IAU WGCCRE 2015 report.
+    ///
+    /// Only available for the Sun, the planets, Pluto, the Moon and the largest moons.
+    pub fn mean_radius_km(&self) -> Option<f64> {
+        match self {
+            Self::Sun => Some(695_700.),
+            Self::Mercury => Some(2_439.4),
+            Self::Venus => Some(6_051.8),
+            Self::Earth => Some(6_371.008_4),
+            Self::Moon => Some(1_737.4),
+            Self::Mars => Some(3_389.5),
+            Self::Jupiter => Some(69_911.),
+            Self::Io => Some(1_821.49),
+            Self::Europa => Some(1_560.8),
+            Self::Ganymede => Some(2_631.2),
+            Self::Callisto => Some(2_410.3),
+            Self::Saturn => Some(58_232.),
+            Self::Titan => Some(2_574.73),
+            Self::Uranus => Some(25_362.),
+            Self::Neptune => Some(24_622.),
+            Self::Triton => Some(1_352.6),
+            Self::Pluto => Some(1_188.3),
+            Self::Charon => Some(606.),
+            _ => None,
+        }
+    }
+}
+
+impl MajorBody {
+    /// Looks up a body by name or NAIF code, ignoring case, spaces and punctuation.
+    ///
+    /// Besides the variant names, this accepts Horizons' `<Planet> Barycenter` names, the
+    /// provisional form of unnamed moons (`S/2010 J 1`), and `SSB`, `EMB`, `Sol` and `Luna`.
+    pub fn from_name(name: &str) -> Result<Self, InvalidBodyName> {
+        if let Ok(code) = name.trim().parse::<i64>() {
+            return Self::try_from(code).map_err(|_| InvalidBodyName(name.to_owned()));
+        }
+
+        let mut normalized = normalize_name(name);
+
+        for suffix in ["barycenter", "barycentre"] {
+            if normalized.ends_with(suffix) {
+                normalized.truncate(normalized.len() - suffix.len());
+                normalized.push_str("bary");
+            }
+        }
+
+        match normalized.as_str() {
+            "ssb" => Some(Self::SolarSystemBary),
+            "sol" => Some(Self::Sun),
+            "emb" => Some(Self::EarthMoonBary),
+            "luna" => Some(Self::Moon),
+            other => Self::from_variant_name(other),
+        }
+        .ok_or_else(|| InvalidBodyName(name.to_owned()))
+    }
+}
+
+impl FromStr for MajorBody {
+    type Err = InvalidBodyName;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_name(s)
+    }
+}
+
 impl serde::Serialize for MajorBody {
     fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
     where
@@ -148,3 +380,353 @@ impl serde::Serialize for MajorBody {
         s.serialize_u32(u32::from(self))
     }
 }
+
+bodies! {
+    /// Spacecraft are identified by negative NAIF codes
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    [repr i32]
+    [from i32 i64 i128 isize]
+    Spacecraft {
+        // Earth orbit
+        ISS = -125_544, Hubble = -48, Chandra = -151, Tess = -95,
+
+        // Sun-Earth L1 and L2, and Earth-trailing
+        Soho = -21, Jwst = -170, Gaia = -139_479, Spitzer = -79, Kepler = -227,
+
+        // Heliophysics
+        ParkerSolarProbe = -96, SolarOrbiter = -144,
+
+        // Moon and inner planets
+        Lro = -85, Messenger = -236, BepiColombo = -121,
+
+        // Mars
+        MarsOdyssey = -53, Mro = -74, Maven = -202, Curiosity = -76, Perseverance = -168,
+
+        // Small bodies
+        Rosetta = -226, Dawn = -203, OsirisRex = -64, Hayabusa2 = -37, Dart = -135,
+        Lucy = -49, Psyche = -255,
+
+        // Outer planets
+        Galileo = -77, Cassini = -82, Juno = -61, Juice = -28, EuropaClipper = -159,
+
+        // Interstellar and deep space
+        Pioneer10 = -23, Pioneer11 = -24, Voyager1 = -31, Voyager2 = -32, NewHorizons = -98,
+    }
+}
+
+impl FromStr for Spacecraft {
+    type Err = InvalidBodyName;
+
+    /// Parses a variant name, ignoring case and punctuation, or a NAIF code
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().parse::<i64>() {
+            Ok(code) => Self::try_from(code).ok(),
+            Err(_) => Self::from_variant_name(&normalize_name(s)),
+        }
+        .ok_or_else(|| InvalidBodyName(s.to_owned()))
+    }
+}
+
+impl serde::Serialize for Spacecraft {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        s.serialize_i32(i32::from(self))
+    }
+}
+
+/// Asteroids and comets, selected with the Horizons small-body command syntax
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SmallBody {
+    /// Designation or SPK-ID, e.g. `DES=2000433;`
+    Designation(String),
+    /// Name, e.g. `NAME=Eros;`
+    Name(String),
+    /// IAU number of a numbered asteroid, e.g. `433;`
+    Number(u32),
+    /// Provisional designation of an unnumbered asteroid, e.g. `DES=2021 QM1;`
+    Provisional(ProvisionalDesignation),
+    /// Record number in the small-body database, e.g. `433;`
+    Record(u32),
+    /// Comet designation with apparition and fragment options, e.g. `DES=73P-C;CAP;`
+    Comet(Comet),
+}
+
+impl SmallBody {
+    pub fn designation(des: impl Into<String>) -> Self {
+        Self::Designation(des.into())
+    }
+
+    pub fn name(name: impl Into<String>) -> Self {
+        Self::Name(name.into())
+    }
+
+    pub fn number(number: u32) -> Self {
+        Self::Number(number)
+    }
+
+    pub fn provisional(des: &str) -> Result<Self, InvalidDesignation> {
+        des.parse().map(Self::Provisional)
+    }
+
+    pub fn record(record: u32) -> Self {
+        Self::Record(record)
+    }
+}
+
+impl Display for SmallBody {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            SmallBody::Designation(des) => write!(f, "DES={des};"),
+            SmallBody::Name(name) => write!(f, "NAME={name};"),
+            SmallBody::Number(number) => write!(f, "{number};"),
+            SmallBody::Provisional(des) => write!(f, "DES={des};"),
+            SmallBody::Record(record) => write!(f, "{record};"),
+            SmallBody::Comet(comet) => write!(f, "{comet}"),
+        }
+    }
+}
+
+impl From<Comet> for SmallBody {
+    fn from(comet: Comet) -> Self {
+        Self::Comet(comet)
+    }
+}
+
+impl From<SpkId> for SmallBody {
+    fn from(id: SpkId) -> Self {
+        Self::Designation(id.to_string())
+    }
+}
+
+#[repr(transparent)]
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[error("{0} is not a small-body SPK-ID")]
+pub struct InvalidSpkId(pub u64);
+
+/// What a small-body SPK-ID refers to, by its numbering range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpkIdKind {
+    /// `1000000 + record number`
+    Comet,
+    /// `2000000 + number`, or `20000000 + number` above 999 999
+    NumberedAsteroid(u32),
+    /// `3000000 + sequence`, for unnumbered asteroids
+    UnnumberedAsteroid,
+}
+
+/// SPK-ID of an asteroid or comet, as returned by the Lookup API
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SpkId(u32);
+
+impl SpkId {
+    pub fn new(id: u32) -> Result<Self, InvalidSpkId> {
+        match id {
+            1_000_001..=1_999_999 | 2_000_001..=3_999_999 | 21_000_000..=29_999_999 => Ok(Self(id)),
+            _ => Err(InvalidSpkId(u64::from(id))),
+        }
+    }
+
+    /// SPK-ID of the numbered asteroid `number`
+    pub fn asteroid(number: u32) -> Result<Self, InvalidSpkId> {
+        match number {
+            1..=999_999 => Ok(Self(2_000_000 + number)),
+            1_000_000..=9_999_999 => Ok(Self(20_000_000 + number)),
+            _ => Err(InvalidSpkId(u64::from(number))),
+        }
+    }
+
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+
+    pub fn kind(&self) -> SpkIdKind {
+        match self.0 {
+            1_000_001..=1_999_999 => SpkIdKind::Comet,
+            2_000_001..=2_999_999 => SpkIdKind::NumberedAsteroid(self.0 - 2_000_000),
+            21_000_000..=29_999_999 => SpkIdKind::NumberedAsteroid(self.0 - 20_000_000),
+            // 3_000_000..=3_999_999, as `new` admits nothing else
+            _ => SpkIdKind::UnnumberedAsteroid,
+        }
+    }
+}
+
+impl TryFrom<i64> for SpkId {
+    type Error = InvalidSpkId;
+
+    fn try_from(id: i64) -> Result<Self, Self::Error> {
+        u32::try_from(id)
+            .map_err(|_| InvalidSpkId(id.unsigned_abs()))
+            .and_then(Self::new)
+    }
+}
+
+impl Display for SpkId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("`{0}` is not a valid provisional designation")]
+pub struct InvalidDesignation(pub String);
+
+/// Minor Planet Center provisional designation, e.g. `2021 QM1`
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ProvisionalDesignation(String);
+
+impl ProvisionalDesignation {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for ProvisionalDesignation {
+    type Err = InvalidDesignation;
+
+    /// Accepts a four digit year, a space, the half-month letter (`A`-`Y`, without `I`),
+    /// the order letter (`A`-`Z`, without `I`) and an optional cycle count
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || InvalidDesignation(s.to_owned());
+
+        let (year, rest) = s.split_once(' ').ok_or_else(err)?;
+
+        if year.len() != 4
+            || !year.bytes().all(|b| b.is_ascii_digit())
+            || !year.starts_with(['1', '2'])
+        {
+            return Err(err());
+        }
+
+        let mut letters = rest.bytes();
+
+        match (letters.next(), letters.next()) {
+            (Some(half_month), Some(order))
+                if matches!(half_month, b'A'..=b'Y')
+                    && order.is_ascii_uppercase()
+                    && half_month != b'I'
+                    && order != b'I' => {}
+            _ => return Err(err()),
+        }
+
+        let cycle = &rest[2..];
+
+        if cycle.starts_with('0') || !cycle.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(err());
+        }
+
+        Ok(Self(s.to_owned()))
+    }
+}
+
+impl Display for ProvisionalDesignation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(&self.0)
+    }
+}
+
+/// Selects one of the orbit solutions of a periodic comet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Apparition {
+    /// `CAP`, the last apparition before the current date
+    Current,
+    /// `CAP<JD`, the last apparition before the given epoch
+    Before(DateTime<Utc>),
+}
+
+impl Display for Apparition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Apparition::Current => f.write_str("CAP"),
+            Apparition::Before(epoch) => write!(f, "CAP<{}", julian_date(epoch)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comet {
+    designation: String,
+    fragment: Option<String>,
+    apparition: Option<Apparition>,
+    no_frag: bool,
+}
+
+impl Comet {
+    /// A comet by designation, e.g. `73P` or `C/2020 F3`
+    pub fn new(designation: impl Into<String>) -> Self {
+        Self {
+            designation: designation.into(),
+            fragment: None,
+            apparition: None,
+            no_frag: false,
+        }
+    }
+
+    /// Selects a fragment of a split comet, e.g. `C` for `73P-C`
+    pub fn fragment(&mut self, fragment: impl Into<String>) -> &mut Self {
+        self.fragment = Some(fragment.into());
+        self
+    }
+
+    pub fn apparition(&mut self, apparition: Apparition) -> &mut Self {
+        self.apparition = Some(apparition);
+        self
+    }
+
+    /// `NOFRAG`, excludes fragments from the search
+    pub fn no_frag(&mut self, no_frag: bool) -> &mut Self {
+        self.no_frag = no_frag;
+        self
+    }
+}
+
+impl Display for Comet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "DES={}", self.designation)?;
+
+        if let Some(fragment) = &self.fragment {
+            write!(f, "-{fragment}")?;
+        }
+
+        f.write_str(";")?;
+
+        if let Some(apparition) = self.apparition {
+            write!(f, "{apparition};")?;
+        }
+
+        if self.no_frag {
+            f.write_str("NOFRAG;")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl serde::Serialize for SmallBody {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        s.serialize_str(&self.to_string())
+    }
+}
+
+/// Request for the table of all major bodies (`COMMAND='MB'`), which can be parsed with
+/// [`MajorBodyRecord::parse_list`](crate::response::bodies::MajorBodyRecord::parse_list)
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct MajorBodyList {
+    format: Format,
+    command: &'static str,
+}
+
+impl Default for MajorBodyList {
+    fn default() -> Self {
+        Self {
+            format: Format::Text,
+            command: "MB",
+        }
+    }
+}
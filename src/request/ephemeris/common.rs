@@ -2,16 +2,38 @@
 
 use crate::request::{
     ephemeris::{EphemType, Format, RefSystem, TimeSpec},
-    Center, Command, HzBool,
+    params::PARAM_ORDER,
+    tle::Tle,
+    Center, Command, HzBool, SiteCoord,
 };
-use serde::Serialize;
+use serde::{ser::SerializeMap, Serialize, Serializer};
 use thiserror::Error;
 
+/// Parameters of [`CommonBuilder::extra_param`], by lowercase name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ExtraParams(Vec<(String, String)>);
+
+impl Serialize for ExtraParams {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(self.0.len()))?;
+
+        for (name, value) in &self.0 {
+            map.serialize_entry(name, value)?;
+        }
+
+        map.end()
+    }
+}
+
 #[derive(Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct Common {
     command: Command,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tle: Option<Tle>,
     ephem_type: EphemType,
     center: Center,
+    #[serde(flatten)]
+    site_coord: Option<SiteCoord>,
     ref_system: RefSystem,
     format: Format,
     obj_data: HzBool,
@@ -20,6 +42,55 @@ pub struct Common {
 
     #[serde(flatten)]
     time_spec: TimeSpec,
+
+    #[serde(flatten)]
+    extra: ExtraParams,
+}
+
+impl Common {
+    pub(crate) fn command(&self) -> &Command {
+        &self.command
+    }
+
+    pub(crate) fn ephem_type(&self) -> EphemType {
+        self.ephem_type
+    }
+
+    pub(crate) fn center(&self) -> &Center {
+        &self.center
+    }
+
+    pub(crate) fn time_spec(&self) -> &TimeSpec {
+        &self.time_spec
+    }
+
+    pub(crate) fn ref_system(&self) -> RefSystem {
+        self.ref_system
+    }
+
+    pub(crate) fn set_command(&mut self, command: Command) {
+        self.command = command;
+    }
+
+    pub(crate) fn set_time_spec(&mut self, time_spec: TimeSpec) {
+        self.time_spec = time_spec;
+    }
+
+    /// A builder with every field set as in `self`
+    pub fn to_builder(&self) -> CommonBuilder {
+        CommonBuilder {
+            command: Some(self.command.clone()),
+            ephem_type: Some(self.ephem_type),
+            center: Some(self.center.clone()),
+            ref_system: self.ref_system,
+            time_spec: Some(self.time_spec.clone()),
+            format: self.format,
+            obj_data: self.obj_data.into(),
+            make_ephem: self.make_ephem.into(),
+            csv_format: self.csv_format.into(),
+            extra: self.extra.0.clone(),
+        }
+    }
 }
 
 #[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
@@ -28,6 +99,20 @@ pub enum CommonBuilderError {
     UninitializedField(&'static str),
 }
 
+/// A parameter name that [`CommonBuilder::extra_param`] does not accept
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ExtraParamError {
+    #[error("Invalid parameter name `{0}`; expected ASCII letters, digits and `_`")]
+    InvalidName(String),
+    #[error("Parameter `{0}` has a typed setter")]
+    Modelled(String),
+}
+
+/// Every uninitialized field of a [`CommonBuilder`], in the order of [`CommonBuilder::build`]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Uninitialized fields {}", .0.iter().map(|field| format!("`{field}`")).collect::<Vec<_>>().join(", "))]
+pub struct MissingFields(pub Vec<&'static str>);
+
 #[derive(Debug, Clone)]
 pub struct CommonBuilder {
     command: Option<Command>,
@@ -39,6 +124,7 @@ pub struct CommonBuilder {
     obj_data: bool,
     make_ephem: bool,
     csv_format: bool,
+    extra: Vec<(String, String)>,
 }
 
 impl CommonBuilder {
@@ -91,6 +177,106 @@ impl CommonBuilder {
         self
     }
 
+    /// Sets a parameter the crate does not model, e.g. `("time_digits", "FRACSEC")`, as
+    /// written in a query string. Names are case-insensitive, and setting one again
+    /// replaces its value.
+    pub fn extra_param(
+        &mut self,
+        name: &str,
+        value: impl Into<String>,
+    ) -> Result<&mut Self, ExtraParamError> {
+        let name = name.to_ascii_lowercase();
+
+        if name.is_empty() || !name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_') {
+            return Err(ExtraParamError::InvalidName(name));
+        }
+        if PARAM_ORDER.contains(&name.as_str()) {
+            return Err(ExtraParamError::Modelled(name));
+        }
+
+        // Sorted like the unknown parameters of `Query::params`, so that equal queries
+        // compare equal however they were built
+        let value = value.into();
+        match self.extra.binary_search_by(|(n, _)| n.as_str().cmp(&name)) {
+            Ok(i) => self.extra[i].1 = value,
+            Err(i) => self.extra.insert(i, (name, value)),
+        }
+
+        Ok(self)
+    }
+
+    pub fn get_command(&self) -> Option<&Command> {
+        self.command.as_ref()
+    }
+
+    pub fn get_ephem_type(&self) -> Option<EphemType> {
+        self.ephem_type
+    }
+
+    pub fn get_center(&self) -> Option<&Center> {
+        self.center.as_ref()
+    }
+
+    pub fn get_ref_system(&self) -> RefSystem {
+        self.ref_system
+    }
+
+    pub fn get_time_spec(&self) -> Option<&TimeSpec> {
+        self.time_spec.as_ref()
+    }
+
+    pub fn get_format(&self) -> Format {
+        self.format
+    }
+
+    pub fn get_obj_data(&self) -> bool {
+        self.obj_data
+    }
+
+    pub fn get_make_ephem(&self) -> bool {
+        self.make_ephem
+    }
+
+    pub fn get_csv_format(&self) -> bool {
+        self.csv_format
+    }
+
+    /// Parameters of [`CommonBuilder::extra_param`], by name
+    pub fn get_extra_params(&self) -> &[(String, String)] {
+        &self.extra
+    }
+
+    /// Fails with every field that [`CommonBuilder::build`] requires but is not set
+    pub fn validate(&self) -> Result<(), MissingFields> {
+        let missing = self.missing_fields();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(MissingFields(missing))
+        }
+    }
+
+    /// Names of the fields that [`CommonBuilder::build`] requires but are not set
+    pub fn missing_fields(&self) -> Vec<&'static str> {
+        [
+            ("command", self.command.is_none()),
+            ("ephem_type", self.ephem_type.is_none()),
+            ("center", self.center.is_none()),
+            ("time_spec", self.time_spec.is_none()),
+        ]
+        .into_iter()
+        .filter_map(|(field, missing)| missing.then_some(field))
+        .collect()
+    }
+
+    /// Like [`CommonBuilder::build`], but reports all uninitialized fields instead of the
+    /// first
+    pub fn build_all_errors(&self) -> Result<Common, MissingFields> {
+        self.build()
+            .map_err(|_| MissingFields(self.missing_fields()))
+    }
+
     pub fn build(&self) -> Result<Common, CommonBuilderError> {
         let command = self
             .command
@@ -121,8 +307,10 @@ impl CommonBuilder {
         } = self;
 
         Ok(Common {
+            tle: command.tle().cloned(),
             command,
             ephem_type,
+            site_coord: center.site_coord(),
             center,
             ref_system,
             time_spec,
@@ -130,6 +318,7 @@ impl CommonBuilder {
             obj_data: obj_data.into(),
             make_ephem: make_ephem.into(),
             csv_format: csv_format.into(),
+            extra: ExtraParams(self.extra.clone()),
         })
     }
 }
@@ -146,6 +335,7 @@ impl Default for CommonBuilder {
             obj_data: true,
             make_ephem: true,
             csv_format: false,
+            extra: Vec::new(),
         }
     }
 }
@@ -156,13 +346,14 @@ mod tests {
         request::{
             bodies::MajorBody,
             ephemeris::{
-                common::{Common, CommonBuilder},
+                common::{Common, CommonBuilder, ExtraParamError, ExtraParams, MissingFields},
                 EphemType, Format, RefSystem, StepSizeUnit, TimeSpec,
             },
+            Command, Query,
         },
         TestResult,
     };
-    use chrono::Utc;
+    use chrono::{TimeZone, Utc};
 
     #[test]
     fn test_common_builder() -> TestResult {
@@ -185,18 +376,21 @@ mod tests {
             case,
             Common {
                 command: MajorBody::Europa.into(),
+                tle: None,
                 ephem_type: EphemType::Vectors,
                 center: MajorBody::Jupiter.into(),
+                site_coord: None,
                 time_spec: TimeSpec::Bounded {
                     step_size: (6, StepSizeUnit::Hours).into(),
-                    start_time: now,
-                    stop_time: now + chrono::Duration::days(2),
+                    start_time: now.into(),
+                    stop_time: (now + chrono::Duration::days(2)).into(),
                 },
                 ref_system: RefSystem::ICRF,
                 format: Format::Text,
                 obj_data: false.into(),
                 make_ephem: true.into(),
                 csv_format: true.into(),
+                extra: ExtraParams::default(),
             }
         );
 
@@ -204,4 +398,73 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_missing_fields() {
+        let mut builder = CommonBuilder::new();
+        builder.ephem_type(EphemType::Vectors);
+
+        let error = builder.build_all_errors().unwrap_err();
+        assert_eq!(MissingFields(vec!["command", "center", "time_spec"]), error);
+        assert_eq!(Err(error.clone()), builder.validate());
+        assert_eq!(
+            "Uninitialized fields `command`, `center`, `time_spec`",
+            error.to_string()
+        );
+
+        builder
+            .command(MajorBody::Europa)
+            .center(MajorBody::Jupiter)
+            .time_spec(TimeSpec::from_list([Utc::now()]));
+        assert!(builder.missing_fields().is_empty());
+        assert_eq!(Ok(()), builder.validate());
+        assert_eq!(builder.build().ok(), builder.build_all_errors().ok());
+
+        assert_eq!(
+            Some(&Command::from(MajorBody::Europa)),
+            builder.get_command()
+        );
+        assert_eq!(Some(EphemType::Vectors), builder.get_ephem_type());
+        assert!(builder.get_obj_data() && !builder.get_csv_format());
+    }
+
+    #[test]
+    fn test_extra_param() -> TestResult {
+        let mut builder = Query::vectors();
+        builder
+            .common
+            .command(MajorBody::Europa)
+            .center(MajorBody::Jupiter)
+            .time_spec(TimeSpec::from_list([Utc
+                .with_ymd_and_hms(2023, 1, 1, 0, 0, 0)
+                .unwrap()]))
+            .extra_param("TIME_DIGITS", "MINUTES")?
+            .extra_param("time_digits", "FRACSEC")?
+            .extra_param("cal_type", "'M'")?;
+
+        assert_eq!(
+            Err(ExtraParamError::InvalidName("time-digits".to_owned())),
+            builder
+                .common
+                .extra_param("time-digits", "FRACSEC")
+                .map(|_| ())
+        );
+        assert_eq!(
+            Err(ExtraParamError::Modelled("csv_format".to_owned())),
+            builder.common.extra_param("CSV_FORMAT", "YES").map(|_| ())
+        );
+
+        let query = builder.build()?;
+        let params = query.to_query_string();
+        assert!(
+            params.ends_with("&cal_type=%27M%27&time_digits=FRACSEC"),
+            "{params}"
+        );
+
+        assert_eq!(query, Query::from_params(query.params())?);
+        assert_eq!(query, Query::from_bytes(&query.to_bytes())?);
+        assert!(Query::from_params_strict(query.params()).is_err());
+
+        Ok(())
+    }
 }
@@ -23,6 +23,30 @@ pub struct Elements {
     elm_labels: HzBool,
 }
 
+impl Elements {
+    pub(crate) fn out_units(&self) -> OutUnits {
+        self.out_units
+    }
+
+    pub(crate) fn set_out_units(&mut self, out_units: OutUnits) {
+        self.out_units = out_units;
+    }
+
+    pub(crate) fn ref_plane(&self) -> RefPlane {
+        self.ref_plane
+    }
+
+    /// A builder with every field set as in `self`
+    pub fn to_builder(&self) -> ElementsBuilder {
+        ElementsBuilder {
+            tp_type: self.tp_type,
+            out_units: self.out_units,
+            ref_plane: self.ref_plane,
+            elm_labels: self.elm_labels.into(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct ElementsBuilder {
     tp_type: TpType,
@@ -56,6 +80,22 @@ impl ElementsBuilder {
         self
     }
 
+    pub fn get_tp_type(&self) -> TpType {
+        self.tp_type
+    }
+
+    pub fn get_out_units(&self) -> OutUnits {
+        self.out_units
+    }
+
+    pub fn get_ref_plane(&self) -> RefPlane {
+        self.ref_plane
+    }
+
+    pub fn get_elm_labels(&self) -> bool {
+        self.elm_labels
+    }
+
     pub fn build(&self) -> Elements {
         let &Self {
             tp_type,
@@ -1,20 +1,25 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, SecondsFormat, TimeZone, Utc};
 use serde::{Serialize, Serializer};
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use thiserror::Error;
 
 pub mod common;
 pub mod elements;
+pub mod spk;
 pub mod vectors;
 
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum StepSizeUnit {
+    /// Number of equal intervals of the span, see [`StepSize::intervals`]
     Unitless,
     Minutes,
     Hours,
     Days,
     Years,
     Months,
+    /// Arcseconds of motion of the target across the sky per step, for observer tables
+    Variable,
 }
 
 impl StepSizeUnit {
@@ -28,6 +33,7 @@ impl StepSizeUnit {
             Years => "y",
             Months => "mo",
             Unitless => "",
+            Variable => " VAR",
         }
     }
 }
@@ -44,17 +50,107 @@ impl From<(u32, StepSizeUnit)> for StepSize {
     }
 }
 
+/// Largest number of intervals Horizons divides a span into
+pub const MAX_INTERVALS: u32 = 90_024;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("Cannot divide a span into {0} intervals, the limit is {MAX_INTERVALS}")]
+pub struct InvalidIntervals(pub u32);
+
+/// A duration that is not a positive whole number of minutes fitting a [`StepSize`]
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("{0} is not a positive whole number of minutes")]
+pub struct InvalidStepDuration(pub Duration);
+
+/// Uses the largest unit that divides the duration exactly, e.g. `6h` or `90m`
+impl TryFrom<Duration> for StepSize {
+    type Error = InvalidStepDuration;
+
+    fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+        let error = InvalidStepDuration(duration);
+
+        if duration <= Duration::zero() || duration != Duration::minutes(duration.num_minutes()) {
+            return Err(error);
+        }
+
+        let minutes = duration.num_minutes();
+        let (value, unit) = if minutes % 1440 == 0 {
+            (minutes / 1440, StepSizeUnit::Days)
+        } else if minutes % 60 == 0 {
+            (minutes / 60, StepSizeUnit::Hours)
+        } else {
+            (minutes, StepSizeUnit::Minutes)
+        };
+
+        Ok(Self {
+            value: value.try_into().map_err(|_| error)?,
+            unit,
+        })
+    }
+}
+
 #[allow(clippy::must_use_candidate)]
 impl StepSize {
     /// Creates a new [`StepSize`].
     pub fn new(value: u32, unit: StepSizeUnit) -> Self {
         Self { value, unit }
     }
+
+    /// Divides the span of a bounded [`TimeSpec`] into `n` equal intervals, giving `n + 1`
+    /// records. Fails if `n` is zero or more than [`MAX_INTERVALS`].
+    pub fn intervals(n: u32) -> Result<Self, InvalidIntervals> {
+        if (1..=MAX_INTERVALS).contains(&n) {
+            Ok(Self::new(n, StepSizeUnit::Unitless))
+        } else {
+            Err(InvalidIntervals(n))
+        }
+    }
+
+    /// A variable step after which the target has moved `arcseconds` across the sky, e.g.
+    /// `60 VAR`. Only observer tables support it, so vectors and elements queries with it
+    /// fail to build with [`TimeSpecError::VariableStep`]; steps shorten as the target speeds up,
+    /// which suits fast-moving near-Earth objects.
+    pub fn angular(arcseconds: u32) -> Self {
+        Self::new(arcseconds, StepSizeUnit::Variable)
+    }
+
+    pub(crate) fn is_variable(self) -> bool {
+        self.unit == StepSizeUnit::Variable
+    }
+
+    /// Length of the step in whole minutes, for steps of minutes, hours or days
+    pub(crate) fn minutes(self) -> Option<u32> {
+        match self.unit {
+            StepSizeUnit::Minutes => Some(self.value),
+            StepSizeUnit::Hours => self.value.checked_mul(60),
+            StepSizeUnit::Days => self.value.checked_mul(1440),
+            _ => None,
+        }
+    }
+
+    /// Length of the step in days, with months and years of average length. `None` for a
+    /// unitless or variable step.
+    pub(crate) fn approximate_days(self) -> Option<f64> {
+        let value = f64::from(self.value);
+
+        match self.unit {
+            StepSizeUnit::Unitless | StepSizeUnit::Variable => None,
+            StepSizeUnit::Minutes => Some(value / 1440.),
+            StepSizeUnit::Hours => Some(value / 24.),
+            StepSizeUnit::Days => Some(value),
+            StepSizeUnit::Months => Some(value * 365.25 / 12.),
+            StepSizeUnit::Years => Some(value * 365.25),
+        }
+    }
 }
 
 impl Display for StepSize {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        write!(f, "{}{}", self.value, self.unit.as_hz_unit())
+        match self.unit {
+            // A bare number is a count of intervals
+            StepSizeUnit::Unitless => write!(f, "{}", self.value),
+            unit => write!(f, "{}{}", self.value, unit.as_hz_unit()),
+        }
     }
 }
 
@@ -76,6 +172,8 @@ pub enum EphemType {
     Elements,
     #[serde(rename = "V")]
     Vectors,
+    #[serde(rename = "SPK")]
+    Spk,
 }
 
 #[repr(u8)]
@@ -87,9 +185,394 @@ pub enum Format {
     Json,
 }
 
+/// Formats an epoch as an RFC 3339 timestamp in UTC.
+///
+/// The output only ever contains ASCII digits and the fixed separators `-`, `:`, `.`, `T`
+/// and `Z`; it does not depend on the system locale.
+pub(crate) fn format_epoch(epoch: &DateTime<Utc>) -> String {
+    epoch.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Julian date (UTC) of an epoch
+pub(crate) fn julian_date(epoch: &DateTime<Utc>) -> f64 {
+    const UNIX_EPOCH_JD: f64 = 2_440_587.5;
+
+    #[allow(clippy::cast_precision_loss)]
+    let millis = epoch.timestamp_millis() as f64;
+
+    UNIX_EPOCH_JD + millis / 86_400_000.
+}
+
+/// Epoch of a Julian date (UTC), or `None` if it is out of range
+pub(crate) fn from_julian_date(jd: f64) -> Option<DateTime<Utc>> {
+    const UNIX_EPOCH_JD: f64 = 2_440_587.5;
+
+    #[allow(clippy::cast_possible_truncation)]
+    let millis = ((jd - UNIX_EPOCH_JD) * 86_400_000.).round() as i64;
+
+    Utc.timestamp_millis_opt(millis).single()
+}
+
+pub(crate) fn serialize_epoch<S>(epoch: &DateTime<Utc>, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    s.serialize_str(&format_epoch(epoch))
+}
+
+/// Julian date, in the time scale of the query (UT by default)
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy)]
+pub struct JulianDate(pub f64);
+
+/// Compares the exact bit patterns, so that dates can be part of `Eq` queries
+impl PartialEq for JulianDate {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for JulianDate {}
+
+impl Display for JulianDate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "JD{}", self.0)
+    }
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarDateError {
+    #[error("Invalid calendar date {0}-{1}-{2}")]
+    InvalidDate(i32, u8, u8),
+    #[error("Invalid time of day {0}:{1}:{2}")]
+    InvalidTime(u8, u8, u8),
+    #[error("There is no year 0 BC; 1 BC is followed by AD 1")]
+    YearZeroBc,
+}
+
+/// A date in the calendar used by Horizons: Gregorian from 1582-Oct-15, Julian before.
+///
+/// Unlike [`DateTime`], which is proleptic Gregorian, this matches the dates of historical
+/// records, and it covers the full range of Horizons. Years are numbered astronomically, so
+/// year 0 is 1 BC and year -499 is 500 BC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CalendarDate {
+    year: i32,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+}
+
+impl CalendarDate {
+    /// Midnight at the start of the day
+    pub fn new(year: i32, month: u8, day: u8) -> Result<Self, CalendarDateError> {
+        let date = Self {
+            year,
+            month,
+            day,
+            hour: 0,
+            minute: 0,
+            second: 0,
+        };
+
+        let gap = (1582, 10, 5) <= (year, month, day) && (year, month, day) < (1582, 10, 15);
+
+        if (1..=12).contains(&month) && (1..=date.days_in_month()).contains(&day) && !gap {
+            Ok(date)
+        } else {
+            Err(CalendarDateError::InvalidDate(year, month, day))
+        }
+    }
+
+    /// A date in the year `year` BC, e.g. `CalendarDate::bc(500, 1, 1)`. Fails for year 0.
+    pub fn bc(year: u16, month: u8, day: u8) -> Result<Self, CalendarDateError> {
+        if year == 0 {
+            return Err(CalendarDateError::YearZeroBc);
+        }
+
+        Self::new(1 - i32::from(year), month, day)
+    }
+
+    /// The same date at the given time of day
+    pub fn and_hms(self, hour: u8, minute: u8, second: u8) -> Result<Self, CalendarDateError> {
+        if hour < 24 && minute < 60 && second < 60 {
+            Ok(Self {
+                hour,
+                minute,
+                second,
+                ..self
+            })
+        } else {
+            Err(CalendarDateError::InvalidTime(hour, minute, second))
+        }
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    fn is_gregorian(&self) -> bool {
+        (self.year, self.month, self.day) >= (1582, 10, 15)
+    }
+
+    fn days_in_month(&self) -> u8 {
+        let leap = if self.is_gregorian() {
+            self.year % 4 == 0 && (self.year % 100 != 0 || self.year % 400 == 0)
+        } else {
+            self.year.rem_euclid(4) == 0
+        };
+
+        match self.month {
+            2 if leap => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    /// Julian date, by the algorithm of Meeus, _Astronomical Algorithms_, ch. 7
+    pub fn julian_date(&self) -> f64 {
+        let (year, month) = match self.month {
+            1 | 2 => (f64::from(self.year) - 1., f64::from(self.month) + 12.),
+            _ => (f64::from(self.year), f64::from(self.month)),
+        };
+
+        let correction = if self.is_gregorian() {
+            let century = (year / 100.).floor();
+            2. - century + (century / 4.).floor()
+        } else {
+            0.
+        };
+
+        let day = f64::from(self.day)
+            + (f64::from(self.hour)
+                + (f64::from(self.minute) + f64::from(self.second) / 60.) / 60.)
+                / 24.;
+
+        (365.25 * (year + 4716.)).floor() + (30.6001 * (month + 1.)).floor() + day + correction
+            - 1524.5
+    }
+}
+
+/// Formats as e.g. `2000-Jan-01 12:00:00`, or `BC 0500-Jan-01 00:00:00` before year 1
+impl Display for CalendarDate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        const MONTHS: [&str; 12] = [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ];
+
+        if self.year < 1 {
+            write!(f, "BC {:04}", 1 - i64::from(self.year))?;
+        } else {
+            write!(f, "{:04}", self.year)?;
+        }
+
+        write!(
+            f,
+            "-{}-{:02} {:02}:{:02}:{:02}",
+            MONTHS[usize::from(self.month - 1)],
+            self.day,
+            self.hour,
+            self.minute,
+            self.second
+        )
+    }
+}
+
+/// Time scale of input epochs, appended to them as a suffix when it is not UT
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeScale {
+    /// Universal Time, i.e. UTC for modern epochs
+    #[default]
+    UT,
+    /// Terrestrial Time
+    TT,
+    /// Barycentric Dynamical Time, the scale of vectors and elements tables
+    TDB,
+}
+
+impl TimeScale {
+    /// Reads a Horizons time scale label such as `TDB`, or the `JDTDB` column name of a
+    /// CSV table
+    pub(crate) fn from_label(label: &str) -> Option<Self> {
+        match label.trim().trim_start_matches("JD") {
+            "UT" | "UTC" => Some(Self::UT),
+            "TT" => Some(Self::TT),
+            "TDB" => Some(Self::TDB),
+            _ => None,
+        }
+    }
+}
+
+impl Display for TimeScale {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(match self {
+            Self::UT => "UT",
+            Self::TT => "TT",
+            Self::TDB => "TDB",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Instant {
+    DateTime(DateTime<Utc>),
+    JulianDate(JulianDate),
+    Calendar(CalendarDate),
+}
+
+/// An instant given as a [`DateTime`], a [`JulianDate`] or a [`CalendarDate`], in UT unless marked
+/// otherwise with [`Epoch::with_scale`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Epoch {
+    instant: Instant,
+    scale: TimeScale,
+}
+
+impl Epoch {
+    /// Marks the epoch as being in `scale`, without converting it. For a calendar date,
+    /// the fields of the [`DateTime`] are read as a date in `scale`.
+    #[must_use]
+    pub fn with_scale(self, scale: TimeScale) -> Self {
+        Self { scale, ..self }
+    }
+
+    pub fn scale(&self) -> TimeScale {
+        self.scale
+    }
+
+    /// Julian date of the epoch, in its time scale
+    pub fn julian_date(&self) -> f64 {
+        match &self.instant {
+            Instant::DateTime(epoch) => julian_date(epoch),
+            Instant::JulianDate(jd) => jd.0,
+            Instant::Calendar(date) => date.julian_date(),
+        }
+    }
+}
+
+impl Epoch {
+    /// The epoch `minutes` later, in the same time scale
+    fn plus_minutes(self, minutes: i64) -> Self {
+        let instant = match self.instant {
+            Instant::DateTime(epoch) => epoch
+                .checked_add_signed(Duration::minutes(minutes))
+                .map(Instant::DateTime),
+            _ => None,
+        };
+
+        #[allow(clippy::cast_precision_loss)]
+        let instant = instant.unwrap_or_else(|| {
+            Instant::JulianDate(JulianDate(self.julian_date() + minutes as f64 / 1440.))
+        });
+
+        Self { instant, ..self }
+    }
+}
+
+impl From<DateTime<Utc>> for Epoch {
+    fn from(epoch: DateTime<Utc>) -> Self {
+        Self {
+            instant: Instant::DateTime(epoch),
+            scale: TimeScale::UT,
+        }
+    }
+}
+
+impl From<CalendarDate> for Epoch {
+    fn from(date: CalendarDate) -> Self {
+        Self {
+            instant: Instant::Calendar(date),
+            scale: TimeScale::UT,
+        }
+    }
+}
+
+impl From<JulianDate> for Epoch {
+    fn from(jd: JulianDate) -> Self {
+        Self {
+            instant: Instant::JulianDate(jd),
+            scale: TimeScale::UT,
+        }
+    }
+}
+
+impl Display for Epoch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match (&self.instant, self.scale) {
+            (Instant::DateTime(epoch), TimeScale::UT) => f.write_str(&format_epoch(epoch)),
+            // The `Z` of RFC 3339 would contradict the suffix
+            (Instant::DateTime(epoch), scale) => {
+                write!(f, "{} {scale}", epoch.format("%Y-%m-%dT%H:%M:%S%.f"))
+            }
+            (Instant::JulianDate(jd), TimeScale::UT) => jd.fmt(f),
+            (Instant::JulianDate(jd), scale) => write!(f, "{jd} {scale}"),
+            (Instant::Calendar(date), TimeScale::UT) => date.fmt(f),
+            (Instant::Calendar(date), scale) => write!(f, "{date} {scale}"),
+        }
+    }
+}
+
+impl Serialize for Epoch {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        s.serialize_str(&self.to_string())
+    }
+}
+
+/// Largest number of epochs Horizons accepts in a `TLIST`
+pub const MAX_TLIST_LEN: usize = 10_000;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TListError {
+    #[error("The list of epochs is empty")]
+    Empty,
+    #[error("{0} epochs is more than the limit of {MAX_TLIST_LEN}")]
+    TooLong(usize),
+}
+
 #[repr(transparent)]
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct TList(Vec<DateTime<Utc>>);
+pub struct TList(Vec<Epoch>);
+
+impl TList {
+    /// Sorts the epochs by Julian date and removes duplicates, failing if none are left or
+    /// if there are more than [`MAX_TLIST_LEN`]. Epochs in different time scales are
+    /// ordered by their Julian dates as given, without converting between scales.
+    pub fn new<E: Into<Epoch>>(epochs: impl IntoIterator<Item = E>) -> Result<Self, TListError> {
+        let mut epochs: Vec<Epoch> = epochs.into_iter().map(Into::into).collect();
+
+        // By scale as well, so that duplicates are adjacent
+        epochs.sort_by(|a, b| {
+            a.julian_date()
+                .total_cmp(&b.julian_date())
+                .then_with(|| (a.scale() as u8).cmp(&(b.scale() as u8)))
+        });
+        epochs.dedup_by(|a, b| {
+            a.scale() == b.scale() && a.julian_date().to_bits() == b.julian_date().to_bits()
+        });
+
+        match epochs.len() {
+            0 => Err(TListError::Empty),
+            len if len > MAX_TLIST_LEN => Err(TListError::TooLong(len)),
+            _ => Ok(Self(epochs)),
+        }
+    }
+
+    pub fn epochs(&self) -> &[Epoch] {
+        &self.0
+    }
+}
+
+impl From<TList> for TimeSpec {
+    fn from(tlist: TList) -> Self {
+        Self::List { tlist }
+    }
+}
 
 impl Serialize for TList {
     fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
@@ -99,7 +582,7 @@ impl Serialize for TList {
         s.serialize_str(
             self.0
                 .iter()
-                .map(|s| s.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true))
+                .map(Epoch::to_string)
                 .collect::<Vec<_>>()
                 .join(",")
                 .as_str(),
@@ -107,13 +590,25 @@ impl Serialize for TList {
     }
 }
 
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeSpecError {
+    #[error("The start time is not before the stop time")]
+    StartNotBeforeStop,
+    #[error("The step size is zero")]
+    ZeroStep,
+    #[error("Year {0} is outside the supported range of dates")]
+    YearOutOfRange(i32),
+    #[error("Variable step sizes are only supported by observer tables")]
+    VariableStep,
+}
+
 #[derive(Serialize, Debug, Clone, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum TimeSpec {
     Bounded {
         step_size: StepSize,
-        start_time: DateTime<Utc>,
-        stop_time: DateTime<Utc>,
+        start_time: Epoch,
+        stop_time: Epoch,
     },
     List {
         tlist: TList,
@@ -123,21 +618,135 @@ pub enum TimeSpec {
 impl TimeSpec {
     pub fn bounded(
         step_size: impl Into<StepSize>,
-        start_time: DateTime<Utc>,
-        stop_time: DateTime<Utc>,
+        start_time: impl Into<Epoch>,
+        stop_time: impl Into<Epoch>,
     ) -> Self {
         Self::Bounded {
             step_size: step_size.into(),
-            start_time,
-            stop_time,
+            start_time: start_time.into(),
+            stop_time: stop_time.into(),
         }
     }
 
-    pub fn from_list(list: impl IntoIterator<Item = DateTime<Utc>>) -> Self {
+    /// Epochs in the order given, unchecked; see [`TList::new`] to sort and validate them
+    pub fn from_list<E: Into<Epoch>>(list: impl IntoIterator<Item = E>) -> Self {
         Self::List {
-            tlist: TList(list.into_iter().collect()),
+            tlist: TList(list.into_iter().map(Into::into).collect()),
         }
     }
+
+    /// Checks that a bounded span starts before it stops and has a non-zero step. The
+    /// times are compared by Julian date, ignoring any difference in time scale.
+    pub fn validate(&self) -> Result<(), TimeSpecError> {
+        match self {
+            Self::Bounded {
+                step_size,
+                start_time,
+                stop_time,
+            } => {
+                if start_time.julian_date() >= stop_time.julian_date() {
+                    Err(TimeSpecError::StartNotBeforeStop)
+                } else if step_size.value == 0 {
+                    Err(TimeSpecError::ZeroStep)
+                } else {
+                    Ok(())
+                }
+            }
+            Self::List { .. } => Ok(()),
+        }
+    }
+
+    /// Number of records the ephemeris will have, estimated from the span and step size.
+    /// `None` for a zero or variable step.
+    pub fn estimated_records(&self) -> Option<u64> {
+        match self {
+            Self::Bounded {
+                step_size,
+                start_time,
+                stop_time,
+            } => {
+                let span = (stop_time.julian_date() - start_time.julian_date()).abs();
+
+                let intervals = match step_size.approximate_days() {
+                    Some(step) if step > 0. => (span / step).floor(),
+                    None if step_size.value > 0 && step_size.unit == StepSizeUnit::Unitless => {
+                        f64::from(step_size.value)
+                    }
+                    _ => return None,
+                };
+
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                Some(intervals as u64 + 1)
+            }
+            Self::List { tlist } => Some(tlist.0.len() as u64),
+        }
+    }
+
+    /// Splits a bounded span into consecutive spans of at most `max_records` records each,
+    /// every one starting at the stop time of the one before, so that together they give
+    /// the records of the whole span with each boundary epoch repeated. Lists, and spans
+    /// with a step other than minutes, hours or days, are returned whole.
+    pub fn split(&self, max_records: u64) -> Vec<Self> {
+        let (step_size, start_time, stop_time) = match self {
+            Self::Bounded {
+                step_size,
+                start_time,
+                stop_time,
+            } => (*step_size, *start_time, *stop_time),
+            Self::List { .. } => return vec![self.clone()],
+        };
+
+        let step = match step_size.minutes() {
+            Some(step) if step > 0 && max_records >= 2 => i64::from(step),
+            _ => return vec![self.clone()],
+        };
+
+        let chunk = step.saturating_mul(i64::try_from(max_records - 1).unwrap_or(i64::MAX));
+        let span = (stop_time.julian_date() - start_time.julian_date()) * 1440.;
+
+        let mut spans = Vec::new();
+        let mut offset: i64 = 0;
+
+        loop {
+            let start = start_time.plus_minutes(offset);
+            offset = offset.saturating_add(chunk);
+
+            // Without a sliver of a span from rounding at the end
+            #[allow(clippy::cast_precision_loss)]
+            if offset as f64 >= span - 1e-6 {
+                spans.push(Self::bounded(step_size, start, stop_time));
+                return spans;
+            }
+
+            spans.push(Self::bounded(
+                step_size,
+                start,
+                start_time.plus_minutes(offset),
+            ));
+        }
+    }
+
+    /// Marks every epoch as being in `scale`, see [`Epoch::with_scale`]
+    #[must_use]
+    pub fn with_time_scale(mut self, scale: TimeScale) -> Self {
+        match &mut self {
+            Self::Bounded {
+                start_time,
+                stop_time,
+                ..
+            } => {
+                *start_time = start_time.with_scale(scale);
+                *stop_time = stop_time.with_scale(scale);
+            }
+            Self::List { tlist } => {
+                for epoch in &mut tlist.0 {
+                    *epoch = epoch.with_scale(scale);
+                }
+            }
+        }
+
+        self
+    }
 }
 
 #[allow(non_camel_case_types)]
@@ -167,6 +776,63 @@ impl OutUnits {
             Self::AU_D => units::AU_PER_DAY,
         }
     }
+
+    /// Coefficient of the length unit in m
+    pub fn length_coefficient(&self) -> f64 {
+        use crate::units;
+
+        match self {
+            Self::KM_D | Self::KM_S => units::KILOMETRE,
+            Self::AU_D => units::ASTRONOMICAL_UNIT,
+        }
+    }
+
+    /// Coefficient of the time unit in s
+    pub fn time_coefficient(&self) -> f64 {
+        use crate::units;
+
+        match self {
+            Self::KM_S => units::SECOND,
+            Self::KM_D | Self::AU_D => units::DAY,
+        }
+    }
+
+    /// A length in these units as a dimensioned quantity
+    #[cfg(feature = "uom")]
+    pub fn length(&self, value: f64) -> uom::si::f64::Length {
+        uom::si::f64::Length::new::<uom::si::length::meter>(value * self.length_coefficient())
+    }
+
+    /// A time in these units as a dimensioned quantity
+    #[cfg(feature = "uom")]
+    pub fn time(&self, value: f64) -> uom::si::f64::Time {
+        uom::si::f64::Time::new::<uom::si::time::second>(value * self.time_coefficient())
+    }
+
+    /// A velocity in these units as a dimensioned quantity
+    #[cfg(feature = "uom")]
+    pub fn velocity(&self, value: f64) -> uom::si::f64::Velocity {
+        uom::si::f64::Velocity::new::<uom::si::velocity::meter_per_second>(
+            value * self.get_coefficient(),
+        )
+    }
+
+    /// Factors converting lengths, times and velocities in these units to `to`
+    pub fn conversion_to(&self, to: OutUnits) -> UnitConversion {
+        UnitConversion {
+            length: self.length_coefficient() / to.length_coefficient(),
+            time: self.time_coefficient() / to.time_coefficient(),
+            velocity: self.get_coefficient() / to.get_coefficient(),
+        }
+    }
+}
+
+/// Multiplicative factors between two [`OutUnits`], see [`OutUnits::conversion_to`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnitConversion {
+    pub length: f64,
+    pub time: f64,
+    pub velocity: f64,
 }
 
 #[repr(u8)]
@@ -192,9 +858,43 @@ pub enum RefSystem {
 #[cfg(test)]
 mod tests {
     use crate::{
-        request::ephemeris::{StepSize, StepSizeUnit},
+        request::ephemeris::{
+            format_epoch, CalendarDate, CalendarDateError, Epoch, InvalidIntervals,
+            InvalidStepDuration, JulianDate, StepSize, StepSizeUnit, TList, TListError, TimeScale,
+            TimeSpec, MAX_INTERVALS, MAX_TLIST_LEN,
+        },
         TestResult,
     };
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+
+    /// Deterministic xorshift generator, so failures are reproducible
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// Random epoch between 1000-01-01 and 9999-12-31 with nanosecond resolution
+        fn epoch(&mut self) -> DateTime<Utc> {
+            const MIN: i64 = -30_610_224_000;
+            const MAX: i64 = 253_402_300_799;
+
+            #[allow(clippy::cast_possible_wrap)]
+            let secs = MIN + (self.next() % (MAX - MIN) as u64) as i64;
+            #[allow(clippy::cast_possible_truncation)]
+            let nanos = match self.next() % 3 {
+                0 => 0,
+                1 => (self.next() % 1000) as u32 * 1_000_000,
+                _ => (self.next() % 1_000_000_000) as u32,
+            };
+
+            Utc.timestamp_opt(secs, nanos).unwrap()
+        }
+    }
 
     #[test]
     fn test_step_size() -> TestResult {
@@ -203,6 +903,316 @@ mod tests {
             serde_urlencoded::to_string([("STEP_SIZE", StepSize::new(6, StepSizeUnit::Hours))])?
         );
 
+        assert_eq!(
+            "STEP_SIZE=60+VAR",
+            serde_urlencoded::to_string([("STEP_SIZE", StepSize::angular(60))])?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tlist() -> TestResult {
+        let epoch = Utc.with_ymd_and_hms(2020, 5, 31, 0, 0, 0).unwrap();
+        let tlist = TList::new([
+            Epoch::from(JulianDate(2_459_010.)),
+            Epoch::from(epoch),
+            Epoch::from(JulianDate(2_459_000.5)),
+            Epoch::from(epoch + Duration::days(1)),
+            Epoch::from(epoch),
+        ])?;
+
+        // JD 2459000.5 is the same instant as the first of the calendar dates given
+        assert_eq!(3, tlist.epochs().len());
+        assert_eq!(
+            "tlist=2020-05-31T00%3A00%3A00Z%2C2020-06-01T00%3A00%3A00Z%2CJD2459010",
+            serde_urlencoded::to_string(TimeSpec::from(tlist))?
+        );
+
+        // The same Julian date in UT, TT and UT again
+        let ut = Epoch::from(JulianDate(2_459_000.5));
+        let tt = ut.with_scale(TimeScale::TT);
+        let tlist = TList::new([ut, tt, ut])?;
+        assert_eq!([ut, tt], tlist.epochs());
+
+        assert_eq!(Err(TListError::Empty), TList::new(Vec::<Epoch>::new()));
+        assert_eq!(
+            Err(TListError::TooLong(MAX_TLIST_LEN + 1)),
+            TList::new(
+                (0..=u32::try_from(MAX_TLIST_LEN)?)
+                    .map(|i| epoch + Duration::minutes(i64::from(i)))
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_intervals() -> TestResult {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let spec = TimeSpec::bounded(StepSize::intervals(12)?, start, start + Duration::days(1));
+
+        assert_eq!(
+            "step_size=12&start_time=2024-01-01T00%3A00%3A00Z&stop_time=2024-01-02T00%3A00%3A00Z",
+            serde_urlencoded::to_string(&spec)?
+        );
+        assert_eq!(Some(13), spec.estimated_records());
+
+        assert_eq!(Err(InvalidIntervals(0)), StepSize::intervals(0));
+        assert_eq!(
+            Err(InvalidIntervals(MAX_INTERVALS + 1)),
+            StepSize::intervals(MAX_INTERVALS + 1)
+        );
+
         Ok(())
     }
+
+    #[test]
+    fn test_step_from_duration() {
+        for (duration, expected) in [
+            (Duration::hours(6), "6h"),
+            (Duration::minutes(90), "90m"),
+            (Duration::days(2), "2d"),
+            (Duration::hours(48), "2d"),
+            (Duration::minutes(1), "1m"),
+        ] {
+            assert_eq!(
+                Ok(expected.to_owned()),
+                StepSize::try_from(duration).map(|step| step.to_string())
+            );
+        }
+
+        for duration in [
+            Duration::seconds(90),
+            Duration::zero(),
+            Duration::hours(-1),
+            Duration::days(5_000_000_000),
+        ] {
+            assert_eq!(
+                Err(InvalidStepDuration(duration)),
+                StepSize::try_from(duration)
+            );
+        }
+    }
+
+    #[test]
+    fn test_epoch_format() -> TestResult {
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..10_000 {
+            let epoch = rng.epoch();
+            let formatted = format_epoch(&epoch);
+            let bytes = formatted.as_bytes();
+
+            assert!(bytes.len() >= 20, "{formatted}");
+            for (i, &b) in bytes.iter().enumerate() {
+                let ok = match i {
+                    4 | 7 => b == b'-',
+                    10 => b == b'T',
+                    13 | 16 => b == b':',
+                    19 if bytes.len() > 20 => b == b'.',
+                    _ if i == bytes.len() - 1 => b == b'Z',
+                    _ => b.is_ascii_digit(),
+                };
+                assert!(ok, "unexpected byte {i} in {formatted}");
+            }
+
+            assert_eq!(epoch, DateTime::parse_from_rfc3339(&formatted)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_epoch_encoding() -> TestResult {
+        let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..1_000 {
+            let (start, stop) = (rng.epoch(), rng.epoch());
+            let encoded = serde_urlencoded::to_string(TimeSpec::bounded(
+                (1, StepSizeUnit::Days),
+                start,
+                stop,
+            ))?;
+
+            assert_eq!(
+                format!(
+                    "step_size=1d&start_time={}&stop_time={}",
+                    format_epoch(&start).replace(':', "%3A"),
+                    format_epoch(&stop).replace(':', "%3A")
+                ),
+                encoded
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_julian_date() -> TestResult {
+        assert_eq!(
+            "step_size=1d&start_time=JD2459000.5&stop_time=JD2459010",
+            serde_urlencoded::to_string(TimeSpec::bounded(
+                (1, StepSizeUnit::Days),
+                JulianDate(2_459_000.5),
+                JulianDate(2_459_010.),
+            ))?
+        );
+
+        let epoch = Utc.with_ymd_and_hms(2020, 5, 31, 0, 0, 0).unwrap();
+        assert_eq!(
+            "tlist=JD2459000.5%2C2020-05-31T00%3A00%3A00Z",
+            serde_urlencoded::to_string(TimeSpec::from_list([
+                Epoch::from(JulianDate(2_459_000.5)),
+                Epoch::from(epoch),
+            ]))?
+        );
+
+        #[allow(clippy::float_cmp)]
+        {
+            assert_eq!(2_459_000.5, Epoch::from(epoch).julian_date());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_time_scale() -> TestResult {
+        let epoch = Utc.with_ymd_and_hms(2020, 5, 31, 0, 0, 0).unwrap();
+
+        assert_eq!(
+            "step_size=1d&start_time=2020-05-31T00%3A00%3A00+TDB&stop_time=JD2459010+TDB",
+            serde_urlencoded::to_string(
+                TimeSpec::bounded((1, StepSizeUnit::Days), epoch, JulianDate(2_459_010.))
+                    .with_time_scale(TimeScale::TDB)
+            )?
+        );
+
+        assert_eq!(
+            "tlist=JD2459000.5+TT",
+            serde_urlencoded::to_string(
+                TimeSpec::from_list([JulianDate(2_459_000.5)]).with_time_scale(TimeScale::TT)
+            )?
+        );
+
+        assert_eq!(Some(TimeScale::TDB), TimeScale::from_label("JDTDB"));
+        assert_eq!(
+            TimeScale::TT,
+            Epoch::from(epoch).with_scale(TimeScale::TT).scale()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_calendar_date() -> TestResult {
+        let j2000 = CalendarDate::new(2000, 1, 1)?.and_hms(12, 0, 0)?;
+        assert_eq!(2_451_545., j2000.julian_date());
+        assert_eq!(
+            0.,
+            CalendarDate::bc(4713, 1, 1)?
+                .and_hms(12, 0, 0)?
+                .julian_date()
+        );
+
+        // The day before the Gregorian reform, in the Julian calendar
+        assert_eq!(
+            2_299_160.5,
+            CalendarDate::new(1582, 10, 4)?.julian_date() + 1.
+        );
+        assert_eq!(2_299_160.5, CalendarDate::new(1582, 10, 15)?.julian_date());
+        assert_eq!(
+            Err(CalendarDateError::InvalidDate(1582, 10, 10)),
+            CalendarDate::new(1582, 10, 10)
+        );
+        assert_eq!(CalendarDate::new(0, 1, 1), CalendarDate::bc(1, 1, 1));
+        assert_eq!(
+            Err(CalendarDateError::YearZeroBc),
+            CalendarDate::bc(0, 1, 1)
+        );
+
+        // 1500 was a leap year in the Julian calendar only
+        assert!(CalendarDate::new(1500, 2, 29).is_ok());
+        assert!(CalendarDate::new(1900, 2, 29).is_err());
+        assert!(CalendarDate::new(2000, 1, 1)?.and_hms(24, 0, 0).is_err());
+
+        assert_eq!(
+            "start_time=BC+0500-Mar-21+06%3A00%3A00&stop_time=9999-Dec-31+00%3A00%3A00+TDB",
+            serde_urlencoded::to_string([
+                (
+                    "start_time",
+                    Epoch::from(CalendarDate::bc(500, 3, 21)?.and_hms(6, 0, 0)?)
+                ),
+                (
+                    "stop_time",
+                    Epoch::from(CalendarDate::new(9999, 12, 31)?).with_scale(TimeScale::TDB)
+                ),
+            ])?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_split() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let spec = TimeSpec::bounded((1, StepSizeUnit::Hours), start, start + Duration::days(1));
+
+        // 25 records in chunks of up to 10: 0h-9h, 9h-18h, 18h-24h
+        let spans = spec.split(10);
+        assert_eq!(
+            vec![
+                TimeSpec::bounded((1, StepSizeUnit::Hours), start, start + Duration::hours(9)),
+                TimeSpec::bounded(
+                    (1, StepSizeUnit::Hours),
+                    start + Duration::hours(9),
+                    start + Duration::hours(18)
+                ),
+                TimeSpec::bounded(
+                    (1, StepSizeUnit::Hours),
+                    start + Duration::hours(18),
+                    start + Duration::days(1)
+                ),
+            ],
+            spans
+        );
+        assert!(spans
+            .iter()
+            .all(|span| span.estimated_records() <= Some(10)));
+
+        assert_eq!(vec![spec.clone()], spec.split(25));
+
+        // The stop time need not be on a step
+        let odd = TimeSpec::bounded(
+            (1, StepSizeUnit::Hours),
+            Epoch::from(JulianDate(2_460_000.5)).with_scale(TimeScale::TDB),
+            Epoch::from(JulianDate(2_460_001.01)).with_scale(TimeScale::TDB),
+        );
+        let spans = odd.split(6);
+        assert_eq!(3, spans.len());
+        assert_eq!(Some(3), spans.last().and_then(TimeSpec::estimated_records));
+        assert!(matches!(
+            spans[1],
+            TimeSpec::Bounded { start_time, .. }
+                if start_time.scale() == TimeScale::TDB
+                    && (start_time.julian_date() - 2_460_000.5 - 5. / 24.).abs() < 1e-9
+        ));
+
+        for whole in [
+            TimeSpec::bounded(
+                (12, StepSizeUnit::Unitless),
+                start,
+                start + Duration::days(1),
+            ),
+            TimeSpec::bounded(
+                (1, StepSizeUnit::Months),
+                start,
+                start + Duration::days(400),
+            ),
+            TimeSpec::from_list([start, start + Duration::days(1)]),
+        ] {
+            assert_eq!(vec![whole.clone()], whole.split(2));
+        }
+    }
 }
@@ -0,0 +1,117 @@
+#![allow(clippy::module_name_repetitions)]
+
+use crate::request::{
+    ephemeris::{serialize_epoch, EphemType, Format},
+    Command,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use thiserror::Error;
+
+/// Request for a binary SPK file covering `start_time` to `stop_time`.
+///
+/// Horizons only generates SPK files for small bodies, and returns them base64-encoded in
+/// JSON output. See [`crate::response::spk::SpkFile`] for decoding the response.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Spk {
+    format: Format,
+    command: Command,
+    ephem_type: EphemType,
+    #[serde(serialize_with = "serialize_epoch")]
+    start_time: DateTime<Utc>,
+    #[serde(serialize_with = "serialize_epoch")]
+    stop_time: DateTime<Utc>,
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpkBuilderError {
+    #[error("Unintialized field `{0}`")]
+    UninitializedField(&'static str),
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct SpkBuilder {
+    command: Option<Command>,
+    start_time: Option<DateTime<Utc>>,
+    stop_time: Option<DateTime<Utc>>,
+}
+
+impl SpkBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn command(&mut self, command: impl Into<Command>) -> &mut Self {
+        self.command = Some(command.into());
+        self
+    }
+
+    pub fn start_time(&mut self, start_time: DateTime<Utc>) -> &mut Self {
+        self.start_time = Some(start_time);
+        self
+    }
+
+    pub fn stop_time(&mut self, stop_time: DateTime<Utc>) -> &mut Self {
+        self.stop_time = Some(stop_time);
+        self
+    }
+
+    pub fn build(&self) -> Result<Spk, SpkBuilderError> {
+        let command = self
+            .command
+            .clone()
+            .ok_or(SpkBuilderError::UninitializedField("command"))?;
+
+        let start_time = self
+            .start_time
+            .ok_or(SpkBuilderError::UninitializedField("start_time"))?;
+
+        let stop_time = self
+            .stop_time
+            .ok_or(SpkBuilderError::UninitializedField("stop_time"))?;
+
+        Ok(Spk {
+            format: Format::Json,
+            command,
+            ephem_type: EphemType::Spk,
+            start_time,
+            stop_time,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        request::{
+            bodies::SmallBody,
+            ephemeris::spk::{SpkBuilder, SpkBuilderError},
+        },
+        TestResult,
+    };
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_spk_builder() -> TestResult {
+        let mut builder = SpkBuilder::new();
+
+        builder
+            .command(SmallBody::designation("2000433"))
+            .start_time(Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap());
+
+        assert_eq!(
+            Err(SpkBuilderError::UninitializedField("stop_time")),
+            builder.build()
+        );
+
+        builder.stop_time(Utc.with_ymd_and_hms(2031, 1, 1, 0, 0, 0).unwrap());
+
+        assert_eq!(
+            "format=json&command=DES%3D2000433%3B&ephem_type=SPK\
+            &start_time=2030-01-01T00%3A00%3A00Z&stop_time=2031-01-01T00%3A00%3A00Z",
+            serde_urlencoded::to_string(builder.build()?)?
+        );
+
+        Ok(())
+    }
+}
@@ -57,6 +57,32 @@ pub struct Vectors {
     ref_plane: RefPlane,
 }
 
+impl Vectors {
+    pub(crate) fn out_units(&self) -> OutUnits {
+        self.out_units
+    }
+
+    pub(crate) fn set_out_units(&mut self, out_units: OutUnits) {
+        self.out_units = out_units;
+    }
+
+    pub(crate) fn ref_plane(&self) -> RefPlane {
+        self.ref_plane
+    }
+
+    /// A builder with every field set as in `self`
+    pub fn to_builder(&self) -> VectorsBuilder {
+        VectorsBuilder {
+            vec_table: self.vec_table,
+            vec_labels: self.vec_labels.into(),
+            vec_delta_t: self.vec_delta_t.into(),
+            vec_corr: self.vec_corr,
+            out_units: self.out_units,
+            ref_plane: self.ref_plane,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct VectorsBuilder {
     vec_table: TableFormat,
@@ -102,6 +128,30 @@ impl VectorsBuilder {
         self
     }
 
+    pub fn get_table_format(&self) -> TableFormat {
+        self.vec_table
+    }
+
+    pub fn get_vec_labels(&self) -> bool {
+        self.vec_labels
+    }
+
+    pub fn get_vec_delta_t(&self) -> bool {
+        self.vec_delta_t
+    }
+
+    pub fn get_vec_corr(&self) -> Correction {
+        self.vec_corr
+    }
+
+    pub fn get_out_units(&self) -> OutUnits {
+        self.out_units
+    }
+
+    pub fn get_ref_plane(&self) -> RefPlane {
+        self.ref_plane
+    }
+
     pub fn build(&self) -> Vectors {
         let &Self {
             vec_table,
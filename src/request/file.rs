@@ -0,0 +1,140 @@
+//! The input files of the Horizons file API ([`ApiEndpoint::HORIZONS_FILE`]), which takes
+//! a query as a posted form instead of a URL, so that long lists of epochs fit.
+//!
+//! A file holds one `NAME='value'` line per parameter between `!$$SOF` and `!$$EOF`, with
+//! the epochs of a `TLIST` on lines of their own.
+
+use super::{ephemeris::TimeSpec, params::ApiEndpoint, Query};
+
+/// Separates the parts of the form of [`Query::to_form`]. No input file contains it, as
+/// parameter values are all on lines starting with a name or a quote.
+const BOUNDARY: &str = "horizons-rs-input-file";
+
+/// The `Content-Type` of the body of [`Query::to_form`]
+pub const FORM_CONTENT_TYPE: &str = "multipart/form-data; boundary=horizons-rs-input-file";
+
+/// `value` in single quotes, unless it already is, like site coordinates
+fn quoted(value: &str) -> String {
+    if value.starts_with('\'') {
+        value.to_owned()
+    } else {
+        format!("'{value}'")
+    }
+}
+
+impl Query {
+    /// The input file of the query, with the same parameters as [`Query::params`] except
+    /// `format`, which the file API takes as a separate field of the form
+    pub fn to_input_file(&self) -> String {
+        let mut file = String::from("!$$SOF\n");
+
+        for (name, value) in self.params() {
+            let name = name.to_ascii_uppercase();
+
+            match (name.as_str(), self.time_spec()) {
+                ("FORMAT", _) => {}
+                ("TLIST", TimeSpec::List { tlist }) => {
+                    file.push_str("TLIST=\n");
+
+                    for epoch in tlist.epochs() {
+                        file.push_str(&quoted(&epoch.to_string()));
+                        file.push('\n');
+                    }
+                }
+                _ => {
+                    file.push_str(&name);
+                    file.push('=');
+                    file.push_str(&quoted(&value));
+                    file.push('\n');
+                }
+            }
+        }
+
+        file.push_str("!$$EOF\n");
+        file
+    }
+
+    /// The `multipart/form-data` body to POST to the file API, of content type
+    /// [`FORM_CONTENT_TYPE`]
+    pub fn to_form(&self) -> Vec<u8> {
+        let format = self
+            .params()
+            .into_iter()
+            .find(|(name, _)| name == "format")
+            .map_or_else(|| "text".to_owned(), |(_, value)| value);
+
+        format!(
+            "--{BOUNDARY}\r\n\
+             Content-Disposition: form-data; name=\"format\"\r\n\r\n\
+             {format}\r\n\
+             --{BOUNDARY}\r\n\
+             Content-Disposition: form-data; name=\"input\"; filename=\"input.txt\"\r\n\
+             Content-Type: text/plain\r\n\r\n\
+             {}\r\n\
+             --{BOUNDARY}--\r\n",
+            self.to_input_file()
+        )
+        .into_bytes()
+    }
+}
+
+impl ApiEndpoint {
+    /// The official file API at JPL
+    pub fn file() -> Self {
+        Self::new(Self::HORIZONS_FILE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        request::{
+            bodies::MajorBody,
+            ephemeris::{Epoch, JulianDate, TimeScale, TimeSpec},
+            file::FORM_CONTENT_TYPE,
+            presets::state_vectors,
+            GeodeticCoord,
+        },
+        TestResult,
+    };
+
+    #[test]
+    fn test_input_file() -> TestResult {
+        let query = state_vectors(
+            MajorBody::Moon,
+            (GeodeticCoord::new(-116.89, 35.43, 1.0)?, MajorBody::Earth),
+            TimeSpec::from_list([
+                Epoch::from(JulianDate(2_459_000.5)),
+                Epoch::from(JulianDate(2_459_001.5)),
+            ])
+            .with_time_scale(TimeScale::TDB),
+        )?;
+
+        let file = query.to_input_file();
+        let lines: Vec<&str> = file.lines().collect();
+
+        assert_eq!(Some(&"!$$SOF"), lines.first());
+        assert_eq!(Some(&"!$$EOF"), lines.last());
+        assert!(lines.contains(&"COMMAND='301'"));
+        assert!(lines.contains(&"SITE_COORD='-116.89,35.43,1'"));
+        assert!(!lines.iter().any(|line| line.starts_with("FORMAT=")));
+
+        let tlist = lines.iter().position(|line| *line == "TLIST=").unwrap_or(0);
+        assert_eq!(
+            ["'JD2459000.5 TDB'", "'JD2459001.5 TDB'"],
+            lines[tlist + 1..tlist + 3]
+        );
+
+        let form = String::from_utf8(query.to_form())?;
+        let boundary = FORM_CONTENT_TYPE
+            .split("boundary=")
+            .nth(1)
+            .unwrap_or_default();
+        assert!(form.starts_with(&format!("--{boundary}\r\n")));
+        assert!(form.ends_with(&format!("--{boundary}--\r\n")));
+        assert!(form.contains("name=\"format\"\r\n\r\ntext\r\n"));
+        assert!(form.contains(&file));
+
+        Ok(())
+    }
+}
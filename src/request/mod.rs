@@ -2,19 +2,29 @@
 
 pub mod bodies;
 pub mod ephemeris;
+pub mod file;
+pub mod observatory;
+pub mod params;
 pub mod presets;
+pub mod session;
+pub mod stations;
+pub mod tle;
+pub mod typed;
 
 use std::fmt::{Display, Formatter, Result as FmtResult};
 
-use bodies::MajorBody;
+use bodies::{MajorBody, SmallBody, Spacecraft, SpkId};
 use ephemeris::{
     common::{Common, CommonBuilder, CommonBuilderError},
     elements::{Elements, ElementsBuilder},
     vectors::{Vectors, VectorsBuilder},
-    EphemType,
+    EphemType, OutUnits, RefPlane, RefSystem, TimeSpec, TimeSpecError, UnitConversion,
 };
+use observatory::Observatory;
+use params::ParamsError;
 use serde::Serialize;
 use thiserror::Error;
+use tle::Tle;
 
 #[repr(u8)]
 #[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -35,24 +45,81 @@ impl From<bool> for HzBool {
     }
 }
 
+impl From<HzBool> for bool {
+    fn from(b: HzBool) -> Self {
+        b == HzBool::Yes
+    }
+}
+
 #[derive(Serialize, Debug, Clone, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum Command {
     Body(Body),
+    SmallBody(SmallBody),
+    /// Serialized as `TLE`, with the element set itself sent as the `TLE` parameter
+    #[serde(serialize_with = "serialize_tle_command")]
+    Tle(Tle),
     /// Only use this variant if you are absolutely sure about what you are doing
     Custom(String),
 }
 
+fn serialize_tle_command<S>(_: &Tle, s: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    s.serialize_str("TLE")
+}
+
 impl<B: Into<Body>> From<B> for Command {
     fn from(b: B) -> Self {
         Self::Body(b.into())
     }
 }
 
+impl From<SmallBody> for Command {
+    fn from(b: SmallBody) -> Self {
+        Self::SmallBody(b)
+    }
+}
+
+impl From<SpkId> for Command {
+    fn from(id: SpkId) -> Self {
+        Self::SmallBody(id.into())
+    }
+}
+
+impl From<Tle> for Command {
+    fn from(tle: Tle) -> Self {
+        Self::Tle(tle)
+    }
+}
+
+/// e.g. `Jupiter (599)`
+impl Display for Command {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Command::Body(body) => body.fmt_named(f),
+            Command::SmallBody(body) => body.fmt(f),
+            Command::Tle(tle) => f.write_str(tle.name().unwrap_or("TLE")),
+            Command::Custom(s) => f.write_str(s),
+        }
+    }
+}
+
+impl Command {
+    pub(crate) fn tle(&self) -> Option<&Tle> {
+        match self {
+            Command::Tle(tle) => Some(tle),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Serialize, Debug, Clone, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum Body {
     MajorBody(MajorBody),
+    Spacecraft(Spacecraft),
     /// Only use this variant if you are absolutely sure about what you are doing
     Custom(String),
 }
@@ -63,20 +130,228 @@ impl From<MajorBody> for Body {
     }
 }
 
+impl From<Spacecraft> for Body {
+    fn from(b: Spacecraft) -> Self {
+        Body::Spacecraft(b)
+    }
+}
+
 impl Display for Body {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match self {
             Body::MajorBody(b) => write!(f, "{}", u32::from(b)),
+            Body::Spacecraft(b) => write!(f, "{}", i32::from(b)),
             Body::Custom(s) => f.write_str(s),
         }
     }
 }
 
+impl Body {
+    /// Name and code of a typed body, e.g. `Jupiter (599)`
+    fn fmt_named(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Body::MajorBody(b) => write!(f, "{b} ({self})"),
+            Body::Spacecraft(b) => write!(f, "{b} ({self})"),
+            Body::Custom(s) => f.write_str(s),
+        }
+    }
+}
+
+/// Geodetic coordinates of a site on the surface of a body
+#[derive(Debug, Clone, Copy)]
+pub struct GeodeticCoord {
+    lon: f64,
+    lat: f64,
+    alt_km: f64,
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq)]
+pub enum CoordError {
+    #[error("The {0} is not finite")]
+    NotFinite(&'static str),
+    #[error("Latitude {0} is outside [-90, 90]")]
+    LatitudeOutOfRange(f64),
+    #[error("Longitude {0} is outside [-180, 360)")]
+    LongitudeOutOfRange(f64),
+}
+
+/// Checks that all values are finite and that the longitude is in [-180, 360)
+fn check_coord(lon: f64, values: [(&'static str, f64); 3]) -> Result<(), CoordError> {
+    if let Some((name, _)) = values.iter().find(|(_, value)| !value.is_finite()) {
+        return Err(CoordError::NotFinite(name));
+    }
+
+    if (-180. ..360.).contains(&lon) {
+        Ok(())
+    } else {
+        Err(CoordError::LongitudeOutOfRange(lon))
+    }
+}
+
+impl GeodeticCoord {
+    /// East longitude `lon` in [-180, 360) and latitude `lat` in [-90, 90], in degrees, and
+    /// altitude above the reference ellipsoid `alt_km`, in km
+    pub fn new(lon: f64, lat: f64, alt_km: f64) -> Result<Self, CoordError> {
+        check_coord(
+            lon,
+            [("longitude", lon), ("latitude", lat), ("altitude", alt_km)],
+        )?;
+
+        if !(-90. ..=90.).contains(&lat) {
+            return Err(CoordError::LatitudeOutOfRange(lat));
+        }
+
+        Ok(Self { lon, lat, alt_km })
+    }
+
+    /// East longitude in degrees
+    pub fn lon(&self) -> f64 {
+        self.lon
+    }
+
+    /// Latitude in degrees
+    pub fn lat(&self) -> f64 {
+        self.lat
+    }
+
+    /// Altitude above the reference ellipsoid in km
+    pub fn alt_km(&self) -> f64 {
+        self.alt_km
+    }
+}
+
+/// Compares the exact bit patterns, so that coordinates can be part of `Eq` queries. The
+/// values are finite, so there is no NaN to be unequal to itself.
+impl PartialEq for GeodeticCoord {
+    fn eq(&self, other: &Self) -> bool {
+        self.lon.to_bits() == other.lon.to_bits()
+            && self.lat.to_bits() == other.lat.to_bits()
+            && self.alt_km.to_bits() == other.alt_km.to_bits()
+    }
+}
+
+impl Eq for GeodeticCoord {}
+
+impl Display for GeodeticCoord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{},{},{}", self.lon, self.lat, self.alt_km)
+    }
+}
+
+impl Serialize for GeodeticCoord {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        s.serialize_str(&format!("'{self}'"))
+    }
+}
+
+/// Site position relative to the rotation axis of the center body
+#[derive(Debug, Clone, Copy)]
+pub struct CylindricalCoord {
+    lon: f64,
+    dxy_km: f64,
+    dz_km: f64,
+}
+
+impl CylindricalCoord {
+    /// East longitude `lon` in [-180, 360) degrees, distance from the rotation axis `dxy_km`
+    /// and height above the equatorial plane `dz_km`, in km
+    pub fn new(lon: f64, dxy_km: f64, dz_km: f64) -> Result<Self, CoordError> {
+        check_coord(
+            lon,
+            [("longitude", lon), ("distance", dxy_km), ("height", dz_km)],
+        )?;
+
+        Ok(Self { lon, dxy_km, dz_km })
+    }
+
+    /// East longitude in degrees
+    pub fn lon(&self) -> f64 {
+        self.lon
+    }
+
+    /// Distance from the rotation axis in km
+    pub fn dxy_km(&self) -> f64 {
+        self.dxy_km
+    }
+
+    /// Height above the equatorial plane in km
+    pub fn dz_km(&self) -> f64 {
+        self.dz_km
+    }
+}
+
+/// Compares the exact bit patterns, like [`GeodeticCoord`]
+impl PartialEq for CylindricalCoord {
+    fn eq(&self, other: &Self) -> bool {
+        self.lon.to_bits() == other.lon.to_bits()
+            && self.dxy_km.to_bits() == other.dxy_km.to_bits()
+            && self.dz_km.to_bits() == other.dz_km.to_bits()
+    }
+}
+
+impl Eq for CylindricalCoord {}
+
+impl Display for CylindricalCoord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{},{},{}", self.lon, self.dxy_km, self.dz_km)
+    }
+}
+
+impl Serialize for CylindricalCoord {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        s.serialize_str(&format!("'{self}'"))
+    }
+}
+
+#[repr(u8)]
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordType {
+    #[serde(rename = "GEODETIC")]
+    Geodetic,
+    #[serde(rename = "CYLINDRICAL")]
+    Cylindrical,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(untagged)]
+pub(crate) enum Coord {
+    Geodetic(GeodeticCoord),
+    Cylindrical(CylindricalCoord),
+}
+
+impl Display for Coord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Coord::Geodetic(coord) => coord.fmt(f),
+            Coord::Cylindrical(coord) => coord.fmt(f),
+        }
+    }
+}
+
+/// `COORD_TYPE` and `SITE_COORD` parameters, emitted when the center is a coordinate site
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SiteCoord {
+    coord_type: CoordType,
+    site_coord: Coord,
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum Site {
     #[default]
     Center,
     Custom(u16),
+    /// Observatory on the Earth, by MPC code
+    Observatory(Observatory),
+    /// Arbitrary location on or above the surface of the center body
+    Coord(GeodeticCoord),
+    /// Arbitrary location relative to the rotation axis of the center body
+    Cylindrical(CylindricalCoord),
 }
 
 impl From<u16> for Site {
@@ -85,11 +360,31 @@ impl From<u16> for Site {
     }
 }
 
+impl From<Observatory> for Site {
+    fn from(observatory: Observatory) -> Self {
+        Site::Observatory(observatory)
+    }
+}
+
+impl From<GeodeticCoord> for Site {
+    fn from(coord: GeodeticCoord) -> Self {
+        Site::Coord(coord)
+    }
+}
+
+impl From<CylindricalCoord> for Site {
+    fn from(coord: CylindricalCoord) -> Self {
+        Site::Cylindrical(coord)
+    }
+}
+
 impl Display for Site {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match self {
             Site::Center => f.write_str("500"),
             Site::Custom(s) => write!(f, "{s}"),
+            Site::Observatory(obs) => write!(f, "{obs}"),
+            Site::Coord(_) | Site::Cylindrical(_) => f.write_str("coord"),
         }
     }
 }
@@ -118,12 +413,51 @@ impl<S: Into<Site>, B: Into<Body>> From<(S, B)> for Center {
     }
 }
 
+impl Center {
+    /// The ID of the body, e.g. `10`, the form Horizons echoes in parentheses after its name
+    pub(crate) fn body_id(&self) -> Option<String> {
+        match &self.body {
+            Body::MajorBody(_) | Body::Spacecraft(_) => Some(self.body.to_string()),
+            Body::Custom(_) => None,
+        }
+    }
+
+    pub(crate) fn site_coord(&self) -> Option<SiteCoord> {
+        match self.site {
+            Site::Coord(coord) => Some(SiteCoord {
+                coord_type: CoordType::Geodetic,
+                site_coord: Coord::Geodetic(coord),
+            }),
+            Site::Cylindrical(coord) => Some(SiteCoord {
+                coord_type: CoordType::Cylindrical,
+                site_coord: Coord::Cylindrical(coord),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// e.g. `Sun (10)`, or `T16@Earth (399)` for a site on the body
+impl Display for Center {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if self.site != Site::Center {
+            write!(f, "{}@", self.site)?;
+        }
+
+        self.body.fmt_named(f)
+    }
+}
+
 impl Serialize for Center {
     fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        s.serialize_str(&format!("{}@{}", self.site, self.body))
+        match (&self.site, &self.body) {
+            // Spacecraft have no surface sites, and are addressed as `@-NNN`
+            (Site::Center, Body::Spacecraft(_)) => s.serialize_str(&format!("@{}", self.body)),
+            (site, body) => s.serialize_str(&format!("{site}@{body}")),
+        }
     }
 }
 
@@ -135,14 +469,40 @@ pub enum Ephemeris {
     Vectors(Vectors),
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+/// Serializes to the parameters of [`Query::params`], in that order
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Query {
-    #[serde(flatten)]
     common: Common,
-    #[serde(flatten)]
     specific: Ephemeris,
 }
 
+/// Fields of a [`Query`] in serde's flattened form, before ordering
+#[derive(Serialize)]
+struct QueryFields<'a> {
+    #[serde(flatten)]
+    common: &'a Common,
+    #[serde(flatten)]
+    specific: &'a Ephemeris,
+}
+
+impl Serialize for Query {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::{Error as _, SerializeMap};
+
+        let params = self.try_params().map_err(S::Error::custom)?;
+        let mut map = s.serialize_map(Some(params.len()))?;
+
+        for (name, value) in &params {
+            map.serialize_entry(name, value)?;
+        }
+
+        map.end()
+    }
+}
+
 impl Query {
     /// # Example
     /// ```ignore
@@ -187,6 +547,163 @@ impl Query {
             specific: VectorsBuilder::default(),
         }
     }
+
+    /// A builder with every setting of the query, to change some of them and build a new
+    /// query, e.g. the same query over a later time span:
+    /// ```ignore
+    /// let mut builder = query.to_builder();
+    /// builder.common().time_spec(later);
+    /// let later_query = builder.build()?;
+    /// ```
+    pub fn to_builder(&self) -> AnyQueryBuilder {
+        let common = self.common.to_builder();
+
+        match &self.specific {
+            Ephemeris::Elements(elements) => AnyQueryBuilder::Elements(QueryBuilder {
+                common,
+                specific: elements.to_builder(),
+            }),
+            Ephemeris::Vectors(vectors) => AnyQueryBuilder::Vectors(QueryBuilder {
+                common,
+                specific: vectors.to_builder(),
+            }),
+        }
+    }
+
+    pub(crate) fn center(&self) -> &Center {
+        self.common.center()
+    }
+
+    pub(crate) fn set_command(&mut self, command: Command) {
+        self.common.set_command(command);
+    }
+
+    pub(crate) fn time_spec(&self) -> &TimeSpec {
+        self.common.time_spec()
+    }
+
+    pub(crate) fn ref_system(&self) -> RefSystem {
+        self.common.ref_system()
+    }
+
+    pub(crate) fn ref_plane(&self) -> RefPlane {
+        match &self.specific {
+            Ephemeris::Elements(elements) => elements.ref_plane(),
+            Ephemeris::Vectors(vectors) => vectors.ref_plane(),
+        }
+    }
+
+    /// Units of distances, times and velocities in the response
+    pub fn out_units(&self) -> OutUnits {
+        match &self.specific {
+            Ephemeris::Elements(elements) => elements.out_units(),
+            Ephemeris::Vectors(vectors) => vectors.out_units(),
+        }
+    }
+
+    /// Switches the query to `out_units`, returning the factors that convert values
+    /// expected in the old units (such as tolerances or cached results) to the new ones.
+    /// Parsed tables can be converted with [`VectorTable::to_units`].
+    ///
+    /// [`VectorTable::to_units`]: crate::response::ephemeris::vectors::VectorTable::to_units
+    pub fn set_out_units(&mut self, out_units: OutUnits) -> UnitConversion {
+        let conversion = self.out_units().conversion_to(out_units);
+
+        match &mut self.specific {
+            Ephemeris::Elements(elements) => elements.set_out_units(out_units),
+            Ephemeris::Vectors(vectors) => vectors.set_out_units(out_units),
+        }
+
+        conversion
+    }
+
+    /// The `(name, value)` pairs of the query, ordered by [`PARAM_ORDER`] regardless of how
+    /// the query is built, so that encoded queries can serve as cache keys and in golden
+    /// tests.
+    ///
+    /// # Panics
+    ///
+    /// Never for a query built by this crate, whose parameters are all scalars.
+    ///
+    /// [`PARAM_ORDER`]: params::PARAM_ORDER
+    pub fn params(&self) -> Vec<(String, String)> {
+        self.try_params()
+            .expect("query parameters should all be scalars")
+    }
+
+    fn try_params(&self) -> Result<Vec<(String, String)>, ParamsError> {
+        params::to_params(&QueryFields {
+            common: &self.common,
+            specific: &self.specific,
+        })
+    }
+
+    /// Fails if the query would return more than `max_records` records, judging from its
+    /// time span and step size, so that accidentally large queries can be rejected before
+    /// they are sent. Queries with a zero or variable step are let through.
+    pub fn check_size(&self, max_records: u64) -> Result<(), TooLarge> {
+        match self.common.time_spec().estimated_records() {
+            Some(estimated) if estimated > max_records => Err(TooLarge {
+                estimated,
+                max: max_records,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Copies of the query over consecutive parts of its time span, each returning at most
+    /// `max_records` records; see [`TimeSpec::split`]
+    pub fn split(&self, max_records: u64) -> Vec<Query> {
+        self.common
+            .time_spec()
+            .split(max_records)
+            .into_iter()
+            .map(|time_spec| {
+                let mut query = self.clone();
+                query.common.set_time_spec(time_spec);
+                query
+            })
+            .collect()
+    }
+}
+
+/// Summary for logs, e.g.
+/// `Jupiter (599) rel. SolarSystemBary (0), vectors, 2022-08-28T00:00:00Z→2022-08-30T00:00:00Z @6h`
+impl Display for Query {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let kind = match self.common.ephem_type() {
+            EphemType::Observer => "observer",
+            EphemType::Elements => "elements",
+            EphemType::Vectors => "vectors",
+            EphemType::Spk => "SPK",
+        };
+
+        write!(
+            f,
+            "{} rel. {}, {kind}, ",
+            self.common.command(),
+            self.common.center()
+        )?;
+
+        match self.common.time_spec() {
+            TimeSpec::Bounded {
+                step_size,
+                start_time,
+                stop_time,
+            } => write!(f, "{start_time}→{stop_time} @{step_size}"),
+            TimeSpec::List { tlist } => match tlist.epochs() {
+                [epoch] => epoch.fmt(f),
+                epochs => write!(f, "{} epochs", epochs.len()),
+            },
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("The query would return about {estimated} records, more than the limit of {max}")]
+pub struct TooLarge {
+    pub estimated: u64,
+    pub max: u64,
 }
 
 /// Do not use this struct directly. Use one of the functions on [`Query`] instead.
@@ -195,18 +712,100 @@ pub struct QueryBuilder<T> {
     pub specific: T,
 }
 
+/// The builder of a [`Query`] of any ephemeris type, from [`Query::to_builder`]
+pub enum AnyQueryBuilder {
+    Elements(QueryBuilder<ElementsBuilder>),
+    Vectors(QueryBuilder<VectorsBuilder>),
+}
+
+impl AnyQueryBuilder {
+    /// The builder of the parameters shared by all ephemeris types
+    pub fn common(&mut self) -> &mut CommonBuilder {
+        match self {
+            Self::Elements(builder) => &mut builder.common,
+            Self::Vectors(builder) => &mut builder.common,
+        }
+    }
+
+    pub fn build(&self) -> Result<Query, QueryBuilderError> {
+        match self {
+            Self::Elements(builder) => builder.build(),
+            Self::Vectors(builder) => builder.build(),
+        }
+    }
+}
+
 #[derive(Error, Debug, Clone)]
 pub enum QueryBuilderError {
     #[error("{0}")]
     CommonBuilderError(CommonBuilderError),
+    #[error("{0}")]
+    TimeSpecError(TimeSpecError),
+}
+
+crate::impl_from_for_inner_enum!(QueryBuilderError: CommonBuilderError, TimeSpecError);
+
+/// Builds the common part of a query, validating its [`TimeSpec`] for its ephemeris type
+fn build_common(common: &CommonBuilder) -> Result<Common, QueryBuilderError> {
+    let common = common.build()?;
+    common.time_spec().validate()?;
+
+    if let TimeSpec::Bounded { step_size, .. } = common.time_spec() {
+        if step_size.is_variable() && common.ephem_type() != EphemType::Observer {
+            return Err(TimeSpecError::VariableStep.into());
+        }
+    }
+
+    Ok(common)
+}
+
+/// Consuming setters, to build a query in one expression:
+/// `Query::vectors().with_command(..).with_center(..).with_time_spec(..).build()?`
+impl<T> QueryBuilder<T> {
+    #[must_use]
+    pub fn with_command(mut self, command: impl Into<Command>) -> Self {
+        self.common.command(command);
+        self
+    }
+
+    #[must_use]
+    pub fn with_center(mut self, center: impl Into<Center>) -> Self {
+        self.common.center(center);
+        self
+    }
+
+    #[must_use]
+    pub fn with_time_spec(mut self, time_spec: TimeSpec) -> Self {
+        self.common.time_spec(time_spec);
+        self
+    }
+
+    /// Applies the `&mut` setters of [`CommonBuilder`] in `f`
+    #[must_use]
+    pub fn with_common(mut self, f: impl FnOnce(&mut CommonBuilder) -> &mut CommonBuilder) -> Self {
+        f(&mut self.common);
+        self
+    }
+
+    /// Applies the `&mut` setters of the ephemeris type's builder in `f`
+    #[must_use]
+    pub fn with_specific(mut self, f: impl FnOnce(&mut T) -> &mut T) -> Self {
+        f(&mut self.specific);
+        self
+    }
 }
 
-crate::impl_from_for_inner_enum!(QueryBuilderError: CommonBuilderError);
+crate::assert_send_sync!(
+    Query,
+    QueryBuilder<ElementsBuilder>,
+    QueryBuilder<VectorsBuilder>,
+    AnyQueryBuilder
+);
 
 impl QueryBuilder<ElementsBuilder> {
     pub fn build(&self) -> Result<Query, QueryBuilderError> {
         Ok(Query {
-            common: self.common.build()?,
+            common: build_common(&self.common)?,
             specific: Ephemeris::Elements(self.specific.build()),
         })
     }
@@ -215,7 +814,7 @@ impl QueryBuilder<ElementsBuilder> {
 impl QueryBuilder<VectorsBuilder> {
     pub fn build(&self) -> Result<Query, QueryBuilderError> {
         Ok(Query {
-            common: self.common.build()?,
+            common: build_common(&self.common)?,
             specific: Ephemeris::Vectors(self.specific.build()),
         })
     }
@@ -225,9 +824,15 @@ impl QueryBuilder<VectorsBuilder> {
 mod tests {
     use crate::{
         request::{
-            bodies::{InvalidBodyCode, MajorBody},
-            ephemeris::{vectors::Correction, StepSizeUnit, TimeSpec},
-            HzBool, Query,
+            bodies::{
+                Apparition, Comet, InvalidBodyCode, InvalidBodyName, InvalidDesignation,
+                InvalidSpkId, MajorBody, SmallBody, Spacecraft, SpkId, SpkIdKind,
+            },
+            ephemeris::{vectors::Correction, OutUnits, StepSizeUnit, TimeSpec, TimeSpecError},
+            observatory::Observatory,
+            tle::Tle,
+            Body, Center, Command, CoordError, CylindricalCoord, GeodeticCoord, HzBool, Query,
+            QueryBuilderError, TooLarge,
         },
         TestResult,
     };
@@ -259,10 +864,257 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_major_body_name() {
+        for (name, body) in [
+            ("jupiter", MajorBody::Jupiter),
+            ("Jupiter Barycenter", MajorBody::JupiterBary),
+            ("Earth-Moon barycentre", MajorBody::EarthMoonBary),
+            ("SSB", MajorBody::SolarSystemBary),
+            ("Luna", MajorBody::Moon),
+            ("S/2010 J 1", MajorBody::S2010J1),
+            ("EM-L2", MajorBody::EM_L2),
+            ("599", MajorBody::Jupiter),
+        ] {
+            assert_eq!(Ok(body), name.parse());
+        }
+
+        assert_eq!(
+            Err(InvalidBodyName("Vulcan".to_owned())),
+            MajorBody::from_name("Vulcan")
+        );
+        assert!(MajorBody::from_name("598").is_err());
+
+        assert_eq!(Ok(Spacecraft::Jwst), "JWST".parse());
+        assert_eq!(Ok(Spacecraft::Hubble), "-48".parse());
+    }
+
+    #[test]
+    fn test_major_body_categories() {
+        let planets: Vec<_> = MajorBody::iter().filter(MajorBody::is_planet).collect();
+        assert_eq!(9, planets.len());
+        assert_eq!(MajorBody::Mercury, planets[0]);
+
+        assert_eq!(
+            10,
+            MajorBody::iter().filter(MajorBody::is_barycenter).count()
+        );
+        assert_eq!(
+            8,
+            MajorBody::iter()
+                .filter(MajorBody::is_lagrange_point)
+                .count()
+        );
+
+        let saturnian = MajorBody::iter().filter(|body| body.is_moon_of(MajorBody::Saturn));
+        assert_eq!(82, saturnian.count());
+
+        assert!(MajorBody::Moon.is_moon_of(MajorBody::EarthMoonBary));
+        assert!(MajorBody::S2003J2.is_moon_of(MajorBody::Jupiter));
+        assert!(!MajorBody::EM_L1.is_moon_of(MajorBody::Earth));
+        assert!(!MajorBody::Saturn.is_moon_of(MajorBody::Saturn));
+        assert!(!MajorBody::Titan.is_moon_of(MajorBody::Sun));
+
+        assert_eq!(Some(Spacecraft::ISS), Spacecraft::iter().next());
+    }
+
+    #[test]
+    fn test_major_body_system() {
+        assert_eq!(
+            Some(MajorBody::JupiterBary),
+            MajorBody::Europa.system_barycenter()
+        );
+        assert_eq!(Some(MajorBody::Jupiter), MajorBody::Europa.primary());
+        assert_eq!(
+            Some(MajorBody::SaturnBary),
+            MajorBody::S2004S31.system_barycenter()
+        );
+        assert_eq!(
+            Some(MajorBody::EarthMoonBary),
+            MajorBody::Earth.system_barycenter()
+        );
+        assert_eq!(Some(MajorBody::Sun), MajorBody::Earth.primary());
+        assert_eq!(Some(MajorBody::Sun), MajorBody::PlutoBary.primary());
+        assert_eq!(
+            Some(MajorBody::MarsBary),
+            MajorBody::MarsBary.system_barycenter()
+        );
+        assert_eq!(
+            Some(MajorBody::SolarSystemBary),
+            MajorBody::Sun.system_barycenter()
+        );
+
+        assert_eq!(None, MajorBody::Sun.primary());
+        assert_eq!(None, MajorBody::EM_L4.system_barycenter());
+        assert_eq!(None, MajorBody::SEMB_L2.primary());
+    }
+
+    #[test]
+    fn test_spacecraft() -> TestResult {
+        assert_eq!(
+            "command=-125544",
+            serde_urlencoded::to_string([("command", Spacecraft::ISS)])?
+        );
+
+        assert_eq!(Ok(Spacecraft::Jwst), Spacecraft::try_from(-170));
+
+        assert_eq!(Err(InvalidBodyCode(-1)), Spacecraft::try_from(-1));
+
+        assert_eq!("-170", Body::from(Spacecraft::Jwst).to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_small_body() -> TestResult {
+        let commands: [(Command, &str); 3] = [
+            (
+                SmallBody::designation("2000433").into(),
+                "command=DES%3D2000433%3B",
+            ),
+            (SmallBody::name("Eros").into(), "command=NAME%3DEros%3B"),
+            (SmallBody::record(433).into(), "command=433%3B"),
+        ];
+
+        for (command, expected) in commands {
+            assert_eq!(
+                expected,
+                serde_urlencoded::to_string([("command", command)])?
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_spk_id() -> TestResult {
+        let eros = SpkId::asteroid(433)?;
+
+        assert_eq!(2_000_433, eros.get());
+        assert_eq!(SpkIdKind::NumberedAsteroid(433), eros.kind());
+        assert_eq!(Ok(eros), SpkId::try_from(2_000_433_i64));
+        assert_eq!(
+            SpkIdKind::NumberedAsteroid(1_000_001),
+            SpkId::asteroid(1_000_001)?.kind()
+        );
+        assert_eq!(SpkIdKind::Comet, SpkId::new(1_003_400)?.kind());
+
+        assert_eq!(Err(InvalidSpkId(599)), SpkId::new(599));
+        for invalid in [1_000_000, 2_000_000, 4_000_000, 20_999_999, 30_000_000] {
+            assert_eq!(Err(InvalidSpkId(u64::from(invalid))), SpkId::new(invalid));
+        }
+        assert_eq!(Err(InvalidSpkId(2_000_000)), SpkId::try_from(2_000_000_i64));
+
+        assert_eq!(SpkIdKind::Comet, SpkId::new(1_000_001)?.kind());
+        assert_eq!(SpkIdKind::Comet, SpkId::new(1_999_999)?.kind());
+        assert_eq!(
+            SpkIdKind::NumberedAsteroid(1),
+            SpkId::new(2_000_001)?.kind()
+        );
+        assert_eq!(
+            SpkIdKind::NumberedAsteroid(999_999),
+            SpkId::new(2_999_999)?.kind()
+        );
+        assert_eq!(SpkIdKind::UnnumberedAsteroid, SpkId::new(3_000_000)?.kind());
+        assert_eq!(SpkIdKind::UnnumberedAsteroid, SpkId::new(3_999_999)?.kind());
+        assert_eq!(
+            SpkIdKind::NumberedAsteroid(1_000_000),
+            SpkId::new(21_000_000)?.kind()
+        );
+        assert_eq!(
+            SpkIdKind::NumberedAsteroid(9_999_999),
+            SpkId::new(29_999_999)?.kind()
+        );
+        assert_eq!(Err(InvalidSpkId(125_544)), SpkId::try_from(-125_544_i64));
+
+        assert_eq!(
+            "command=DES%3D2000433%3B",
+            serde_urlencoded::to_string([("command", Command::from(eros))])?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_asteroid() -> TestResult {
+        assert_eq!("433;", SmallBody::number(433).to_string());
+        assert_eq!("54321;", SmallBody::record(54321).to_string());
+        assert_eq!(
+            "DES=2021 QM1;",
+            SmallBody::provisional("2021 QM1")?.to_string()
+        );
+        assert_eq!(
+            "DES=1999 AN10;",
+            SmallBody::provisional("1999 AN10")?.to_string()
+        );
+
+        for invalid in [
+            "2021QM1",
+            "21 QM1",
+            "2021 IA",
+            "2021 ZA",
+            "2021 QI",
+            "2021 QM01",
+            "2021 QM1a",
+        ] {
+            assert_eq!(
+                Err(InvalidDesignation(invalid.to_owned())),
+                SmallBody::provisional(invalid)
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_comet() {
+        let mut comet = Comet::new("73P");
+        comet.fragment("C");
+        assert_eq!("DES=73P-C;", SmallBody::from(comet.clone()).to_string());
+
+        comet.apparition(Apparition::Current).no_frag(true);
+        assert_eq!("DES=73P-C;CAP;NOFRAG;", SmallBody::from(comet).to_string());
+
+        let halley = Comet::new("1P")
+            .apparition(Apparition::Before(
+                Utc.with_ymd_and_hms(2020, 5, 31, 0, 0, 0).unwrap(),
+            ))
+            .clone();
+        assert_eq!("DES=1P;CAP<2459000.5;", SmallBody::from(halley).to_string());
+    }
+
+    #[test]
+    fn test_tle() -> TestResult {
+        let tle = Tle::new(
+            None,
+            "1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927",
+            "2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537",
+        )?;
+
+        let mut builder = Query::vectors();
+
+        builder
+            .common
+            .command(tle)
+            .center(MajorBody::Earth)
+            .time_spec(TimeSpec::from_list(vec![Utc
+                .with_ymd_and_hms(2008, 9, 20, 12, 0, 0)
+                .unwrap()]));
+
+        let encoded = serde_urlencoded::to_string(builder.build()?)?;
+
+        assert!(encoded.starts_with(
+            "command=TLE&tle=1+25544U+98067A+++08264.51782528+-.00002182++00000-0+-11606-4+0++2927\
+            %0A2+25544++51.6416+247.4627+0006703+130.5360+325.0288+15.72125391563537&ephem_type=V&"
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn test_query() -> TestResult {
         let mut builder = Query::vectors();
-        let start = Utc.ymd(2022, 8, 28).and_hms(0, 0, 0);
+        let start = Utc.with_ymd_and_hms(2022, 8, 28, 0, 0, 0).unwrap();
 
         builder
             .common
@@ -285,9 +1137,293 @@ mod tests {
             &obj_data=no&make_ephem=yes&csv_format=no&step_size=6h\
             &start_time=2022-08-28T00%3A00%3A00Z&stop_time=2022-08-30T00%3A00%3A00Z\
             &vec_table=3&vec_labels=yes&vec_delta_t=no&vec_corr=LT%2BS&out_units=km-s&ref_plane=E",
+            serde_urlencoded::to_string(&query)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_set_out_units() -> TestResult {
+        let mut builder = Query::elements();
+        builder
+            .common
+            .command(MajorBody::Mars)
+            .center(MajorBody::Sun)
+            .time_spec(TimeSpec::from_list([Utc
+                .with_ymd_and_hms(2023, 1, 1, 0, 0, 0)
+                .unwrap()]));
+        builder.specific.out_units(OutUnits::AU_D);
+
+        let mut query = builder.build()?;
+        assert_eq!(OutUnits::AU_D, query.out_units());
+
+        let conversion = query.set_out_units(OutUnits::KM_S);
+        assert_eq!(149_597_870.7, conversion.length);
+        assert_eq!(86_400., conversion.time);
+        assert!((conversion.velocity - 1_731.456_836_805_555_6).abs() < 1e-9);
+
+        assert_eq!(OutUnits::KM_S, query.out_units());
+        assert!(serde_urlencoded::to_string(&query)?.contains("&out_units=km-s&"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_size() -> TestResult {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let mut builder = Query::vectors();
+        builder
+            .common
+            .command(MajorBody::Mars)
+            .center(MajorBody::Sun)
+            .time_spec(TimeSpec::bounded(
+                (10, StepSizeUnit::Minutes),
+                start,
+                start + Duration::days(10),
+            ));
+
+        let query = builder.build()?;
+        assert_eq!(Ok(()), query.check_size(1441));
+        assert_eq!(
+            Err(TooLarge {
+                estimated: 1441,
+                max: 1000
+            }),
+            query.check_size(1000)
+        );
+
+        builder.common.time_spec(TimeSpec::bounded(
+            (99, StepSizeUnit::Unitless),
+            start,
+            start + Duration::days(10),
+        ));
+        assert!(builder.build()?.check_size(99).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_time_spec_validation() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let mut builder = Query::vectors();
+        builder
+            .common
+            .command(MajorBody::Mars)
+            .center(MajorBody::Sun)
+            .time_spec(TimeSpec::bounded((1, StepSizeUnit::Days), start, start));
+
+        assert!(matches!(
+            builder.build(),
+            Err(QueryBuilderError::TimeSpecError(
+                TimeSpecError::StartNotBeforeStop
+            ))
+        ));
+
+        builder.common.time_spec(TimeSpec::bounded(
+            (0, StepSizeUnit::Hours),
+            start,
+            start + Duration::days(1),
+        ));
+        assert!(matches!(
+            builder.build(),
+            Err(QueryBuilderError::TimeSpecError(TimeSpecError::ZeroStep))
+        ));
+    }
+
+    #[test]
+    fn test_display() -> TestResult {
+        let start = Utc.with_ymd_and_hms(2022, 8, 28, 0, 0, 0).unwrap();
+        let mut builder = Query::vectors();
+        builder
+            .common
+            .command(MajorBody::Jupiter)
+            .center(MajorBody::SolarSystemBary)
+            .time_spec(TimeSpec::bounded(
+                (6, StepSizeUnit::Hours),
+                start,
+                start + Duration::days(2),
+            ));
+
+        assert_eq!(
+            "Jupiter (599) rel. SolarSystemBary (0), vectors, \
+            2022-08-28T00:00:00Z→2022-08-30T00:00:00Z @6h",
+            builder.build()?.to_string()
+        );
+
+        builder
+            .common
+            .command(SmallBody::designation("2000433"))
+            .center((Observatory::MaunaKea, MajorBody::Earth))
+            .time_spec(TimeSpec::from_list([start, start + Duration::days(1)]));
+
+        assert_eq!(
+            "DES=2000433; rel. 568@Earth (399), vectors, 2 epochs",
+            builder.build()?.to_string()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_with_setters() -> TestResult {
+        let time_spec =
+            || TimeSpec::from_list([Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap()]);
+
+        let mut builder = Query::vectors();
+        builder
+            .common
+            .command(MajorBody::Mars)
+            .center(MajorBody::Sun)
+            .time_spec(time_spec())
+            .csv_format(true);
+        builder.specific.vec_corr(Correction::LT_S);
+
+        let query = Query::vectors()
+            .with_command(MajorBody::Mars)
+            .with_center(MajorBody::Sun)
+            .with_time_spec(time_spec())
+            .with_common(|common| common.csv_format(true))
+            .with_specific(|vectors| vectors.vec_corr(Correction::LT_S))
+            .build()?;
+
+        assert_eq!(builder.build()?, query);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_builder() -> TestResult {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+
+        let query = Query::vectors()
+            .with_command(MajorBody::Mars)
+            .with_center((GeodeticCoord::new(-116.89, 35.43, 1.0)?, MajorBody::Earth))
+            .with_time_spec(TimeSpec::from_list([start]))
+            .with_common(|common| common.csv_format(true).obj_data(false))
+            .with_specific(|vectors| vectors.vec_corr(Correction::LT_S))
+            .build()?;
+
+        assert_eq!(query, query.to_builder().build()?);
+
+        let later = || TimeSpec::from_list([start + Duration::days(1)]);
+        let mut builder = query.to_builder();
+        builder.common().time_spec(later());
+
+        let expected = Query::vectors()
+            .with_command(MajorBody::Mars)
+            .with_center((GeodeticCoord::new(-116.89, 35.43, 1.0)?, MajorBody::Earth))
+            .with_time_spec(later())
+            .with_common(|common| common.csv_format(true).obj_data(false))
+            .with_specific(|vectors| vectors.vec_corr(Correction::LT_S))
+            .build()?;
+        assert_eq!(expected, builder.build()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_coord_range() -> TestResult {
+        assert_eq!(
+            "-180,-90,-0.1",
+            GeodeticCoord::new(-180., -90., -0.1)?.to_string()
+        );
+        assert_eq!(
+            "359.9,90,1",
+            GeodeticCoord::new(359.9, 90., 1.)?.to_string()
+        );
+
+        for (lon, lat, alt_km, error) in [
+            (f64::NAN, 0., 0., CoordError::NotFinite("longitude")),
+            (0., f64::INFINITY, 0., CoordError::NotFinite("latitude")),
+            (0., 0., f64::NEG_INFINITY, CoordError::NotFinite("altitude")),
+            (0., 90.5, 0., CoordError::LatitudeOutOfRange(90.5)),
+            (0., -91., 0., CoordError::LatitudeOutOfRange(-91.)),
+            (360., 0., 0., CoordError::LongitudeOutOfRange(360.)),
+            (-180.5, 0., 0., CoordError::LongitudeOutOfRange(-180.5)),
+        ] {
+            assert_eq!(Err(error), GeodeticCoord::new(lon, lat, alt_km));
+        }
+
+        assert_eq!(
+            "-5,1700,-0.5",
+            CylindricalCoord::new(-5., 1_700., -0.5)?.to_string()
+        );
+        assert_eq!(
+            Err(CoordError::NotFinite("distance")),
+            CylindricalCoord::new(30., f64::NAN, 0.)
+        );
+        assert_eq!(
+            Err(CoordError::NotFinite("height")),
+            CylindricalCoord::new(30., 1_700., f64::INFINITY)
+        );
+        assert_eq!(
+            Err(CoordError::LongitudeOutOfRange(400.)),
+            CylindricalCoord::new(400., 1_700., 0.)
+        );
+        assert_eq!(
+            "Latitude 95 is outside [-90, 90]",
+            CoordError::LatitudeOutOfRange(95.).to_string()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_site_coord() -> TestResult {
+        let mut builder = Query::vectors();
+
+        builder
+            .common
+            .command(MajorBody::Moon)
+            .center((GeodeticCoord::new(-116.89, 35.43, 1.0)?, MajorBody::Earth))
+            .time_spec(TimeSpec::from_list(vec![Utc
+                .with_ymd_and_hms(2022, 8, 28, 0, 0, 0)
+                .unwrap()]));
+
+        let query = builder.build()?;
+
+        assert_eq!(
+            "command=301&ephem_type=V&center=coord%40399\
+            &coord_type=GEODETIC&site_coord=%27-116.89%2C35.43%2C1%27\
+            &ref_system=ICRF&format=text&obj_data=yes&make_ephem=yes&csv_format=no\
+            &tlist=2022-08-28T00%3A00%3A00Z&vec_table=3&vec_labels=yes&vec_delta_t=no\
+            &vec_corr=NONE&out_units=km-s&ref_plane=E",
             serde_urlencoded::to_string(query)?
         );
 
+        builder
+            .common
+            .center((CylindricalCoord::new(30., 1_700., 0.5)?, MajorBody::Moon));
+
+        assert!(serde_urlencoded::to_string(builder.build()?)?.contains(
+            "&center=coord%40301&coord_type=CYLINDRICAL&site_coord=%2730%2C1700%2C0.5%27&"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_spacecraft_center() -> TestResult {
+        assert_eq!(
+            "center=%40-170",
+            serde_urlencoded::to_string([("center", Center::from(Spacecraft::Jwst))])?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_observatory_site() -> TestResult {
+        assert_eq!(
+            "center=G96%40399",
+            serde_urlencoded::to_string([(
+                "center",
+                Center::from((Observatory::MtLemmonSurvey, MajorBody::Earth))
+            )])?
+        );
+
         Ok(())
     }
 }
@@ -0,0 +1,105 @@
+//! Minor Planet Center observatory codes
+
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{0:?} is not a known observatory code")]
+pub struct InvalidObservatoryCode(pub String);
+
+macro_rules! observatories {
+    ($($(#[$attr:meta])* $variant:ident = $code:literal, $name:literal;)*) => {
+        /// Observatories by MPC code, for use as a [`Site`](crate::request::Site) on the
+        /// Earth. Codes may be alphanumeric, e.g. `G96` for the Mt. Lemmon Survey.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum Observatory {
+            $($(#[$attr])* $variant,)*
+        }
+
+        impl Observatory {
+            const VARIANTS: &'static [Self] = &[$(Self::$variant,)*];
+
+            /// MPC observatory code, e.g. `568`
+            pub fn code(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $code,)*
+                }
+            }
+
+            /// Name as listed by the MPC
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+        }
+    };
+}
+
+observatories! {
+    Greenwich = "000", "Greenwich";
+    MaunaKea = "568", "Mauna Kea";
+    Palomar = "675", "Palomar Mountain";
+    KittPeak = "695", "Kitt Peak";
+    Spacewatch = "691", "Steward Observatory, Kitt Peak-Spacewatch";
+    CatalinaSkySurvey = "703", "Catalina Sky Survey";
+    MtLemmonSurvey = "G96", "Mt. Lemmon Survey";
+    PanStarrs1 = "F51", "Pan-STARRS 1, Haleakala";
+    AtlasHaleakala = "T05", "ATLAS-HKO, Haleakala";
+    AtlasMaunaLoa = "T08", "ATLAS-MLO, Mauna Loa";
+    SidingSpring = "413", "Siding Spring Observatory";
+    Paranal = "309", "Cerro Paranal";
+    LaSilla = "809", "European Southern Observatory, La Silla";
+    LasCampanas = "304", "Las Campanas Observatory";
+    LaPalma = "950", "La Palma";
+    CerroTololo = "807", "Cerro Tololo Observatory, La Serena";
+    CerroTololoDecam = "W84", "Cerro Tololo-DECam";
+    GeminiSouth = "I11", "Gemini South Observatory, Cerro Pachon";
+    Rubin = "X05", "Simonyi Survey Telescope, Rubin Observatory";
+}
+
+impl Observatory {
+    /// Every listed observatory
+    pub fn iter() -> impl Iterator<Item = Self> {
+        Self::VARIANTS.iter().copied()
+    }
+}
+
+impl Display for Observatory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for Observatory {
+    type Err = InvalidObservatoryCode;
+
+    /// Parses an MPC code, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::iter()
+            .find(|obs| obs.code().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| InvalidObservatoryCode(s.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::request::observatory::{InvalidObservatoryCode, Observatory};
+
+    #[test]
+    fn test_observatory() {
+        assert_eq!(Ok(Observatory::MtLemmonSurvey), "g96".parse());
+        assert_eq!(Ok(Observatory::Greenwich), "000".parse());
+        assert_eq!(
+            Err(InvalidObservatoryCode("ZZZ".to_owned())),
+            "ZZZ".parse::<Observatory>()
+        );
+
+        assert_eq!("568", Observatory::MaunaKea.to_string());
+        assert_eq!("Palomar Mountain", Observatory::Palomar.name());
+    }
+}
@@ -0,0 +1,1952 @@
+//! Conversion of queries to and from `(name, value)` parameter pairs in a canonical order,
+//! a compact binary form of the pairs, and the URLs of the Horizons API
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use std::str::FromStr;
+
+use serde::{
+    de::{self, MapAccess, Visitor},
+    ser::{self, Impossible, SerializeMap, SerializeStruct, SerializeStructVariant},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use thiserror::Error;
+
+use crate::request::{
+    bodies::{MajorBody, Spacecraft},
+    ephemeris::{
+        common::CommonBuilder,
+        elements::{ElementsBuilder, TpType},
+        vectors::{Correction, TableFormat, VectorsBuilder},
+        CalendarDate, EphemType, Epoch, Format, JulianDate, OutUnits, RefPlane, RefSystem,
+        StepSize, StepSizeUnit, TimeScale, TimeSpec,
+    },
+    observatory::Observatory,
+    tle::Tle,
+    Body, Center, Command, CoordError, CoordType, CylindricalCoord, GeodeticCoord, HzBool, Query,
+    QueryBuilder, QueryBuilderError, Site,
+};
+
+/// Order of the parameters of a serialized [`Query`](crate::request::Query): the common
+/// ones, then the time specification, then those of each ephemeris type. Parameters that
+/// are not listed follow in alphabetical order.
+pub const PARAM_ORDER: &[&str] = &[
+    "command",
+    "tle",
+    "ephem_type",
+    "center",
+    "coord_type",
+    "site_coord",
+    "ref_system",
+    "format",
+    "obj_data",
+    "make_ephem",
+    "csv_format",
+    "step_size",
+    "start_time",
+    "stop_time",
+    "tlist",
+    "tp_type",
+    "vec_table",
+    "vec_labels",
+    "vec_delta_t",
+    "vec_corr",
+    "out_units",
+    "ref_plane",
+    "elm_labels",
+];
+
+/// How completely the crate models a parameter of the Horizons API
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Coverage {
+    /// Every value the API accepts can be built
+    Full,
+    /// Some values cannot be built; see [`ParamInfo::note`]
+    Partial,
+    /// Not modelled; observer-table settings among others
+    Unsupported,
+}
+
+/// A parameter of the Horizons API and its support in the crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParamInfo {
+    /// As in query strings
+    pub name: &'static str,
+    /// Module that models the parameter, relative to the crate root
+    pub module: Option<&'static str>,
+    pub coverage: Coverage,
+    /// What is missing, for partial coverage
+    pub note: &'static str,
+}
+
+const REGISTRY: &[ParamInfo] = &[
+    ParamInfo {
+        name: "command",
+        module: Some("request"),
+        coverage: Coverage::Full,
+        note: "Bodies, small bodies, spacecraft, TLEs and custom strings",
+    },
+    ParamInfo {
+        name: "tle",
+        module: Some("request::tle"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "ephem_type",
+        module: Some("request::ephemeris"),
+        coverage: Coverage::Partial,
+        note: "Observer tables cannot be built",
+    },
+    ParamInfo {
+        name: "center",
+        module: Some("request"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "coord_type",
+        module: Some("request"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "site_coord",
+        module: Some("request"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "ref_system",
+        module: Some("request::ephemeris"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "format",
+        module: Some("request::ephemeris"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "obj_data",
+        module: Some("request::ephemeris::common"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "make_ephem",
+        module: Some("request::ephemeris::common"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "csv_format",
+        module: Some("request::ephemeris::common"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "step_size",
+        module: Some("request::ephemeris"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "start_time",
+        module: Some("request::ephemeris"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "stop_time",
+        module: Some("request::ephemeris"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "tlist",
+        module: Some("request::ephemeris"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "tp_type",
+        module: Some("request::ephemeris::elements"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "vec_table",
+        module: Some("request::ephemeris::vectors"),
+        coverage: Coverage::Partial,
+        note: "Statistical uncertainty codes are not modelled",
+    },
+    ParamInfo {
+        name: "vec_labels",
+        module: Some("request::ephemeris::vectors"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "vec_delta_t",
+        module: Some("request::ephemeris::vectors"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "vec_corr",
+        module: Some("request::ephemeris::vectors"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "out_units",
+        module: Some("request::ephemeris"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "ref_plane",
+        module: Some("request::ephemeris"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "elm_labels",
+        module: Some("request::ephemeris::elements"),
+        coverage: Coverage::Full,
+        note: "",
+    },
+    ParamInfo {
+        name: "tlist_type",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "time_type",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "time_zone",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "time_digits",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "quantities",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "ang_format",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "cal_format",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "cal_type",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "apparent",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "range_units",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "suppress_range_rate",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "elev_cut",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "skip_daylt",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "solar_elong",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "airmass",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "lha_cutoff",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "ang_rate_cutoff",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "extra_prec",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+    ParamInfo {
+        name: "r_t_s_only",
+        module: None,
+        coverage: Coverage::Unsupported,
+        note: "",
+    },
+];
+
+/// The Horizons API parameters the crate knows of: those of [`PARAM_ORDER`], in that
+/// order, then those it does not model yet
+pub fn registry() -> &'static [ParamInfo] {
+    REGISTRY
+}
+
+/// The entry of [`registry`] for `name`, in any case
+pub fn param_info(name: &str) -> Option<&'static ParamInfo> {
+    REGISTRY
+        .iter()
+        .find(|info| info.name.eq_ignore_ascii_case(name))
+}
+
+/// A value that does not flatten to query parameters
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{0}")]
+pub struct ParamsError(String);
+
+impl ser::Error for ParamsError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+/// Flattens `value`, a struct or map of scalars, into parameter pairs sorted by
+/// [`PARAM_ORDER`]
+pub(crate) fn to_params<T: Serialize + ?Sized>(
+    value: &T,
+) -> Result<Vec<(String, String)>, ParamsError> {
+    let mut params = Params(Vec::new());
+    value.serialize(&mut params)?;
+
+    let rank = |name: &str| {
+        PARAM_ORDER
+            .iter()
+            .position(|&known| known == name)
+            .unwrap_or(PARAM_ORDER.len())
+    };
+
+    // Stable, so that repeated unknown names keep their relative order
+    params
+        .0
+        .sort_by(|(a, _), (b, _)| rank(a).cmp(&rank(b)).then_with(|| a.cmp(b)));
+
+    Ok(params.0)
+}
+
+/// Collects the fields of the top-level struct or map
+struct Params(Vec<(String, String)>);
+
+/// Pending key of a map entry
+struct MapParams<'a> {
+    params: &'a mut Params,
+    key: Option<String>,
+}
+
+impl Params {
+    fn push<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<(), ParamsError> {
+        if let Some(value) = value.serialize(Value)? {
+            self.0.push((key, value));
+        }
+
+        Ok(())
+    }
+}
+
+fn unsupported<T>(what: &str) -> Result<T, ParamsError> {
+    Err(ParamsError(format!(
+        "{what} cannot be a query parameter list"
+    )))
+}
+
+macro_rules! unsupported_scalars {
+    ($($method:ident: $ty:ty),* $(,)?) => {
+        $(
+            fn $method(self, _: $ty) -> Result<Self::Ok, Self::Error> {
+                unsupported(stringify!($ty))
+            }
+        )*
+    };
+}
+
+impl<'a> Serializer for &'a mut Params {
+    type Ok = ();
+    type Error = ParamsError;
+    type SerializeSeq = Impossible<(), ParamsError>;
+    type SerializeTuple = Impossible<(), ParamsError>;
+    type SerializeTupleStruct = Impossible<(), ParamsError>;
+    type SerializeTupleVariant = Impossible<(), ParamsError>;
+    type SerializeMap = MapParams<'a>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    unsupported_scalars!(
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char,
+        serialize_str: &str,
+        serialize_bytes: &[u8],
+    );
+
+    fn serialize_none(self) -> Result<(), ParamsError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), ParamsError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), ParamsError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), ParamsError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<(), ParamsError> {
+        unsupported(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), ParamsError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), ParamsError> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, ParamsError> {
+        unsupported("A sequence")
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, ParamsError> {
+        unsupported("A tuple")
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, ParamsError> {
+        unsupported(name)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, ParamsError> {
+        unsupported(variant)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, ParamsError> {
+        Ok(MapParams {
+            params: self,
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, ParamsError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, ParamsError> {
+        Ok(self)
+    }
+}
+
+impl SerializeStruct for &mut Params {
+    type Ok = ();
+    type Error = ParamsError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ParamsError> {
+        self.push(key.to_owned(), value)
+    }
+
+    fn end(self) -> Result<(), ParamsError> {
+        Ok(())
+    }
+}
+
+impl SerializeStructVariant for &mut Params {
+    type Ok = ();
+    type Error = ParamsError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ParamsError> {
+        self.push(key.to_owned(), value)
+    }
+
+    fn end(self) -> Result<(), ParamsError> {
+        Ok(())
+    }
+}
+
+impl SerializeMap for MapParams<'_> {
+    type Ok = ();
+    type Error = ParamsError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), ParamsError> {
+        match key.serialize(Value)? {
+            Some(key) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            None => unsupported("An empty key"),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ParamsError> {
+        match self.key.take() {
+            Some(key) => self.params.push(key, value),
+            None => unsupported("A value without a key"),
+        }
+    }
+
+    fn end(self) -> Result<(), ParamsError> {
+        Ok(())
+    }
+}
+
+/// Serializes a scalar to its parameter value, or `None` for an absent value
+struct Value;
+
+macro_rules! display_scalars {
+    ($($method:ident: $ty:ty),* $(,)?) => {
+        $(
+            fn $method(self, value: $ty) -> Result<Self::Ok, Self::Error> {
+                Ok(Some(value.to_string()))
+            }
+        )*
+    };
+}
+
+impl Serializer for Value {
+    type Ok = Option<String>;
+    type Error = ParamsError;
+    type SerializeSeq = Impossible<Option<String>, ParamsError>;
+    type SerializeTuple = Impossible<Option<String>, ParamsError>;
+    type SerializeTupleStruct = Impossible<Option<String>, ParamsError>;
+    type SerializeTupleVariant = Impossible<Option<String>, ParamsError>;
+    type SerializeMap = Impossible<Option<String>, ParamsError>;
+    type SerializeStruct = Impossible<Option<String>, ParamsError>;
+    type SerializeStructVariant = Impossible<Option<String>, ParamsError>;
+
+    display_scalars!(
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char,
+        serialize_str: &str,
+    );
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<Self::Ok, ParamsError> {
+        unsupported("Bytes")
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, ParamsError> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, ParamsError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, ParamsError> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Self::Ok, ParamsError> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, ParamsError> {
+        Ok(Some(variant.to_owned()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, ParamsError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, ParamsError> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, ParamsError> {
+        unsupported("A sequence")
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, ParamsError> {
+        unsupported("A tuple")
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, ParamsError> {
+        unsupported(name)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, ParamsError> {
+        unsupported(variant)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, ParamsError> {
+        unsupported("A nested map")
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, ParamsError> {
+        unsupported(name)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, ParamsError> {
+        unsupported(variant)
+    }
+}
+
+#[derive(Error, Debug, Clone)]
+pub enum QueryParseError {
+    #[error("Missing parameter `{0}`")]
+    Missing(&'static str),
+    #[error("Invalid value `{value}` for `{name}`")]
+    InvalidValue { name: String, value: String },
+    #[error("Ephemeris type `{0}` is not supported")]
+    UnsupportedEphemType(String),
+    #[error("Invalid percent-encoding in `{0}`")]
+    InvalidEncoding(String),
+    #[error("Unknown parameter `{name}`{}", did_you_mean(*.suggestion))]
+    Unknown {
+        name: String,
+        suggestion: Option<&'static str>,
+    },
+    #[error("{0}")]
+    QueryBuilderError(QueryBuilderError),
+    #[error("{0}")]
+    CoordError(CoordError),
+}
+
+crate::impl_from_for_inner_enum!(QueryParseError: QueryBuilderError, CoordError);
+
+fn did_you_mean(suggestion: Option<&str>) -> String {
+    suggestion
+        .map(|name| format!("; did you mean `{name}`?"))
+        .unwrap_or_default()
+}
+
+/// Levenshtein distance between two ASCII names
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.as_bytes();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, &ca) in a.as_bytes().iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Fails for a name that is not in [`PARAM_ORDER`], in any case
+pub(crate) fn check_known(name: &str) -> Result<(), QueryParseError> {
+    let name = name.to_ascii_lowercase();
+
+    if PARAM_ORDER.contains(&name.as_str()) {
+        Ok(())
+    } else {
+        Err(QueryParseError::Unknown {
+            suggestion: suggest(&name),
+            name,
+        })
+    }
+}
+
+/// Closest name in [`PARAM_ORDER`] to a misspelled one, if any is close enough
+fn suggest(name: &str) -> Option<&'static str> {
+    PARAM_ORDER
+        .iter()
+        .map(|&known| (edit_distance(name, known), known))
+        .filter(|&(distance, known)| distance <= known.len() / 3)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, known)| known)
+}
+
+/// Parameters by lowercase name, with `'` quotes removed
+struct Lookup(Vec<(String, String)>);
+
+impl Lookup {
+    fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn require(&self, name: &'static str) -> Result<&str, QueryParseError> {
+        self.get(name).ok_or(QueryParseError::Missing(name))
+    }
+
+    /// Parses `name` with `parse` if it is present
+    fn parse<T>(
+        &self,
+        name: &'static str,
+        parse: impl Fn(&str) -> Option<T>,
+    ) -> Result<Option<T>, QueryParseError> {
+        self.get(name)
+            .map(|value| parse(value).ok_or_else(|| invalid(name, value)))
+            .transpose()
+    }
+}
+
+fn invalid(name: &str, value: &str) -> QueryParseError {
+    QueryParseError::InvalidValue {
+        name: name.to_owned(),
+        value: value.to_owned(),
+    }
+}
+
+/// The candidate that serializes to `value`, ignoring case, so that parsing follows the
+/// `serde(rename)`s of the type
+fn variant<T: Serialize + Copy>(candidates: &[T]) -> impl Fn(&str) -> Option<T> + '_ {
+    move |value| {
+        candidates.iter().copied().find(|candidate| {
+            matches!(candidate.serialize(Value), Ok(Some(s)) if s.eq_ignore_ascii_case(value))
+        })
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    variant(&[HzBool::Yes, HzBool::No])(value).map(|b| b == HzBool::Yes)
+}
+
+/// A major body or spacecraft by code, or the value itself otherwise
+fn parse_body(value: &str) -> Body {
+    value
+        .parse::<i64>()
+        .ok()
+        .and_then(|code| match code {
+            ..=-1 => Spacecraft::try_from(code).ok().map(Body::Spacecraft),
+            _ => MajorBody::try_from(code).ok().map(Body::MajorBody),
+        })
+        .unwrap_or_else(|| Body::Custom(value.to_owned()))
+}
+
+fn parse_triple(value: &str) -> Option<(f64, f64, f64)> {
+    let mut parts = value.split(',').map(|part| part.trim().parse().ok());
+    let triple = (parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(triple)
+}
+
+fn parse_center(params: &Lookup) -> Result<Center, QueryParseError> {
+    let value = params.require("center")?;
+    let (site, body) = value
+        .split_once('@')
+        .ok_or_else(|| invalid("center", value))?;
+    let body = parse_body(body);
+
+    let site = match site {
+        "" | "500" => Site::Center,
+        "coord" => {
+            let (a, b, c) = params
+                .parse("site_coord", parse_triple)?
+                .ok_or(QueryParseError::Missing("site_coord"))?;
+
+            match params
+                .parse(
+                    "coord_type",
+                    variant(&[CoordType::Geodetic, CoordType::Cylindrical]),
+                )?
+                .unwrap_or(CoordType::Geodetic)
+            {
+                CoordType::Geodetic => Site::Coord(GeodeticCoord::new(a, b, c)?),
+                CoordType::Cylindrical => Site::Cylindrical(CylindricalCoord::new(a, b, c)?),
+            }
+        }
+        code => match (code.parse::<Observatory>(), code.parse::<u16>()) {
+            (Ok(observatory), _) => Site::Observatory(observatory),
+            (_, Ok(num)) => Site::Custom(num),
+            _ => return Err(invalid("center", value)),
+        },
+    };
+
+    Ok(Center::from((site, body)))
+}
+
+fn parse_command(params: &Lookup) -> Result<Command, QueryParseError> {
+    let value = params.require("command")?;
+
+    if value.eq_ignore_ascii_case("TLE") {
+        let tle = params.require("tle")?;
+        let rows: Vec<&str> = tle.lines().collect();
+
+        let parsed = match rows[..] {
+            [line1, line2] => Tle::new(None, line1, line2).ok(),
+            [name, line1, line2] => Tle::new(Some(name.to_owned()), line1, line2).ok(),
+            _ => None,
+        };
+
+        return parsed.map(Command::Tle).ok_or_else(|| invalid("tle", tle));
+    }
+
+    Ok(match parse_body(value) {
+        Body::Custom(custom) => Command::Custom(custom),
+        body => Command::Body(body),
+    })
+}
+
+fn parse_step_size(value: &str) -> Option<StepSize> {
+    if let Some(arcseconds) = value.strip_suffix(" VAR") {
+        return arcseconds.trim().parse().ok().map(StepSize::angular);
+    }
+
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let unit = match unit {
+        "" => StepSizeUnit::Unitless,
+        "m" => StepSizeUnit::Minutes,
+        "h" => StepSizeUnit::Hours,
+        "d" => StepSizeUnit::Days,
+        "mo" => StepSizeUnit::Months,
+        "y" => StepSizeUnit::Years,
+        _ => return None,
+    };
+
+    Some(StepSize::new(number.parse().ok()?, unit))
+}
+
+/// `2000-Jan-01 12:00:00`, optionally prefixed with `BC `
+fn parse_calendar(value: &str) -> Option<CalendarDate> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let (bc, value) = match value.strip_prefix("BC ") {
+        Some(rest) => (true, rest),
+        None => (false, value),
+    };
+
+    let (date, time) = value.split_once(' ').unwrap_or((value, "00:00:00"));
+
+    let mut date = date.split('-');
+    let (year, month, day) = (date.next()?, date.next()?, date.next()?);
+    let month = MONTHS.iter().position(|m| m.eq_ignore_ascii_case(month))?;
+
+    let mut time = time.split(':').map(str::parse::<u8>);
+    let (hour, minute, second) = (
+        time.next()?.ok()?,
+        time.next().unwrap_or(Ok(0)).ok()?,
+        time.next().unwrap_or(Ok(0)).ok()?,
+    );
+
+    #[allow(clippy::cast_possible_truncation)]
+    let (month, day) = (month as u8 + 1, day.parse().ok()?);
+
+    let date = if bc {
+        CalendarDate::bc(year.parse().ok()?, month, day)
+    } else {
+        CalendarDate::new(year.parse().ok()?, month, day)
+    };
+
+    date.ok()?.and_hms(hour, minute, second).ok()
+}
+
+/// Any of the forms an [`Epoch`] serializes to
+fn parse_epoch(value: &str) -> Option<Epoch> {
+    let (value, scale) = match value.rsplit_once(' ') {
+        Some((rest, label)) if !label.starts_with("JD") => match TimeScale::from_label(label) {
+            Some(scale) => (rest, scale),
+            None => (value, TimeScale::UT),
+        },
+        _ => (value, TimeScale::UT),
+    };
+
+    let epoch = if let Some(jd) = value.strip_prefix("JD") {
+        Epoch::from(JulianDate(jd.parse().ok()?))
+    } else if value.contains(|c: char| c.is_ascii_alphabetic() && c != 'T' && c != 'Z') {
+        Epoch::from(parse_calendar(value)?)
+    } else if let Ok(epoch) = DateTime::parse_from_rfc3339(value) {
+        Epoch::from(epoch.with_timezone(&Utc))
+    } else {
+        let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+        Epoch::from(Utc.from_utc_datetime(&naive))
+    };
+
+    Some(epoch.with_scale(scale))
+}
+
+fn parse_time_spec(params: &Lookup) -> Result<TimeSpec, QueryParseError> {
+    if let Some(tlist) = params.get("tlist") {
+        let epochs: Option<Vec<Epoch>> = tlist.split(',').map(|e| parse_epoch(e.trim())).collect();
+        return epochs
+            .map(TimeSpec::from_list)
+            .ok_or_else(|| invalid("tlist", tlist));
+    }
+
+    let required = |name| {
+        params
+            .parse(name, parse_epoch)?
+            .ok_or(QueryParseError::Missing(name))
+    };
+
+    Ok(TimeSpec::bounded(
+        params
+            .parse("step_size", parse_step_size)?
+            .ok_or(QueryParseError::Missing("step_size"))?,
+        required("start_time")?,
+        required("stop_time")?,
+    ))
+}
+
+fn parse_out_units(params: &Lookup) -> Result<Option<OutUnits>, QueryParseError> {
+    params.parse(
+        "out_units",
+        variant(&[OutUnits::KM_D, OutUnits::KM_S, OutUnits::AU_D]),
+    )
+}
+
+fn parse_ref_plane(params: &Lookup) -> Result<Option<RefPlane>, QueryParseError> {
+    params.parse(
+        "ref_plane",
+        variant(&[RefPlane::Ecliptic, RefPlane::Frame, RefPlane::BodyEquator]),
+    )
+}
+
+fn parse_elements(params: &Lookup) -> Result<ElementsBuilder, QueryParseError> {
+    let mut specific = ElementsBuilder::new();
+
+    if let Some(tp_type) =
+        params.parse("tp_type", variant(&[TpType::Absolute, TpType::Relative]))?
+    {
+        specific.tp_type(tp_type);
+    }
+    if let Some(elm_labels) = params.parse("elm_labels", parse_bool)? {
+        specific.elm_labels(elm_labels);
+    }
+    if let Some(out_units) = parse_out_units(params)? {
+        specific.out_units(out_units);
+    }
+    if let Some(ref_plane) = parse_ref_plane(params)? {
+        specific.ref_plane(ref_plane);
+    }
+
+    Ok(specific)
+}
+
+fn parse_vectors(params: &Lookup) -> Result<VectorsBuilder, QueryParseError> {
+    let mut specific = VectorsBuilder::new();
+
+    if let Some(vec_table) = params.parse(
+        "vec_table",
+        variant(&[
+            TableFormat::Position,
+            TableFormat::State,
+            TableFormat::State_LT,
+            TableFormat::Position_LT,
+            TableFormat::Velocity,
+            TableFormat::LT,
+        ]),
+    )? {
+        specific.table_format(vec_table);
+    }
+    if let Some(vec_labels) = params.parse("vec_labels", parse_bool)? {
+        specific.vec_labels(vec_labels);
+    }
+    if let Some(vec_delta_t) = params.parse("vec_delta_t", parse_bool)? {
+        specific.vec_delta_t(vec_delta_t);
+    }
+    if let Some(vec_corr) = params.parse(
+        "vec_corr",
+        variant(&[Correction::None, Correction::LT, Correction::LT_S]),
+    )? {
+        specific.vec_corr(vec_corr);
+    }
+    if let Some(out_units) = parse_out_units(params)? {
+        specific.out_units(out_units);
+    }
+    if let Some(ref_plane) = parse_ref_plane(params)? {
+        specific.ref_plane(ref_plane);
+    }
+
+    Ok(specific)
+}
+
+impl Query {
+    /// Reconstructs a query from `(name, value)` pairs such as those of [`Query::params`].
+    /// Names are case-insensitive, values may be quoted with `'`, and unknown parameters
+    /// become [`extra_param`](CommonBuilder::extra_param)s with lowercase names and their
+    /// values as given, quotes included, or are ignored if their names are not valid.
+    ///
+    /// Commands and centers that are not codes of a [`MajorBody`] or [`Spacecraft`] come
+    /// back as `Custom` variants, which serialize identically.
+    pub fn from_params<K, V>(
+        params: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<Self, QueryParseError>
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let raw: Vec<(String, String)> = params
+            .into_iter()
+            .map(|(name, value)| {
+                (
+                    name.as_ref().to_ascii_lowercase(),
+                    value.as_ref().to_owned(),
+                )
+            })
+            .collect();
+
+        let params = Lookup(
+            raw.iter()
+                .map(|(name, value)| {
+                    let value = value
+                        .strip_prefix('\'')
+                        .and_then(|v| v.strip_suffix('\''))
+                        .unwrap_or(value);
+
+                    (name.clone(), value.to_owned())
+                })
+                .collect(),
+        );
+
+        let ephem_type = params.require("ephem_type")?;
+        let ephem_type = variant(&[EphemType::Elements, EphemType::Vectors])(ephem_type)
+            .ok_or_else(|| QueryParseError::UnsupportedEphemType(ephem_type.to_owned()))?;
+
+        let mut common = CommonBuilder::new();
+        common
+            .command(parse_command(&params)?)
+            .ephem_type(ephem_type)
+            .center(parse_center(&params)?)
+            .time_spec(parse_time_spec(&params)?);
+
+        if let Some(ref_system) =
+            params.parse("ref_system", variant(&[RefSystem::ICRF, RefSystem::B1950]))?
+        {
+            common.ref_system(ref_system);
+        }
+        if let Some(format) = params.parse("format", variant(&[Format::Text, Format::Json]))? {
+            common.format(format);
+        }
+        if let Some(obj_data) = params.parse("obj_data", parse_bool)? {
+            common.obj_data(obj_data);
+        }
+        if let Some(make_ephem) = params.parse("make_ephem", parse_bool)? {
+            common.make_ephem(make_ephem);
+        }
+        if let Some(csv_format) = params.parse("csv_format", parse_bool)? {
+            common.csv_format(csv_format);
+        }
+
+        // Unknown parameters with valid names are kept as they are, others dropped
+        for (name, value) in &raw {
+            if !PARAM_ORDER.contains(&name.as_str()) {
+                common.extra_param(name, value.as_str()).ok();
+            }
+        }
+
+        let query = if ephem_type == EphemType::Elements {
+            QueryBuilder {
+                common,
+                specific: parse_elements(&params)?,
+            }
+            .build()?
+        } else {
+            QueryBuilder {
+                common,
+                specific: parse_vectors(&params)?,
+            }
+            .build()?
+        };
+
+        Ok(query)
+    }
+
+    /// Like [`Query::from_params`], but rejects parameters that are not in [`PARAM_ORDER`]
+    /// with the closest known name as a suggestion, for queries loaded from configuration
+    /// where a misspelled key would otherwise be silently dropped.
+    pub fn from_params_strict<K, V>(
+        params: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<Self, QueryParseError>
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let params: Vec<(K, V)> = params.into_iter().collect();
+
+        for (name, _) in &params {
+            check_known(name.as_ref())?;
+        }
+
+        Self::from_params(params)
+    }
+}
+
+/// Base URL of a Horizons API, to which [`Query::to_url`] appends the query string
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ApiEndpoint(String);
+
+impl ApiEndpoint {
+    /// The official API at JPL
+    pub const HORIZONS: &'static str = "https://ssd.jpl.nasa.gov/api/horizons.api";
+
+    /// The official file API at JPL, see [`Query::to_form`]
+    pub const HORIZONS_FILE: &'static str = "https://ssd.jpl.nasa.gov/api/horizons_file.api";
+
+    /// The official Lookup API at JPL, see [`Lookup`](crate::lookup::Lookup)
+    pub const HORIZONS_LOOKUP: &'static str = "https://ssd.jpl.nasa.gov/api/horizons_lookup.api";
+
+    /// An endpoint at `base`, e.g. a mirror or a local mock server
+    pub fn new(base: impl Into<String>) -> Self {
+        Self(base.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for ApiEndpoint {
+    fn default() -> Self {
+        Self::new(Self::HORIZONS)
+    }
+}
+
+/// Parameters of a [`Query`] in [`PARAM_ORDER`], independent of the order of the builder
+/// calls, for keying caches and deduplicating requests
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CanonicalQuery(Vec<(String, String)>);
+
+impl CanonicalQuery {
+    pub fn params(&self) -> &[(String, String)] {
+        &self.0
+    }
+
+    /// The typed query again
+    pub fn to_query(&self) -> Result<Query, QueryParseError> {
+        Query::from_params(self.0.iter().map(|(name, value)| (name, value)))
+    }
+}
+
+/// The query string of [`Query::to_query_string`]
+impl std::fmt::Display for CanonicalQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&query_string(&self.0))
+    }
+}
+
+impl From<&Query> for CanonicalQuery {
+    fn from(query: &Query) -> Self {
+        query.canonicalize()
+    }
+}
+
+/// Consistent with the derived `Eq`, since equal queries have equal parameters
+impl std::hash::Hash for Query {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.canonicalize().hash(state);
+    }
+}
+
+/// Appends `s` in the `application/x-www-form-urlencoded` encoding
+fn push_form_encoded(out: &mut String, s: &str) {
+    for byte in s.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'*' | b'-' | b'.' | b'_' => {
+                out.push(char::from(byte));
+            }
+            b' ' => out.push('+'),
+            _ => {
+                const HEX: &[u8; 16] = b"0123456789ABCDEF";
+                out.push('%');
+                out.push(char::from(HEX[usize::from(byte >> 4)]));
+                out.push(char::from(HEX[usize::from(byte & 0xf)]));
+            }
+        }
+    }
+}
+
+pub(crate) fn query_string(params: &[(String, String)]) -> String {
+    let mut out = String::new();
+
+    for (i, (name, value)) in params.iter().enumerate() {
+        if i > 0 {
+            out.push('&');
+        }
+
+        push_form_encoded(&mut out, name);
+        out.push('=');
+        push_form_encoded(&mut out, value);
+    }
+
+    out
+}
+
+impl Query {
+    /// Stable form of the query. Two queries built with the same settings in any order
+    /// have equal canonical forms.
+    pub fn canonicalize(&self) -> CanonicalQuery {
+        CanonicalQuery(self.params())
+    }
+
+    /// Parameters of [`Query::params`] as a form-encoded query string, without the `?`
+    pub fn to_query_string(&self) -> String {
+        query_string(&self.params())
+    }
+
+    /// Complete GET URL of the query against `base`, usually [`ApiEndpoint::default`]
+    pub fn to_url(&self, base: &ApiEndpoint) -> String {
+        let separator = if base.0.contains('?') { '&' } else { '?' };
+        format!("{}{separator}{}", base.0, self.to_query_string())
+    }
+}
+
+/// Decodes `s` from the `application/x-www-form-urlencoded` encoding
+fn form_decode(s: &str) -> Result<String, QueryParseError> {
+    let invalid = || QueryParseError::InvalidEncoding(s.to_owned());
+
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = rest.get(..2).ok_or_else(invalid)?;
+                let hex = std::str::from_utf8(hex).map_err(|_| invalid())?;
+                bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+                rest = &rest[2..];
+            }
+            _ => bytes.push(byte),
+        }
+    }
+
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+/// Parses a query string as written by [`Query::to_query_string`], or a whole URL as
+/// written by [`Query::to_url`], with the lenience of [`Query::from_params`]
+impl FromStr for Query {
+    type Err = QueryParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let query = s.split_once('?').map_or(s, |(_, query)| query);
+
+        let params = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                Ok((form_decode(name)?, form_decode(value)?))
+            })
+            .collect::<Result<Vec<_>, QueryParseError>>()?;
+
+        Self::from_params(params)
+    }
+}
+
+/// A parameter value in a configuration file, where numbers and booleans may be unquoted
+struct ParamValue(String);
+
+impl<'de> Deserialize<'de> for ParamValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ValueVisitor;
+
+        impl Visitor<'_> for ValueVisitor {
+            type Value = ParamValue;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a string, number or boolean")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(ParamValue(v.to_owned()))
+            }
+
+            fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+                Ok(ParamValue(if v { "yes" } else { "no" }.to_owned()))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(ParamValue(v.to_string()))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(ParamValue(v.to_string()))
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+                Ok(ParamValue(v.to_string()))
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+/// Deserializes from a query string, or from a map of parameters by name as in
+/// [`Query::from_params`]
+impl<'de> Deserialize<'de> for Query {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct QueryVisitor;
+
+        impl<'de> Visitor<'de> for QueryVisitor {
+            type Value = Query;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a query string or a map of query parameters")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut params = Vec::new();
+
+                while let Some((name, ParamValue(value))) =
+                    map.next_entry::<String, ParamValue>()?
+                {
+                    params.push((name, value));
+                }
+
+                Query::from_params(params).map_err(de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_any(QueryVisitor)
+    }
+}
+
+/// Version of the [`Query::to_bytes`] format. Decoding accepts every version up to this
+/// one; a change to the layout or to the meaning of a parameter name index must add a
+/// version, with the decoding of older versions kept as a migration path.
+pub const BYTES_VERSION: u8 = 1;
+
+/// Name index marking a parameter that is not in [`PARAM_ORDER`], whose name follows
+const NAMED: u8 = u8::MAX;
+
+#[derive(Error, Debug, Clone)]
+pub enum QueryBytesError {
+    #[error("The encoded query is empty")]
+    Empty,
+    /// Version 0 is never written; any other version is from a newer crate
+    #[error("Encoded query version {0} is not supported; expected 1 to {BYTES_VERSION}")]
+    IncompatibleVersion(u8),
+    #[error("The encoded query is truncated or malformed")]
+    Malformed,
+    #[error("{0}")]
+    QueryParseError(QueryParseError),
+}
+
+crate::impl_from_for_inner_enum!(QueryBytesError: QueryParseError);
+
+fn write_varint(out: &mut Vec<u8>, mut n: usize) {
+    loop {
+        #[allow(clippy::cast_possible_truncation)]
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+
+        if n == 0 {
+            out.push(byte);
+            return;
+        }
+
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_varint(out, s.len());
+    out.extend_from_slice(s.as_bytes());
+}
+
+/// Reads from the front of a byte slice
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, QueryBytesError> {
+        let (&first, rest) = self.0.split_first().ok_or(QueryBytesError::Malformed)?;
+        self.0 = rest;
+        Ok(first)
+    }
+
+    fn varint(&mut self) -> Result<usize, QueryBytesError> {
+        let mut n = 0_usize;
+
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.byte()?;
+            n |= usize::from(byte & 0x7f) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+
+        Err(QueryBytesError::Malformed)
+    }
+
+    fn str(&mut self) -> Result<&'a str, QueryBytesError> {
+        let len = self.varint()?;
+
+        if len > self.0.len() {
+            return Err(QueryBytesError::Malformed);
+        }
+
+        let (s, rest) = self.0.split_at(len);
+        self.0 = rest;
+        std::str::from_utf8(s).map_err(|_| QueryBytesError::Malformed)
+    }
+}
+
+impl Query {
+    /// Compact binary form for job queues and caches: a [`BYTES_VERSION`] byte, the number
+    /// of parameters, then each parameter as its index in [`PARAM_ORDER`] (or a marker and
+    /// its name) and its length-prefixed value.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let params = self.params();
+        let mut out = vec![BYTES_VERSION];
+        write_varint(&mut out, params.len());
+
+        for (name, value) in &params {
+            if let Some(index) = PARAM_ORDER.iter().position(|&known| known == name) {
+                #[allow(clippy::cast_possible_truncation)]
+                out.push(index as u8);
+            } else {
+                out.push(NAMED);
+                write_str(&mut out, name);
+            }
+
+            write_str(&mut out, value);
+        }
+
+        out
+    }
+
+    /// Decodes the output of [`Query::to_bytes`] from this or an earlier crate version
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, QueryBytesError> {
+        let mut reader = Reader(bytes);
+
+        match reader.byte().map_err(|_| QueryBytesError::Empty)? {
+            1 => {}
+            version => return Err(QueryBytesError::IncompatibleVersion(version)),
+        }
+
+        let count = reader.varint()?;
+        let mut params = Vec::with_capacity(count.min(PARAM_ORDER.len()));
+
+        for _ in 0..count {
+            let name = match reader.byte()? {
+                NAMED => reader.str()?,
+                index => PARAM_ORDER
+                    .get(usize::from(index))
+                    .ok_or(QueryBytesError::Malformed)?,
+            };
+
+            params.push((name, reader.str()?));
+        }
+
+        if !reader.0.is_empty() {
+            return Err(QueryBytesError::Malformed);
+        }
+
+        Ok(Self::from_params(params)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use serde::Serialize;
+
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::{
+        request::{
+            bodies::{MajorBody, Spacecraft},
+            ephemeris::{
+                vectors::Correction, CalendarDate, Epoch, JulianDate, OutUnits, StepSize,
+                StepSizeUnit, TimeScale, TimeSpec, TimeSpecError,
+            },
+            observatory::Observatory,
+            params::{
+                param_info, registry, to_params, ApiEndpoint, CanonicalQuery, Coverage,
+                QueryBytesError, QueryParseError, BYTES_VERSION, PARAM_ORDER,
+            },
+            presets::{annual_positions, state_vectors},
+            tle::Tle,
+            CoordError, CylindricalCoord, GeodeticCoord, HzBool, Query, QueryBuilderError,
+        },
+        TestResult,
+    };
+
+    #[derive(Serialize)]
+    struct Unordered {
+        zeta: u8,
+        out_units: &'static str,
+        #[serde(flatten)]
+        flat: Flat,
+        command: &'static str,
+    }
+
+    #[derive(Serialize)]
+    struct Flat {
+        alpha: Option<f64>,
+        obj_data: HzBool,
+        skipped: Option<u8>,
+    }
+
+    #[test]
+    fn test_param_order() -> TestResult {
+        let params = to_params(&Unordered {
+            zeta: 1,
+            out_units: "km-s",
+            flat: Flat {
+                alpha: Some(0.5),
+                obj_data: HzBool::Yes,
+                skipped: None,
+            },
+            command: "499",
+        })?;
+
+        assert_eq!(
+            vec![
+                ("command", "499"),
+                ("obj_data", "yes"),
+                ("out_units", "km-s"),
+                ("alpha", "0.5"),
+                ("zeta", "1"),
+            ],
+            params
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect::<Vec<_>>()
+        );
+
+        assert!(to_params(&[1, 2]).is_err());
+
+        Ok(())
+    }
+
+    fn queries() -> TestResult<Vec<Query>> {
+        let start = Utc.with_ymd_and_hms(2022, 8, 28, 0, 0, 0).unwrap();
+        let mut queries = vec![
+            annual_positions(MajorBody::Mars, MajorBody::Sun, 2023)?,
+            state_vectors(
+                Spacecraft::Jwst,
+                (Observatory::MaunaKea, MajorBody::Earth),
+                TimeSpec::bounded(
+                    (10, StepSizeUnit::Minutes),
+                    start,
+                    start + Duration::days(1),
+                ),
+            )?,
+            state_vectors(
+                MajorBody::Moon,
+                (GeodeticCoord::new(-116.89, 35.43, 1.0)?, MajorBody::Earth),
+                TimeSpec::from_list([
+                    Epoch::from(JulianDate(2_459_000.5)),
+                    Epoch::from(CalendarDate::bc(500, 3, 21)?.and_hms(6, 0, 0)?),
+                ])
+                .with_time_scale(TimeScale::TDB),
+            )?,
+        ];
+
+        let mut builder = Query::elements();
+        builder
+            .common
+            .command(Tle::new(
+                Some("ISS (ZARYA)".to_owned()),
+                "1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927",
+                "2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537",
+            )?)
+            .center((CylindricalCoord::new(30., 1_700., 0.5)?, MajorBody::Moon))
+            .csv_format(true)
+            .time_spec(TimeSpec::bounded(
+                (90, StepSizeUnit::Minutes),
+                start,
+                start + Duration::hours(12),
+            ));
+        builder.specific.out_units(OutUnits::AU_D).elm_labels(false);
+        queries.push(builder.build()?);
+
+        let mut builder = Query::vectors();
+        builder
+            .common
+            .command(MajorBody::Jupiter)
+            .center(MajorBody::SolarSystemBary)
+            .time_spec(TimeSpec::bounded(
+                (6, StepSizeUnit::Hours),
+                start,
+                start + Duration::days(2),
+            ));
+        builder.specific.vec_corr(Correction::LT_S);
+        queries.push(builder.build()?);
+
+        Ok(queries)
+    }
+
+    #[test]
+    fn test_registry() -> TestResult {
+        assert_eq!(
+            PARAM_ORDER,
+            registry()[..PARAM_ORDER.len()]
+                .iter()
+                .map(|info| info.name)
+                .collect::<Vec<_>>()
+        );
+
+        for query in queries()? {
+            for (name, _) in query.params() {
+                let info = param_info(&name).ok_or(name)?;
+                assert_ne!(Coverage::Unsupported, info.coverage);
+            }
+        }
+
+        let quantities = param_info("QUANTITIES").ok_or("QUANTITIES")?;
+        assert_eq!(
+            (Coverage::Unsupported, None),
+            (quantities.coverage, quantities.module)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_params() -> TestResult {
+        for query in queries()? {
+            assert_eq!(query, Query::from_params(query.params())?);
+        }
+
+        // Case and quotes as written by hand
+        let query = Query::from_params([
+            ("COMMAND", "'499'"),
+            ("EPHEM_TYPE", "'V'"),
+            ("CENTER", "'500@10'"),
+            ("TLIST", "'2023-01-01T00:00:00Z'"),
+            ("OUT_UNITS", "'AU-D'"),
+            ("TIME_DIGITS", "'FRACSEC'"),
+            ("NOT VALID", "'dropped'"),
+        ])?;
+        assert_eq!(OutUnits::AU_D, query.out_units());
+        let extras: Vec<_> = query
+            .params()
+            .into_iter()
+            .filter(|(name, _)| !PARAM_ORDER.contains(&name.as_str()))
+            .collect();
+        assert_eq!(
+            vec![("time_digits".to_owned(), "'FRACSEC'".to_owned())],
+            extras
+        );
+        assert_eq!(Some("499"), query.params().first().map(|(_, v)| v.as_str()));
+
+        assert!(matches!(
+            Query::from_params([("ephem_type", "O")]),
+            Err(QueryParseError::UnsupportedEphemType(_))
+        ));
+        assert!(matches!(
+            Query::from_params([("ephem_type", "V"), ("command", "499")]),
+            Err(QueryParseError::Missing("center"))
+        ));
+
+        let variable = StepSize::angular(60).to_string();
+        assert!(matches!(
+            Query::from_params([
+                ("ephem_type", "V"),
+                ("command", "499"),
+                ("center", "500@10"),
+                ("start_time", "2023-01-01T00:00:00Z"),
+                ("stop_time", "2023-01-02T00:00:00Z"),
+                ("step_size", &variable),
+            ]),
+            Err(QueryParseError::QueryBuilderError(
+                QueryBuilderError::TimeSpecError(TimeSpecError::VariableStep)
+            ))
+        ));
+        assert!(matches!(
+            Query::from_params([
+                ("ephem_type", "V"),
+                ("command", "301"),
+                ("center", "coord@399"),
+                ("site_coord", "'-116.89,95,1'"),
+            ]),
+            Err(QueryParseError::CoordError(CoordError::LatitudeOutOfRange(
+                _
+            )))
+        ));
+
+        for query in queries()? {
+            assert_eq!(query, Query::from_params_strict(query.params())?);
+        }
+
+        let misspelled = Query::from_params_strict([
+            ("ephem_type", "V"),
+            ("command", "499"),
+            ("center", "500@10"),
+            ("Stepsize", "1d"),
+        ]);
+        assert_eq!(
+            "Unknown parameter `stepsize`; did you mean `step_size`?",
+            misspelled.unwrap_err().to_string()
+        );
+        assert!(matches!(
+            Query::from_params_strict([("frobnicate", "1")]),
+            Err(QueryParseError::Unknown {
+                suggestion: None,
+                ..
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_url() -> TestResult {
+        for query in queries()? {
+            let url = query.to_url(&ApiEndpoint::default());
+            let (base, string) = url.split_once('?').unwrap();
+
+            assert_eq!(ApiEndpoint::HORIZONS, base);
+            assert_eq!(serde_urlencoded::to_string(&query)?, string);
+            assert_eq!(string, query.to_query_string());
+        }
+
+        let query = &queries()?[0];
+        assert!(query
+            .to_url(&ApiEndpoint::new("http://localhost:8080/api?format=text"))
+            .starts_with("http://localhost:8080/api?format=text&command="));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_str() -> TestResult {
+        for query in queries()? {
+            assert_eq!(query, query.to_query_string().parse()?);
+            assert_eq!(query, query.to_url(&ApiEndpoint::default()).parse()?);
+
+            let deserialized: Query = serde_urlencoded::from_str(&query.to_query_string())?;
+            assert_eq!(query, deserialized);
+        }
+
+        let query: Query =
+            "COMMAND=%27499%27&EPHEM_TYPE=V&CENTER=%27500%4010%27&TLIST=2023-01-01T00%3A00%3A00Z"
+                .parse()?;
+        assert_eq!(
+            Some("500@10"),
+            query.params().get(2).map(|(_, v)| v.as_str())
+        );
+
+        assert!(matches!(
+            "command=%4".parse::<Query>(),
+            Err(QueryParseError::InvalidEncoding(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_canonicalize() -> TestResult {
+        let step = || TimeSpec::from_list([Epoch::from(JulianDate(2_460_000.5))]);
+
+        let mut a = Query::vectors();
+        a.common.command(MajorBody::Mars).center(MajorBody::Sun);
+        a.common.time_spec(step());
+        a.specific
+            .vec_corr(Correction::LT)
+            .out_units(OutUnits::AU_D);
+
+        let mut b = Query::vectors();
+        b.specific
+            .out_units(OutUnits::AU_D)
+            .vec_corr(Correction::LT);
+        b.common
+            .time_spec(step())
+            .center(MajorBody::Sun)
+            .command(MajorBody::Mars);
+
+        let (a, b) = (a.build()?, b.build()?);
+        assert_eq!(a.canonicalize(), b.canonicalize());
+        assert_eq!(a.to_query_string(), a.canonicalize().to_string());
+        assert_eq!(a, CanonicalQuery::from(&b).to_query()?);
+
+        let mut set: HashSet<Query> = queries()?.into_iter().collect();
+        let count = set.len();
+        set.insert(a);
+        set.insert(b);
+        assert_eq!(count + 1, set.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bytes() -> TestResult {
+        for query in queries()? {
+            let bytes = query.to_bytes();
+            assert_eq!(BYTES_VERSION, bytes[0]);
+            assert_eq!(query, Query::from_bytes(&bytes)?);
+        }
+
+        let bytes = queries()?[4].to_bytes();
+        assert!(bytes.len() < 160, "{} bytes", bytes.len());
+
+        assert!(matches!(
+            Query::from_bytes(&[]),
+            Err(QueryBytesError::Empty)
+        ));
+        assert!(matches!(
+            Query::from_bytes(&[BYTES_VERSION + 1]),
+            Err(QueryBytesError::IncompatibleVersion(2))
+        ));
+        assert!(matches!(
+            Query::from_bytes(&[0]),
+            Err(QueryBytesError::IncompatibleVersion(0))
+        ));
+        assert!(matches!(
+            Query::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(QueryBytesError::Malformed)
+        ));
+
+        Ok(())
+    }
+}
@@ -1,51 +1,189 @@
-#![allow(clippy::missing_panics_doc)]
+use chrono::{DateTime, TimeZone, Utc};
 
 use super::{
+    bodies::{MajorBody, SmallBody},
     ephemeris::{
         vectors::{TableFormat, VectorsBuilder},
-        TimeSpec,
+        TimeSpec, TimeSpecError,
     },
-    Body, Center, Query, QueryBuilder,
+    Body, Center, Query, QueryBuilder, QueryBuilderError,
 };
 
 pub fn state_vectors<B: Into<Body>, C: Into<Center>>(
     target: B,
     center: C,
     time: TimeSpec,
-) -> Query {
+) -> Result<Query, QueryBuilderError> {
     let mut query = vectors(target, center, time);
     query.specific.table_format(TableFormat::State);
-    query.build().unwrap()
+    query.build()
 }
 
 pub fn position_vectors<B: Into<Body>, C: Into<Center>>(
     target: B,
     center: C,
     time: TimeSpec,
-) -> Query {
+) -> Result<Query, QueryBuilderError> {
     let mut query = vectors(target, center, time);
     query.specific.table_format(TableFormat::Position);
-    query.build().unwrap()
+    query.build()
 }
 
 pub fn velocity_vector<B: Into<Body>, C: Into<Center>>(
     target: B,
     center: C,
     time: TimeSpec,
-) -> Query {
+) -> Result<Query, QueryBuilderError> {
     let mut query = vectors(target, center, time);
     query.specific.table_format(TableFormat::Velocity);
-    query.build().unwrap()
+    query.build()
 }
 
 pub fn light_time_vectors<B: Into<Body>, C: Into<Center>>(
     target: B,
     center: C,
     time: TimeSpec,
-) -> Query {
+) -> Result<Query, QueryBuilderError> {
     let mut query = vectors(target, center, time);
     query.specific.table_format(TableFormat::LT);
-    query.build().unwrap()
+    query.build()
+}
+
+/// Position vectors on the first day of every month of `year`, at 00:00 UTC
+pub fn annual_positions<B: Into<Body>, C: Into<Center>>(
+    target: B,
+    center: C,
+    year: i32,
+) -> Result<Query, QueryBuilderError> {
+    monthly_positions(target, center, year, 1..=12)
+}
+
+/// Position vectors at the start of each meteorological season of `year`
+/// (1 March, 1 June, 1 September and 1 December), at 00:00 UTC
+pub fn seasonal_positions<B: Into<Body>, C: Into<Center>>(
+    target: B,
+    center: C,
+    year: i32,
+) -> Result<Query, QueryBuilderError> {
+    monthly_positions(target, center, year, [3, 6, 9, 12])
+}
+
+/// State vectors for each of `bodies` at one epoch, relative to the same center.
+///
+/// The parsed tables can be combined with [`crate::response::nbody::Snapshot::from_tables`].
+pub fn snapshot_states<C: Into<Center>>(
+    bodies: &[MajorBody],
+    center: C,
+    epoch: DateTime<Utc>,
+) -> Result<Vec<Query>, QueryBuilderError> {
+    let center = center.into();
+
+    bodies
+        .iter()
+        .map(|&body| state_vectors(body, center.clone(), TimeSpec::from_list([epoch])))
+        .collect()
+}
+
+/// State vectors of the Sun and the eight planetary system barycenters relative to the
+/// Solar System barycenter at `epoch`, the usual initial conditions of an n-body
+/// integration. See [`snapshot_states`] to add more bodies, e.g. `PlutoBary`.
+pub fn solar_system_snapshot(epoch: DateTime<Utc>) -> Result<Vec<Query>, QueryBuilderError> {
+    snapshot_states(
+        &[
+            MajorBody::Sun,
+            MajorBody::MercuryBary,
+            MajorBody::VenusBary,
+            MajorBody::EarthMoonBary,
+            MajorBody::MarsBary,
+            MajorBody::JupiterBary,
+            MajorBody::SaturnBary,
+            MajorBody::UranusBary,
+            MajorBody::NeptuneBary,
+        ],
+        MajorBody::SolarSystemBary,
+        epoch,
+    )
+}
+
+/// Osculating elements of `target` at one epoch, relative to `center`
+pub fn osculating_elements<B: Into<Body>, C: Into<Center>>(
+    target: B,
+    center: C,
+    epoch: DateTime<Utc>,
+) -> Result<Query, QueryBuilderError> {
+    let mut query = Query::elements();
+    query
+        .common
+        .command(target.into())
+        .center(center)
+        .time_spec(TimeSpec::from_list([epoch]));
+    query.build()
+}
+
+/// Osculating elements of each small body in `designations`, e.g. the members of an
+/// asteroid family, with the same center and times
+pub fn small_body_batch<C: Into<Center>>(
+    designations: impl IntoIterator<Item = impl Into<String>>,
+    center: C,
+    time: &TimeSpec,
+) -> Result<Vec<Query>, QueryBuilderError> {
+    let center = center.into();
+
+    designations
+        .into_iter()
+        .map(|des| {
+            let mut query = Query::elements();
+            query
+                .common
+                .command(SmallBody::designation(des))
+                .center(center.clone())
+                .time_spec(time.clone());
+            query.build()
+        })
+        .collect()
+}
+
+/// Position vectors of the same small body from two orbit solutions, selected by their
+/// record numbers in the small-body database, to be compared with
+/// [`VectorTable::position_differences`] after new observations update the orbit.
+///
+/// [`VectorTable::position_differences`]: crate::response::ephemeris::vectors::VectorTable::position_differences
+pub fn solution_comparison<C: Into<Center>>(
+    records: [u32; 2],
+    center: C,
+    time: TimeSpec,
+) -> Result<[Query; 2], QueryBuilderError> {
+    let center = center.into();
+    let query = |record, time| {
+        let mut query = Query::vectors();
+        query
+            .common
+            .command(SmallBody::record(record))
+            .center(center.clone())
+            .time_spec(time);
+        query.specific.table_format(TableFormat::Position);
+        query.build()
+    };
+
+    Ok([query(records[0], time.clone())?, query(records[1], time)?])
+}
+
+fn monthly_positions<B: Into<Body>, C: Into<Center>>(
+    target: B,
+    center: C,
+    year: i32,
+    months: impl IntoIterator<Item = u32>,
+) -> Result<Query, QueryBuilderError> {
+    let epochs = months
+        .into_iter()
+        .map(|month| Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single())
+        .collect::<Option<Vec<_>>>()
+        .ok_or(TimeSpecError::YearOutOfRange(year))?;
+    let time = TimeSpec::from_list(epochs);
+
+    let mut query = vectors(target, center, time);
+    query.specific.table_format(TableFormat::Position);
+    query.build()
 }
 
 fn vectors<B: Into<Body>, C: Into<Center>>(
@@ -67,16 +205,20 @@ mod tests {
     use crate::{
         request::{
             bodies::MajorBody,
-            ephemeris::TimeSpec,
-            presets::{light_time_vectors, position_vectors, state_vectors, velocity_vector},
-            Query,
+            ephemeris::{TimeSpec, TimeSpecError},
+            presets::{
+                annual_positions, light_time_vectors, osculating_elements, position_vectors,
+                seasonal_positions, small_body_batch, snapshot_states, solar_system_snapshot,
+                solution_comparison, state_vectors, velocity_vector,
+            },
+            Query, QueryBuilderError,
         },
         TestResult,
     };
 
     #[test]
     fn test_vectors() -> TestResult {
-        type VectorFn = fn(MajorBody, MajorBody, TimeSpec) -> Query;
+        type VectorFn = fn(MajorBody, MajorBody, TimeSpec) -> Result<Query, QueryBuilderError>;
 
         let funcs: [(VectorFn, &str); 4] = [
             (
@@ -112,15 +254,123 @@ mod tests {
         let (target, center, time) = (
             MajorBody::Europa,
             MajorBody::Jupiter,
-            TimeSpec::from_list(vec![Utc.ymd(2022, 8, 31).and_hms(0, 0, 0)]),
+            TimeSpec::from_list(vec![Utc.with_ymd_and_hms(2022, 8, 31, 0, 0, 0).unwrap()]),
         );
 
         for (func, expected) in funcs {
-            let query = func(target, center, time.clone());
+            let query = func(target, center, time.clone())?;
 
             assert_eq!(expected, serde_urlencoded::to_string(query)?);
         }
 
         Ok(())
     }
+
+    #[test]
+    fn test_annual_positions() -> TestResult {
+        let query = annual_positions(MajorBody::Mars, MajorBody::Sun, 2023)?;
+        let encoded = serde_urlencoded::to_string(query)?;
+
+        assert!(encoded.contains(
+            "&tlist=2023-01-01T00%3A00%3A00Z%2C2023-02-01T00%3A00%3A00Z\
+            %2C2023-03-01T00%3A00%3A00Z%2C2023-04-01T00%3A00%3A00Z\
+            %2C2023-05-01T00%3A00%3A00Z%2C2023-06-01T00%3A00%3A00Z\
+            %2C2023-07-01T00%3A00%3A00Z%2C2023-08-01T00%3A00%3A00Z\
+            %2C2023-09-01T00%3A00%3A00Z%2C2023-10-01T00%3A00%3A00Z\
+            %2C2023-11-01T00%3A00%3A00Z%2C2023-12-01T00%3A00%3A00Z&vec_table=1&"
+        ));
+
+        let query = seasonal_positions(MajorBody::Mars, MajorBody::Sun, 2023)?;
+
+        assert_eq!(
+            "command=499&ephem_type=V&center=500%4010\
+            &ref_system=ICRF&format=text&obj_data=yes&make_ephem=yes\
+            &csv_format=no&tlist=2023-03-01T00%3A00%3A00Z%2C2023-06-01T00%3A00%3A00Z\
+            %2C2023-09-01T00%3A00%3A00Z%2C2023-12-01T00%3A00%3A00Z&vec_table=1\
+            &vec_labels=yes&vec_delta_t=no&vec_corr=NONE&out_units=km-s&ref_plane=E",
+            serde_urlencoded::to_string(query)?
+        );
+
+        assert!(matches!(
+            annual_positions(MajorBody::Mars, MajorBody::Sun, 1_000_000),
+            Err(QueryBuilderError::TimeSpecError(
+                TimeSpecError::YearOutOfRange(1_000_000)
+            ))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot_states() -> TestResult {
+        let queries = snapshot_states(
+            &[MajorBody::Sun, MajorBody::EarthMoonBary],
+            MajorBody::SolarSystemBary,
+            Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap(),
+        )?;
+
+        assert_eq!(2, queries.len());
+        assert!(serde_urlencoded::to_string(&queries[1])?
+            .starts_with("command=3&ephem_type=V&center=500%400&"));
+
+        let queries = solar_system_snapshot(Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap())?;
+        assert_eq!(9, queries.len());
+        assert!(queries[8]
+            .to_query_string()
+            .starts_with("command=8&ephem_type=V&center=500%400&"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_osculating_elements() -> TestResult {
+        let query = osculating_elements(
+            MajorBody::Mars,
+            MajorBody::Sun,
+            Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap(),
+        )?;
+
+        assert_eq!(
+            "command=499&ephem_type=E&center=500%4010\
+            &ref_system=ICRF&format=text&obj_data=yes&make_ephem=yes\
+            &csv_format=no&tlist=2023-01-01T00%3A00%3A00Z&tp_type=Absolute\
+            &out_units=km-s&ref_plane=E&elm_labels=yes",
+            serde_urlencoded::to_string(&query)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_small_body_batch() -> TestResult {
+        let queries = small_body_batch(
+            ["8", "3", "135"],
+            MajorBody::Sun,
+            &TimeSpec::from_list([Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap()]),
+        )?;
+
+        assert_eq!(3, queries.len());
+        assert!(serde_urlencoded::to_string(&queries[2])?
+            .starts_with("command=DES%3D135%3B&ephem_type=E&center=500%4010&"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solution_comparison() -> TestResult {
+        let [a, b] = solution_comparison(
+            [54_000_123, 54_000_124],
+            MajorBody::Sun,
+            TimeSpec::from_list([Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap()]),
+        )?;
+
+        assert!(serde_urlencoded::to_string(&a)?.starts_with("command=54000123%3B&"));
+        assert!(serde_urlencoded::to_string(&b)?.starts_with("command=54000124%3B&"));
+        assert_eq!(
+            a.to_query_string().split_once('&').unwrap().1,
+            b.to_query_string().split_once('&').unwrap().1
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,144 @@
+//! Incremental editing of a query, in the manner of the Horizons telnet interface: start
+//! from a query, change one setting at a time, and send [`Session::query`] again after
+//! each change.
+
+use crate::request::{
+    ephemeris::TimeSpec,
+    params::{check_known, to_params, QueryParseError},
+    Query,
+};
+
+/// Parameters of the time specification, all replaced by a new one
+const TIME_PARAMS: [&str; 4] = ["step_size", "start_time", "stop_time", "tlist"];
+
+/// The current query and the ones it replaced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    query: Query,
+    history: Vec<Query>,
+}
+
+impl Session {
+    pub fn new(query: Query) -> Self {
+        Self {
+            query,
+            history: Vec::new(),
+        }
+    }
+
+    /// The query with all changes so far
+    pub fn query(&self) -> &Query {
+        &self.query
+    }
+
+    /// Sets the parameter `name` to `value` as written in a query string, e.g.
+    /// `("step_size", "1h")`. The session is unchanged if the result is not a valid query.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<&mut Self, QueryParseError> {
+        check_known(name)?;
+        let name = name.to_ascii_lowercase();
+        let mut params = self.query.params();
+
+        match params.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, v)) => *v = value.to_owned(),
+            None => params.push((name, value.to_owned())),
+        }
+
+        self.replace(params)
+    }
+
+    /// Sets the target, e.g. `499` or `DES=2000001;`
+    pub fn set_target(&mut self, command: &str) -> Result<&mut Self, QueryParseError> {
+        self.set("command", command)
+    }
+
+    /// Sets the center, e.g. `500@399`
+    pub fn set_center(&mut self, center: &str) -> Result<&mut Self, QueryParseError> {
+        self.set("center", center)
+    }
+
+    /// Sets the step size of a bounded time specification, e.g. `1h` or `10d`
+    pub fn set_step(&mut self, step: &str) -> Result<&mut Self, QueryParseError> {
+        self.set("step_size", step)
+    }
+
+    /// Replaces the whole time specification
+    ///
+    /// # Panics
+    ///
+    /// Never, since the parameters of a time specification are all scalars.
+    pub fn set_time_spec(&mut self, time_spec: &TimeSpec) -> Result<&mut Self, QueryParseError> {
+        let mut params = self.query.params();
+        params.retain(|(name, _)| !TIME_PARAMS.contains(&name.as_str()));
+        params.extend(to_params(time_spec).expect("time specifications should all be scalars"));
+
+        self.replace(params)
+    }
+
+    /// Reverts the last successful change. Returns `false` if there is none.
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some(query) => {
+                self.query = query;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn replace(&mut self, params: Vec<(String, String)>) -> Result<&mut Self, QueryParseError> {
+        let query = Query::from_params(params)?;
+        self.history.push(std::mem::replace(&mut self.query, query));
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::{
+        request::{
+            bodies::MajorBody,
+            ephemeris::{StepSizeUnit, TimeSpec},
+            params::QueryParseError,
+            presets::state_vectors,
+            session::Session,
+        },
+        TestResult,
+    };
+
+    #[test]
+    fn test_session() -> TestResult {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let query = state_vectors(
+            MajorBody::Mars,
+            MajorBody::Sun,
+            TimeSpec::bounded((1, StepSizeUnit::Days), start, start + Duration::days(30)),
+        )?;
+        let mut session = Session::new(query.clone());
+
+        session.set_step("1h")?.set_target("599")?;
+        let params = session.query().to_query_string();
+        assert!(params.contains("command=599&"));
+        assert!(params.contains("step_size=1h&"));
+
+        assert!(matches!(
+            session.set_step("1 fortnight"),
+            Err(QueryParseError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            session.set("stepsize", "1h"),
+            Err(QueryParseError::Unknown { .. })
+        ));
+        assert!(session.query().to_query_string().contains("step_size=1h&"));
+
+        session.set_time_spec(&TimeSpec::from_list([start]))?;
+        assert!(!session.query().to_query_string().contains("step_size"));
+
+        assert!(session.undo() && session.undo() && session.undo());
+        assert_eq!(&query, session.query());
+        assert!(!session.undo());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+//! Coordinates of Deep Space Network antennas and other tracking stations, to use as a
+//! [`Site`] on the Earth. Positions are rounded to about ten metres, which is ample for
+//! pointing but not for precise ranging.
+
+use crate::request::{GeodeticCoord, Site};
+
+/// A named site on the Earth
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Station {
+    pub name: &'static str,
+    pub coord: GeodeticCoord,
+}
+
+impl Station {
+    const fn new(name: &'static str, lon: f64, lat: f64, alt_km: f64) -> Self {
+        Self {
+            name,
+            // Checked by the tests, as floats cannot be compared in a `const fn`
+            coord: GeodeticCoord { lon, lat, alt_km },
+        }
+    }
+}
+
+impl From<Station> for Site {
+    fn from(station: Station) -> Self {
+        Site::Coord(station.coord)
+    }
+}
+
+/// Goldstone 70 m antenna, California
+pub const DSS_14: Station = Station::new("DSS-14 Goldstone", -116.889_5, 35.425_9, 1.002);
+/// Goldstone 34 m beam waveguide antenna
+pub const DSS_25: Station = Station::new("DSS-25 Goldstone", -116.875_4, 35.337_6, 0.961);
+/// Canberra 70 m antenna, Australia
+pub const DSS_43: Station = Station::new("DSS-43 Canberra", 148.981_3, -35.402_4, 0.690);
+/// Canberra 34 m beam waveguide antenna
+pub const DSS_34: Station = Station::new("DSS-34 Canberra", 148.982_0, -35.398_5, 0.692);
+/// Madrid 70 m antenna, Robledo de Chavela, Spain
+pub const DSS_63: Station = Station::new("DSS-63 Madrid", -4.248_0, 40.431_2, 0.866);
+/// Madrid 34 m beam waveguide antenna
+pub const DSS_54: Station = Station::new("DSS-54 Madrid", -4.254_1, 40.425_6, 0.838);
+
+/// ESA 35 m antenna at New Norcia, Australia
+pub const NEW_NORCIA: Station = Station::new("DSA 1 New Norcia", 116.191_5, -31.048_2, 0.252);
+/// ESA 35 m antenna at Cebreros, Spain
+pub const CEBREROS: Station = Station::new("DSA 2 Cebreros", -4.367_5, 40.452_7, 0.794);
+/// ESA 35 m antenna at Malargüe, Argentina
+pub const MALARGUE: Station = Station::new("DSA 3 Malargüe", -69.398_1, -35.776_0, 1.550);
+
+/// Robert C. Byrd Green Bank Telescope, West Virginia
+pub const GREEN_BANK: Station = Station::new("Green Bank Telescope", -79.839_8, 38.433_0, 0.824);
+/// Parkes 64 m radio telescope, Australia
+pub const PARKES: Station = Station::new("Parkes", 148.263_5, -32.998_4, 0.415);
+
+/// The 70 m and 34 m antennas of the three Deep Space Network complexes
+pub const DSN: [Station; 6] = [DSS_14, DSS_25, DSS_43, DSS_34, DSS_63, DSS_54];
+
+/// The deep space antennas of ESA's ESTRACK network
+pub const ESTRACK: [Station; 3] = [NEW_NORCIA, CEBREROS, MALARGUE];
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        request::{
+            bodies::MajorBody,
+            stations::{DSN, DSS_43, ESTRACK, GREEN_BANK, PARKES},
+            Center, GeodeticCoord,
+        },
+        TestResult,
+    };
+
+    #[test]
+    fn test_station() -> TestResult {
+        for station in DSN.iter().chain(&ESTRACK).chain(&[GREEN_BANK, PARKES]) {
+            let coord = station.coord;
+            assert_eq!(
+                coord,
+                GeodeticCoord::new(coord.lon(), coord.lat(), coord.alt_km())?
+            );
+        }
+
+        let center = Center::from((DSS_43, MajorBody::Earth));
+
+        assert_eq!(
+            "center=coord%40399",
+            serde_urlencoded::to_string([("center", &center)])?
+        );
+        assert_eq!(
+            Some("148.9813,-35.4024,0.69"),
+            center
+                .site_coord()
+                .map(|site| site.site_coord.to_string())
+                .as_deref()
+        );
+
+        Ok(())
+    }
+}
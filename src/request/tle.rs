@@ -0,0 +1,153 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use serde::Serialize;
+use thiserror::Error;
+
+const LINE_LEN: usize = 69;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum InvalidTle {
+    #[error("TLE line {line} has {len} characters instead of 69")]
+    Length { line: u8, len: usize },
+    #[error("TLE line {line} does not start with `{line} `")]
+    LineNumber { line: u8 },
+    #[error("TLE line {line} has checksum {found} but its digits sum to {expected}")]
+    Checksum { line: u8, expected: u8, found: u8 },
+    #[error("TLE lines describe different satellites")]
+    SatelliteMismatch,
+}
+
+/// Two-line element set, used as a query target with `COMMAND='TLE'`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tle {
+    name: Option<String>,
+    line1: String,
+    line2: String,
+}
+
+impl Tle {
+    /// Validates the line lengths, line numbers, checksums and satellite numbers
+    pub fn new(
+        name: Option<String>,
+        line1: impl Into<String>,
+        line2: impl Into<String>,
+    ) -> Result<Self, InvalidTle> {
+        let (line1, line2) = (line1.into(), line2.into());
+
+        validate(1, &line1)?;
+        validate(2, &line2)?;
+
+        if line1[2..7] != line2[2..7] {
+            return Err(InvalidTle::SatelliteMismatch);
+        }
+
+        Ok(Self { name, line1, line2 })
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn lines(&self) -> (&str, &str) {
+        (&self.line1, &self.line2)
+    }
+}
+
+fn validate(line: u8, s: &str) -> Result<(), InvalidTle> {
+    if s.len() != LINE_LEN || !s.is_ascii() {
+        return Err(InvalidTle::Length {
+            line,
+            len: s.chars().count(),
+        });
+    }
+
+    let bytes = s.as_bytes();
+
+    if bytes[0] != b'0' + line || bytes[1] != b' ' {
+        return Err(InvalidTle::LineNumber { line });
+    }
+
+    let expected = checksum(&bytes[..LINE_LEN - 1]);
+
+    match bytes[LINE_LEN - 1] {
+        found @ b'0'..=b'9' if found - b'0' == expected => Ok(()),
+        found => Err(InvalidTle::Checksum {
+            line,
+            expected,
+            found: found.wrapping_sub(b'0'),
+        }),
+    }
+}
+
+/// Sum of all digits, with each `-` counting as 1, modulo 10
+fn checksum(bytes: &[u8]) -> u8 {
+    let sum: u32 = bytes
+        .iter()
+        .map(|&b| match b {
+            b'0'..=b'9' => u32::from(b - b'0'),
+            b'-' => 1,
+            _ => 0,
+        })
+        .sum();
+
+    #[allow(clippy::cast_possible_truncation)]
+    let sum = (sum % 10) as u8;
+    sum
+}
+
+impl Display for Tle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if let Some(name) = &self.name {
+            writeln!(f, "{name}")?;
+        }
+
+        write!(f, "{}\n{}", self.line1, self.line2)
+    }
+}
+
+impl Serialize for Tle {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        s.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::request::tle::{InvalidTle, Tle};
+
+    const LINE1: &str = "1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927";
+    const LINE2: &str = "2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537";
+
+    #[test]
+    fn test_tle_validation() {
+        assert!(Tle::new(Some("ISS (ZARYA)".to_owned()), LINE1, LINE2).is_ok());
+
+        assert_eq!(
+            Err(InvalidTle::Length { line: 1, len: 68 }),
+            Tle::new(None, &LINE1[..68], LINE2)
+        );
+        assert_eq!(
+            Err(InvalidTle::LineNumber { line: 2 }),
+            Tle::new(None, LINE1, LINE1)
+        );
+        assert_eq!(
+            Err(InvalidTle::Checksum {
+                line: 2,
+                expected: 7,
+                found: 8
+            }),
+            Tle::new(None, LINE1, format!("{}8", &LINE2[..68]))
+        );
+        assert_eq!(
+            Err(InvalidTle::SatelliteMismatch),
+            Tle::new(
+                None,
+                LINE1,
+                "2 25545  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563538"
+            )
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! A builder that only has a `build` method once the target, center and time
+//! specification are set, so that a missing field is a compile error rather than a
+//! [`CommonBuilderError`](crate::request::ephemeris::common::CommonBuilderError).
+//!
+//! ```ignore
+//! let query = Query::vectors()
+//!     .typed()
+//!     .target(MajorBody::Jupiter)
+//!     .center(MajorBody::SolarSystemBary)
+//!     .time(time_spec)
+//!     .build()?;
+//! ```
+
+use crate::request::{
+    ephemeris::{
+        common::CommonBuilder, elements::ElementsBuilder, vectors::VectorsBuilder, EphemType,
+        TimeSpec, TimeSpecError,
+    },
+    Center, Command, Query, QueryBuilder, QueryBuilderError,
+};
+
+/// A required field that is not set yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unset;
+
+pub struct TypedBuilder<T, C = Unset, Z = Unset, S = Unset> {
+    builder: QueryBuilder<T>,
+    command: C,
+    center: Z,
+    time_spec: S,
+}
+
+impl<T> QueryBuilder<T> {
+    /// Continues with a [`TypedBuilder`], keeping the settings made so far except the
+    /// target, center and time specification
+    pub fn typed(self) -> TypedBuilder<T> {
+        TypedBuilder {
+            builder: self,
+            command: Unset,
+            center: Unset,
+            time_spec: Unset,
+        }
+    }
+}
+
+impl<T, C, Z, S> TypedBuilder<T, C, Z, S> {
+    pub fn target(self, command: impl Into<Command>) -> TypedBuilder<T, Command, Z, S> {
+        TypedBuilder {
+            builder: self.builder,
+            command: command.into(),
+            center: self.center,
+            time_spec: self.time_spec,
+        }
+    }
+
+    pub fn center(self, center: impl Into<Center>) -> TypedBuilder<T, C, Center, S> {
+        TypedBuilder {
+            builder: self.builder,
+            command: self.command,
+            center: center.into(),
+            time_spec: self.time_spec,
+        }
+    }
+
+    pub fn time(self, time_spec: TimeSpec) -> TypedBuilder<T, C, Z, TimeSpec> {
+        TypedBuilder {
+            builder: self.builder,
+            command: self.command,
+            center: self.center,
+            time_spec,
+        }
+    }
+
+    /// Sets the optional common parameters
+    #[must_use]
+    pub fn common(mut self, f: impl FnOnce(&mut CommonBuilder)) -> Self {
+        f(&mut self.builder.common);
+        self
+    }
+
+    /// Sets the parameters of the ephemeris type
+    #[must_use]
+    pub fn specific(mut self, f: impl FnOnce(&mut T)) -> Self {
+        f(&mut self.builder.specific);
+        self
+    }
+}
+
+impl<T> TypedBuilder<T, Command, Center, TimeSpec> {
+    /// The untyped builder, with the ephemeris type of `T` even if the builder this one was
+    /// made from lacked it
+    fn into_untyped(self, ephem_type: EphemType) -> QueryBuilder<T> {
+        let mut builder = self.builder;
+        builder
+            .common
+            .ephem_type(ephem_type)
+            .command(self.command)
+            .center(self.center)
+            .time_spec(self.time_spec);
+
+        builder
+    }
+}
+
+/// The only error left once the required fields and the ephemeris type are set
+#[allow(clippy::needless_pass_by_value)]
+fn time_spec_error(error: QueryBuilderError) -> TimeSpecError {
+    match error {
+        QueryBuilderError::TimeSpecError(error) => error,
+        QueryBuilderError::CommonBuilderError(error) => {
+            unreachable!("all required fields are set, but {error}")
+        }
+    }
+}
+
+impl TypedBuilder<ElementsBuilder, Command, Center, TimeSpec> {
+    /// Fails only for an invalid time specification
+    pub fn build(self) -> Result<Query, TimeSpecError> {
+        self.into_untyped(EphemType::Elements)
+            .build()
+            .map_err(time_spec_error)
+    }
+}
+
+impl TypedBuilder<VectorsBuilder, Command, Center, TimeSpec> {
+    /// Fails only for an invalid time specification
+    pub fn build(self) -> Result<Query, TimeSpecError> {
+        self.into_untyped(EphemType::Vectors)
+            .build()
+            .map_err(time_spec_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::{
+        request::{
+            bodies::MajorBody,
+            ephemeris::{
+                common::CommonBuilder,
+                vectors::{Correction, VectorsBuilder},
+                StepSizeUnit, TimeSpec, TimeSpecError,
+            },
+            Query, QueryBuilder,
+        },
+        TestResult,
+    };
+
+    #[test]
+    fn test_typed_builder() -> TestResult {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let time_spec =
+            TimeSpec::bounded((1, StepSizeUnit::Days), start, start + Duration::days(10));
+
+        let mut builder = Query::vectors();
+        builder
+            .common
+            .command(MajorBody::Jupiter)
+            .center(MajorBody::SolarSystemBary)
+            .time_spec(time_spec.clone())
+            .csv_format(true);
+        builder.specific.vec_corr(Correction::LT);
+
+        let typed = Query::vectors()
+            .typed()
+            .center(MajorBody::SolarSystemBary)
+            .time(time_spec.clone())
+            .common(|common| {
+                common.csv_format(true);
+            })
+            .specific(|vectors| {
+                vectors.vec_corr(Correction::LT);
+            })
+            .target(MajorBody::Jupiter)
+            .build()?;
+
+        assert_eq!(builder.build()?, typed);
+
+        // A builder made from its public fields has no ephemeris type yet
+        let bare = QueryBuilder {
+            common: CommonBuilder::new(),
+            specific: VectorsBuilder::new(),
+        }
+        .typed()
+        .target(MajorBody::Jupiter)
+        .center(MajorBody::SolarSystemBary)
+        .time(time_spec.clone())
+        .common(|common| {
+            common.csv_format(true);
+        })
+        .specific(|vectors| {
+            vectors.vec_corr(Correction::LT);
+        })
+        .build()?;
+        assert_eq!(typed, bare);
+
+        assert_eq!(
+            Err(TimeSpecError::StartNotBeforeStop),
+            Query::elements()
+                .typed()
+                .target(MajorBody::Mars)
+                .center(MajorBody::Sun)
+                .time(TimeSpec::bounded((1, StepSizeUnit::Days), start, start))
+                .build()
+        );
+
+        Ok(())
+    }
+}
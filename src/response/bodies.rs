@@ -0,0 +1,160 @@
+use crate::response::ephemeris::ParseError;
+
+/// One row of the major-body table returned for `COMMAND='MB'`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MajorBodyRecord {
+    pub code: i64,
+    pub name: String,
+    pub designation: Option<String>,
+    /// IAU names, aliases and other identifiers
+    pub aliases: Option<String>,
+}
+
+impl MajorBodyRecord {
+    /// Parses the fixed-width table, using the dashed line under the column titles to find
+    /// the columns
+    pub fn parse_list(text: &str) -> Result<Vec<Self>, ParseError> {
+        let mut lines = text
+            .lines()
+            .skip_while(|line| !line.trim_start().starts_with("---"));
+
+        let columns = column_spans(lines.next().ok_or(ParseError::MissingMarker("-------"))?);
+
+        if columns.len() < 4 {
+            return Err(ParseError::MissingMarker("-------"));
+        }
+
+        lines
+            .take_while(|line| !line.trim().is_empty())
+            .map(|line| {
+                let field = |i: usize| {
+                    let (start, end) = columns[i];
+                    let end = if i == columns.len() - 1 {
+                        line.len()
+                    } else {
+                        end
+                    };
+
+                    line.get(start.min(line.len())..end.min(line.len()))
+                        .unwrap_or_default()
+                        .trim()
+                };
+                let optional = |i| Some(field(i)).filter(|s| !s.is_empty()).map(str::to_owned);
+
+                let code = field(0).parse().map_err(|_| ParseError::InvalidValue {
+                    label: "ID#".to_owned(),
+                    value: field(0).to_owned(),
+                })?;
+
+                Ok(Self {
+                    code,
+                    name: field(1).to_owned(),
+                    designation: optional(2),
+                    aliases: optional(3),
+                })
+            })
+            .collect()
+    }
+
+    /// The candidates listed when the target of an ephemeris query matches several major
+    /// bodies, or `None` if `text` is not such a response
+    pub fn parse_ambiguous(text: &str) -> Option<Vec<Self>> {
+        if !text.contains("Multiple major-bodies match") {
+            return None;
+        }
+
+        Self::parse_list(text)
+            .ok()
+            .filter(|records| !records.is_empty())
+    }
+}
+
+/// Byte ranges of the runs of `-` in the separator line
+fn column_spans(separator: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+
+    for (i, c) in separator.char_indices().chain([(separator.len(), ' ')]) {
+        match (c == '-', start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                spans.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    // Values may start before the dashes when they are right-aligned (e.g. negative IDs),
+    // so each column extends back to the end of the previous one
+    let mut prev_end = 0;
+    for span in &mut spans {
+        span.0 = prev_end;
+        prev_end = span.1;
+    }
+
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        request::bodies::MajorBodyList,
+        response::{bodies::MajorBodyRecord, ephemeris::ParseError},
+        TestResult,
+    };
+
+    const MB: &str = r#"
+*******************************************************************************
+ Multiple major-bodies match string "MB*"
+
+  ID#      Name                               Designation  IAU/aliases/other   
+  -------  ---------------------------------- -----------  ------------------- 
+        0  Solar System Barycenter                         SSB                  
+        1  Mercury Barycenter                                                   
+      301  Moon                                            Luna                 
+      551  S/2010 J 1                         S/2010 J 1                        
+      399  Earth                                           Geocenter            
+  -125544  International Space Station                     ISS, Zarya
+      506  Himalia                            JVI          
+
+   Number of matches =   7. Use ID# to make unique selection.
+*******************************************************************************
+"#;
+
+    #[test]
+    fn test_major_body_list() -> TestResult {
+        assert_eq!(
+            "format=text&command=MB",
+            serde_urlencoded::to_string(MajorBodyList::default())?
+        );
+
+        let records = MajorBodyRecord::parse_list(MB)?;
+
+        assert_eq!(7, records.len());
+        assert_eq!(
+            MajorBodyRecord {
+                code: 0,
+                name: "Solar System Barycenter".to_owned(),
+                designation: None,
+                aliases: Some("SSB".to_owned()),
+            },
+            records[0]
+        );
+        assert_eq!(None, records[1].aliases);
+        assert_eq!(Some("S/2010 J 1"), records[3].designation.as_deref());
+        assert_eq!(-125_544, records[5].code);
+        assert_eq!(Some("ISS, Zarya"), records[5].aliases.as_deref());
+        assert_eq!(Some("JVI"), records[6].designation.as_deref());
+
+        assert_eq!(
+            Err(ParseError::MissingMarker("-------")),
+            MajorBodyRecord::parse_list("No matches found.")
+        );
+
+        assert_eq!(Some(records), MajorBodyRecord::parse_ambiguous(MB));
+        assert_eq!(None, MajorBodyRecord::parse_ambiguous("$$SOE\n$$EOE"));
+
+        Ok(())
+    }
+}
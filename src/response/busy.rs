@@ -0,0 +1,60 @@
+//! When its queue is full, Horizons answers with a message asking to try again later
+//! instead of a table, still with HTTP status 200. Such a response is a soft failure that
+//! a later request may not hit, unlike an error in the query.
+
+use thiserror::Error;
+
+/// Lowercase phrases of the messages of a busy server
+const BUSY_PHRASES: [&str; 6] = [
+    "queue full",
+    "queue is full",
+    "server busy",
+    "server is busy",
+    "too many requests",
+    "try again later",
+];
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Horizons is busy: {message}")]
+pub struct ServerBusy {
+    /// The line of the response saying so
+    pub message: String,
+}
+
+/// The busy message of `text`, if it is one rather than a table. Only the text before the
+/// `$$SOE` marker is searched, so that the name of a body cannot match.
+pub fn detect_busy(text: &str) -> Option<ServerBusy> {
+    let header = text.split("$$SOE").next().unwrap_or_default();
+
+    header
+        .lines()
+        .find(|line| {
+            let line = line.to_ascii_lowercase();
+            BUSY_PHRASES.iter().any(|phrase| line.contains(phrase))
+        })
+        .map(|line| ServerBusy {
+            message: line.trim().to_owned(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::response::{
+        busy::{detect_busy, ServerBusy},
+        ephemeris::vectors::tests::JUPITER_SSB,
+    };
+
+    #[test]
+    fn test_detect_busy() {
+        assert_eq!(
+            Some(ServerBusy {
+                message: "Query queue full. Try again later.".to_owned(),
+            }),
+            detect_busy("\n  Query queue full. Try again later.\n")
+        );
+        assert!(detect_busy(r#"{"error": "Too Many Requests"}"#).is_some());
+
+        assert_eq!(None, detect_busy(JUPITER_SSB));
+        assert_eq!(None, detect_busy("$$SOE\nserver busy\n$$EOE"));
+    }
+}
@@ -0,0 +1,219 @@
+//! Horizons echoes the settings it read in the header of a table. Comparing them with the
+//! query that was sent catches parameters it interpreted differently than intended, such
+//! as a center name that matched another body.
+//!
+//! Only the center, step size, reference frame and plane, and output units are compared,
+//! and each only if the header states it.
+
+use thiserror::Error;
+
+use crate::{
+    request::{
+        ephemeris::{OutUnits, RefPlane, RefSystem, TimeSpec},
+        Query,
+    },
+    response::ephemeris::TableMeta,
+};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Horizons read the {setting} as `{echoed}`, but the query asked for `{sent}`")]
+pub struct MismatchedEcho {
+    /// e.g. `center`
+    pub setting: &'static str,
+    pub sent: String,
+    pub echoed: String,
+}
+
+/// e.g. `Solar System Barycenter (0)` -> `0`
+fn echoed_id(name: &str) -> Option<&str> {
+    let (_, rest) = name.rsplit_once('(')?;
+    rest.split_once(')').map(|(id, _)| id.trim())
+}
+
+/// e.g. `1440 minutes` or `6 hours`
+fn echoed_minutes(step: &str) -> Option<u32> {
+    let mut words = step.split_whitespace();
+    let value: u32 = words.next()?.parse().ok()?;
+
+    let per_unit = match words.next()?.trim_end_matches('s') {
+        "minute" => 1,
+        "hour" => 60,
+        "day" => 1440,
+        _ => return None,
+    };
+
+    value.checked_mul(per_unit)
+}
+
+/// The plane of a description such as `Ecliptic of J2000.0` or `Earth Mean Equator and
+/// Equinox of Reference Epoch`, if it names one
+fn echoed_plane(frame: &str) -> Option<RefPlane> {
+    if frame.contains("Ecliptic") {
+        Some(RefPlane::Ecliptic)
+    } else if frame.contains("Body") && frame.contains("quator") {
+        Some(RefPlane::BodyEquator)
+    } else if frame.contains("quator") {
+        Some(RefPlane::Frame)
+    } else {
+        None
+    }
+}
+
+fn units_label(out_units: OutUnits) -> &'static str {
+    match out_units {
+        OutUnits::KM_S => "KM-S",
+        OutUnits::KM_D => "KM-D",
+        OutUnits::AU_D => "AU-D",
+    }
+}
+
+/// Fails with the first setting that Horizons echoes differently in the header of `text`
+/// than `query` sent it
+pub fn verify_echo(query: &Query, text: &str) -> Result<(), MismatchedEcho> {
+    let header = text.split("$$SOE").next().unwrap_or_default();
+    let meta = TableMeta::parse(header);
+
+    let mut step = None;
+    let mut coord_system = None;
+
+    for (key, value) in header.lines().filter_map(|line| line.split_once(':')) {
+        let value = value.split('{').next().unwrap_or_default().trim();
+
+        match key.trim() {
+            "Step-size" => step = Some(value),
+            "Coordinate systm" | "Reference plane" => coord_system = Some(value),
+            _ => {}
+        }
+    }
+
+    let mismatch = |setting, sent: String, echoed: &str| {
+        Err(MismatchedEcho {
+            setting,
+            sent,
+            echoed: echoed.to_owned(),
+        })
+    };
+
+    if let (Some(sent), Some(echoed)) = (query.center().body_id(), meta.center.as_deref()) {
+        if echoed_id(echoed).map_or(false, |id| id != sent) {
+            return mismatch("center", query.center().to_string(), echoed);
+        }
+    }
+
+    if let (TimeSpec::Bounded { step_size, .. }, Some(echoed)) = (query.time_spec(), step) {
+        if let (Some(sent), Some(read)) = (step_size.minutes(), echoed_minutes(echoed)) {
+            if sent != read {
+                return mismatch("step size", step_size.to_string(), echoed);
+            }
+        }
+    }
+
+    if let Some(frame) = meta.ref_frame.as_deref() {
+        if frame.contains("B1950") != (query.ref_system() == RefSystem::B1950) {
+            return mismatch(
+                "reference system",
+                format!("{:?}", query.ref_system()),
+                frame,
+            );
+        }
+    }
+
+    if let Some(plane) = coord_system.or(meta.ref_frame.as_deref()) {
+        if echoed_plane(plane).map_or(false, |echoed| echoed != query.ref_plane()) {
+            return mismatch("reference plane", format!("{:?}", query.ref_plane()), plane);
+        }
+    }
+
+    if let Some(echoed) = meta.out_units {
+        if echoed != query.out_units() {
+            return mismatch(
+                "output units",
+                units_label(query.out_units()).to_owned(),
+                units_label(echoed),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::{
+        request::{
+            bodies::MajorBody,
+            ephemeris::{OutUnits, RefPlane, StepSizeUnit, TimeSpec},
+            presets::state_vectors,
+            Query,
+        },
+        response::{
+            echo::{verify_echo, MismatchedEcho},
+            ephemeris::vectors::tests::JUPITER_SSB,
+        },
+        TestResult,
+    };
+
+    #[test]
+    fn test_verify_echo() -> TestResult {
+        let start = Utc.with_ymd_and_hms(2022, 8, 28, 0, 0, 0).unwrap();
+        let time = TimeSpec::bounded((1, StepSizeUnit::Days), start, start + Duration::days(1));
+        let query = state_vectors(MajorBody::Jupiter, MajorBody::SolarSystemBary, time.clone())?;
+
+        assert_eq!(Ok(()), verify_echo(&query, JUPITER_SSB));
+
+        let sun = state_vectors(MajorBody::Jupiter, MajorBody::Sun, time.clone())?;
+        assert_eq!(
+            Err(MismatchedEcho {
+                setting: "center",
+                sent: "Sun (10)".to_owned(),
+                echoed: "Solar System Barycenter (0)".to_owned(),
+            }),
+            verify_echo(&sun, JUPITER_SSB)
+        );
+
+        let mut au = query.clone();
+        au.set_out_units(OutUnits::AU_D);
+        assert!(matches!(
+            verify_echo(&au, JUPITER_SSB),
+            Err(MismatchedEcho {
+                setting: "output units",
+                ..
+            })
+        ));
+
+        let equatorial = Query::vectors()
+            .with_command(MajorBody::Jupiter)
+            .with_center(MajorBody::SolarSystemBary)
+            .with_time_spec(time)
+            .with_specific(|vectors| vectors.ref_plane(RefPlane::Frame))
+            .build()?;
+        assert!(matches!(
+            verify_echo(&equatorial, JUPITER_SSB),
+            Err(MismatchedEcho {
+                setting: "reference plane",
+                ..
+            })
+        ));
+
+        // 1440 minutes is one day; one hour is not
+        let hourly =
+            JUPITER_SSB.replace("Output units", "Step-size       : 60 minutes\nOutput units");
+        let daily = hourly.replace("60 minutes", "1440 minutes");
+        assert_eq!(Ok(()), verify_echo(&query, &daily));
+        assert_eq!(
+            Err(MismatchedEcho {
+                setting: "step size",
+                sent: "1d".to_owned(),
+                echoed: "60 minutes".to_owned(),
+            }),
+            verify_echo(&query, &hourly)
+        );
+
+        // Nothing to compare against
+        assert_eq!(Ok(()), verify_echo(&sun, "$$SOE\n$$EOE"));
+
+        Ok(())
+    }
+}
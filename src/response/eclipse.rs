@@ -0,0 +1,294 @@
+//! Shadow entry and exit of a target behind an occulting body, e.g. a spacecraft entering
+//! the Earth's shadow or the Moon during a lunar eclipse
+
+use thiserror::Error;
+
+use crate::{
+    request::{bodies::MajorBody, ephemeris::OutUnits},
+    response::{
+        ephemeris::{
+            vectors::{VectorTable, EPOCH_TOLERANCE},
+            Vector3D,
+        },
+        events::{crossings, EventKind},
+    },
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ShadowModel {
+    /// Shadow of the body's radius along the anti-Sun direction, with no penumbra
+    Cylindrical,
+    /// Umbral and penumbral cones of a spherical Sun and body
+    #[default]
+    Conical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Illumination {
+    Sunlit,
+    /// Partly shadowed, including the antumbra beyond the tip of the umbral cone
+    Penumbra,
+    Umbra,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShadowEventKind {
+    PenumbraEntry,
+    UmbraEntry,
+    UmbraExit,
+    PenumbraExit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadowEvent {
+    pub jd: f64,
+    pub kind: ShadowEventKind,
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EclipseError {
+    #[error("No radius is known for {0}")]
+    UnknownRadius(MajorBody),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Boundary {
+    Penumbra,
+    Umbra,
+}
+
+/// Signed distances of a point target from the penumbral and umbral cone surfaces of
+/// `occulter`, in km: negative inside the cone
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Shadow {
+    penumbra: f64,
+    umbra: f64,
+}
+
+impl Shadow {
+    /// Positions in km relative to the same origin
+    fn new(
+        sun: Vector3D<f64>,
+        occulter: Vector3D<f64>,
+        target: Vector3D<f64>,
+        (sun_radius, radius): (f64, f64),
+        model: ShadowModel,
+    ) -> Self {
+        let to_sun = sun - occulter;
+        let distance = to_sun.norm();
+        let axis = to_sun * (-1. / distance);
+
+        let offset = target - occulter;
+        let behind = offset.dot(&axis);
+        let from_axis = (offset - axis * behind).norm();
+
+        if behind <= 0. {
+            // Sunward of the occulter, where neither cone reaches
+            return Self {
+                penumbra: f64::INFINITY,
+                umbra: f64::INFINITY,
+            };
+        }
+
+        match model {
+            ShadowModel::Cylindrical => Self {
+                penumbra: from_axis - radius,
+                umbra: from_axis - radius,
+            },
+            ShadowModel::Conical => {
+                let penumbra = ((sun_radius + radius) / distance).asin();
+                let umbra = ((sun_radius - radius) / distance).asin();
+
+                Self {
+                    penumbra: from_axis - (radius / penumbra.cos() + behind * penumbra.tan()),
+                    umbra: from_axis - (radius / umbra.cos() - behind * umbra.tan()),
+                }
+            }
+        }
+    }
+
+    fn distance(self, boundary: Boundary) -> f64 {
+        match boundary {
+            Boundary::Penumbra => self.penumbra,
+            Boundary::Umbra => self.umbra,
+        }
+    }
+
+    fn illumination(self) -> Illumination {
+        if self.umbra < 0. {
+            Illumination::Umbra
+        } else if self.penumbra < 0. {
+            Illumination::Penumbra
+        } else {
+            Illumination::Sunlit
+        }
+    }
+}
+
+/// Tables of the Sun, occulting body and target about the same center, with positions
+pub struct ShadowInput<'a> {
+    pub sun: &'a VectorTable,
+    pub occulter: (MajorBody, &'a VectorTable),
+    pub target: &'a VectorTable,
+}
+
+impl ShadowInput<'_> {
+    /// Illumination of the target at each epoch present in all three tables
+    pub fn illumination(
+        &self,
+        model: ShadowModel,
+    ) -> Result<Vec<(f64, Illumination)>, EclipseError> {
+        Ok(self
+            .shadows(model)?
+            .into_iter()
+            .map(|(jd, shadow)| (jd, shadow.illumination()))
+            .collect())
+    }
+
+    /// Shadow entries and exits between epochs, in time order. The target is treated as a
+    /// point; for an extended target such as the Moon, the events are those of its center.
+    pub fn events(&self, model: ShadowModel) -> Result<Vec<ShadowEvent>, EclipseError> {
+        let shadows = self.shadows(model)?;
+        let mut events = Vec::new();
+
+        let boundaries = match model {
+            // The cylindrical model has a single boundary
+            ShadowModel::Cylindrical => &[Boundary::Umbra][..],
+            ShadowModel::Conical => &[Boundary::Penumbra, Boundary::Umbra][..],
+        };
+
+        for &boundary in boundaries {
+            let (entry, exit) = match boundary {
+                Boundary::Penumbra => (
+                    ShadowEventKind::PenumbraEntry,
+                    ShadowEventKind::PenumbraExit,
+                ),
+                Boundary::Umbra => (ShadowEventKind::UmbraEntry, ShadowEventKind::UmbraExit),
+            };
+
+            let samples: Vec<(f64, f64)> = shadows
+                .iter()
+                .map(|(jd, shadow)| (*jd, shadow.distance(boundary)))
+                .filter(|(_, d)| d.is_finite())
+                .collect();
+
+            events.extend(crossings(&samples).into_iter().map(|event| ShadowEvent {
+                jd: event.jd,
+                kind: match event.kind {
+                    EventKind::Falling => entry,
+                    _ => exit,
+                },
+            }));
+        }
+
+        events.sort_by(|a, b| a.jd.total_cmp(&b.jd));
+        Ok(events)
+    }
+
+    fn shadows(&self, model: ShadowModel) -> Result<Vec<(f64, Shadow)>, EclipseError> {
+        let (body, occulter) = self.occulter;
+        let radii = (
+            MajorBody::Sun
+                .mean_radius_km()
+                .ok_or(EclipseError::UnknownRadius(MajorBody::Sun))?,
+            body.mean_radius_km()
+                .ok_or(EclipseError::UnknownRadius(body))?,
+        );
+
+        let position_km = |table: &VectorTable, jd: f64| {
+            let scale = table
+                .meta
+                .out_units
+                .unwrap_or_default()
+                .conversion_to(OutUnits::KM_S)
+                .length;
+
+            table
+                .records
+                .iter()
+                .find(|record| (record.jd - jd).abs() < EPOCH_TOLERANCE)
+                .and_then(|record| record.position)
+                .map(|position| position * scale)
+        };
+
+        Ok(self
+            .target
+            .records
+            .iter()
+            .filter_map(|record| {
+                let jd = record.jd;
+                let shadow = Shadow::new(
+                    position_km(self.sun, jd)?,
+                    position_km(occulter, jd)?,
+                    position_km(self.target, jd)?,
+                    radii,
+                    model,
+                );
+
+                Some((jd, shadow))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        request::bodies::MajorBody,
+        response::{
+            eclipse::{Illumination, ShadowEventKind, ShadowInput, ShadowModel},
+            ephemeris::{
+                vectors::{tests::position_table, VectorTable},
+                Vector3D,
+            },
+        },
+        TestResult,
+    };
+
+    /// 401 records over one day
+    fn table(position: impl Fn(f64) -> Vector3D<f64>) -> VectorTable {
+        position_table((0..=400).map(|i| f64::from(i) / 400.), position)
+    }
+
+    #[test]
+    fn test_shadow_events() -> TestResult {
+        // Sun on the -x axis, target crossing the shadow 10 000 km behind the Earth
+        let sun = table(|_| Vector3D::new(-149_597_870.7, 0., 0.));
+        let earth = table(|_| Vector3D::new(0., 0., 0.));
+        let target = table(|t| Vector3D::new(10_000., 40_000. * (t - 0.5), 0.));
+
+        let input = ShadowInput {
+            sun: &sun,
+            occulter: (MajorBody::Earth, &earth),
+            target: &target,
+        };
+
+        let events = input.events(ShadowModel::Conical)?;
+        let kinds: Vec<_> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            vec![
+                ShadowEventKind::PenumbraEntry,
+                ShadowEventKind::UmbraEntry,
+                ShadowEventKind::UmbraExit,
+                ShadowEventKind::PenumbraExit
+            ],
+            kinds
+        );
+
+        // Cone radii 10 000 km behind the Earth
+        let y = |jd: f64| 40_000. * (jd - 2_460_000.5);
+        assert!((y(events[0].jd) + 6_418.0).abs() < 0.5);
+        assert!((y(events[1].jd) + 6_325.0).abs() < 0.5);
+        assert!((y(events[3].jd) - 6_418.0).abs() < 0.5);
+
+        let events = input.events(ShadowModel::Cylindrical)?;
+        assert_eq!(2, events.len());
+        assert!((y(events[0].jd) + 6_371.).abs() < 0.1);
+
+        let illumination = input.illumination(ShadowModel::Conical)?;
+        assert_eq!(Illumination::Sunlit, illumination[0].1);
+        assert_eq!(Illumination::Umbra, illumination[200].1);
+
+        Ok(())
+    }
+}
@@ -1,12 +1,40 @@
-use std::ops::{Deref, DerefMut};
+pub mod vectors;
 
+use core::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    ops::{Add, Deref, DerefMut, Mul, Sub},
+};
+
+#[cfg(feature = "std")]
+use thiserror::Error;
+
+#[cfg(feature = "std")]
+use crate::request::ephemeris::{Epoch, JulianDate, OutUnits, TimeScale};
+
+#[cfg(feature = "std")]
+use self::vectors::RecordField;
+
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Vector3D<T>([T; 3]);
 
 #[repr(C)]
 pub struct Vector3DIndex<T> {
-    x: T,
-    y: T,
-    z: T,
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Vector3D<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
+        Self([x, y, z])
+    }
+}
+
+impl<T> From<[T; 3]> for Vector3D<T> {
+    fn from(v: [T; 3]) -> Self {
+        Self(v)
+    }
 }
 
 impl<T> Deref for Vector3D<T> {
@@ -22,3 +50,302 @@ impl<T> DerefMut for Vector3D<T> {
         unsafe { &mut *self.0.as_mut_ptr().cast() }
     }
 }
+
+impl<T: Add<Output = T> + Copy> Add for Vector3D<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self([0, 1, 2].map(|i| self.0[i] + rhs.0[i]))
+    }
+}
+
+impl<T: Sub<Output = T> + Copy> Sub for Vector3D<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self([0, 1, 2].map(|i| self.0[i] - rhs.0[i]))
+    }
+}
+
+impl<T: Mul<Output = T> + Copy> Mul<T> for Vector3D<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self {
+        Self(self.0.map(|x| x * rhs))
+    }
+}
+
+#[allow(clippy::must_use_candidate)]
+impl Vector3D<f64> {
+    pub fn dot(&self, rhs: &Self) -> f64 {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+
+    #[must_use]
+    pub fn cross(&self, rhs: &Self) -> Self {
+        Self([
+            self.y * rhs.z - self.z * rhs.y,
+            self.z * rhs.x - self.x * rhs.z,
+            self.x * rhs.y - self.y * rhs.x,
+        ])
+    }
+
+    #[cfg(feature = "std")]
+    pub fn norm(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+}
+
+/// Why the records of a table could not be parsed, borrowing the offending text from the
+/// table so that it can be reported without allocating
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordError<'a> {
+    MissingMarker(&'static str),
+    InvalidEpoch(&'a str),
+    InvalidValue { label: &'a str, value: &'a str },
+    Unlabelled,
+    CapacityExceeded(usize),
+}
+
+impl Display for RecordError<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::MissingMarker(marker) => write!(f, "Missing `{marker}` marker"),
+            Self::InvalidEpoch(line) => write!(f, "Invalid epoch line `{line}`"),
+            Self::InvalidValue { label, value } => {
+                write!(f, "Invalid value `{value}` for `{label}`")
+            }
+            Self::Unlabelled => f.write_str(
+                "Unlabelled vector tables are not supported; request them with `vec_labels` or `csv_format`",
+            ),
+            Self::CapacityExceeded(capacity) => {
+                write!(f, "The table has more than {capacity} records")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RecordError<'_> {}
+
+#[cfg(feature = "std")]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    #[error("Missing `{0}` marker")]
+    MissingMarker(&'static str),
+    #[error("Invalid epoch line `{0}`")]
+    InvalidEpoch(String),
+    #[error("Invalid value `{value}` for `{label}`")]
+    InvalidValue { label: String, value: String },
+    #[error("Unlabelled vector tables are not supported; request them with `vec_labels` or `csv_format`")]
+    Unlabelled,
+    #[error("The table has more than {0} records")]
+    CapacityExceeded(usize),
+}
+
+#[cfg(feature = "std")]
+impl From<RecordError<'_>> for ParseError {
+    fn from(error: RecordError<'_>) -> Self {
+        match error {
+            RecordError::MissingMarker(marker) => Self::MissingMarker(marker),
+            RecordError::InvalidEpoch(line) => Self::InvalidEpoch(line.to_owned()),
+            RecordError::InvalidValue { label, value } => Self::InvalidValue {
+                label: label.to_owned(),
+                value: value.to_owned(),
+            },
+            RecordError::Unlabelled => Self::Unlabelled,
+            RecordError::CapacityExceeded(capacity) => Self::CapacityExceeded(capacity),
+        }
+    }
+}
+
+/// Information from the header of a table
+#[cfg(feature = "std")]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct TableMeta {
+    /// e.g. `Jupiter (599)`
+    pub target: Option<String>,
+    /// e.g. `Solar System Barycenter (0)`
+    pub center: Option<String>,
+    pub out_units: Option<OutUnits>,
+    /// e.g. `Ecliptic of J2000.0`
+    pub ref_frame: Option<String>,
+    /// Time scale of the epochs of the records
+    pub time_scale: Option<TimeScale>,
+    /// Names of the columns in order, e.g. `JDTDB` or `VX`; see [`columns`](Self::columns)
+    pub column_names: Vec<String>,
+    /// Ephemeris of the target, e.g. `jup365_merged`, or `JPL#659` for orbit solution 659 of
+    /// a small body
+    pub target_source: Option<String>,
+    /// Ephemeris of the center, e.g. `DE441`
+    pub center_source: Option<String>,
+    /// Date of the orbit solution of a small body, e.g. `2023-Jun-24_09:38:17`
+    pub solution_date: Option<String>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Expected a table computed with {expected}, found {}", .found.as_deref().unwrap_or("no planetary ephemeris"))]
+pub struct UnexpectedEphemeris {
+    pub expected: String,
+    pub found: Option<String>,
+}
+
+/// A column of a table as returned by Horizons
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub name: String,
+    /// e.g. `km/s`, if the table states its units
+    pub unit: Option<&'static str>,
+    /// Where the records keep the values of the column. `None` for columns that are not
+    /// parsed, such as the calendar date.
+    pub field: Option<RecordField>,
+}
+
+#[cfg(feature = "std")]
+impl TableMeta {
+    /// Reads the `Name : value` lines preceding `$$SOE`
+    pub(crate) fn parse(header: &str) -> Self {
+        let mut meta = Self::default();
+
+        for line in header.lines() {
+            // Column names of a CSV table, e.g. `JDTDB, Calendar Date (TDB), X, ...`
+            if let Some(scale) = line.split(',').next().and_then(|column| {
+                let column = column.trim();
+                column
+                    .starts_with("JD")
+                    .then(|| TimeScale::from_label(column))
+                    .flatten()
+            }) {
+                meta.time_scale = Some(scale);
+                meta.column_names = line
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|column| !column.is_empty())
+                    .map(str::to_owned)
+                    .collect();
+                continue;
+            }
+
+            // e.g. `Rec #:433 (+COV) Soln.date: 2023-Jun-24_09:38:17   # obs: 9130 (1893-2023)`
+            if let Some((_, date)) = line.split_once("Soln.date:") {
+                meta.solution_date = date.split_whitespace().next().map(str::to_owned);
+                continue;
+            }
+
+            let (key, value) = match line.split_once(':') {
+                Some(kv) => kv,
+                None => continue,
+            };
+
+            // Drop trailing annotations such as `{source: DE441}`, keeping the source
+            let (value, annotation) = value.split_once('{').unwrap_or((value, ""));
+            let value = value.trim();
+            let source = annotation
+                .strip_prefix("source:")
+                .and_then(|source| source.split('}').next())
+                .map(|source| source.trim().to_owned());
+
+            match key.trim() {
+                "Target body name" => {
+                    meta.target = Some(value.to_owned());
+                    meta.target_source = source;
+                }
+                "Center body name" => {
+                    meta.center = Some(value.to_owned());
+                    meta.center_source = source;
+                }
+                "Reference frame" => meta.ref_frame = Some(value.to_owned()),
+                // e.g. `A.D. 2022-Aug-28 00:00:00.0000 TDB`
+                "Start time" => {
+                    meta.time_scale = value.rsplit(' ').next().and_then(TimeScale::from_label);
+                }
+                "Output units" => {
+                    meta.out_units = match value {
+                        "KM-S" => Some(OutUnits::KM_S),
+                        "KM-D" => Some(OutUnits::KM_D),
+                        "AU-D" => Some(OutUnits::AU_D),
+                        _ => None,
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        meta
+    }
+
+    /// The JPL planetary ephemeris the table was computed with, e.g. `DE441`, from the
+    /// source of the center or else of the target
+    pub fn planetary_ephemeris(&self) -> Option<&str> {
+        [&self.center_source, &self.target_source]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .find(|source| source.starts_with("DE"))
+    }
+
+    /// The orbit solution of a small body target, e.g. `JPL#659`
+    pub fn small_body_solution(&self) -> Option<&str> {
+        self.target_source
+            .as_deref()
+            .filter(|source| source.starts_with("JPL#"))
+    }
+
+    /// Fails unless the table was computed with the planetary ephemeris `expected`, e.g.
+    /// `DE441`, to keep archived tables from mixing ephemerides
+    pub fn expect_ephemeris(&self, expected: &str) -> Result<(), UnexpectedEphemeris> {
+        match self.planetary_ephemeris() {
+            Some(found) if found.eq_ignore_ascii_case(expected) => Ok(()),
+            found => Err(UnexpectedEphemeris {
+                expected: expected.to_owned(),
+                found: found.map(str::to_owned),
+            }),
+        }
+    }
+
+    /// The columns of the table with their units and record fields, so that tables can be
+    /// exported without knowing which quantities were requested
+    pub fn columns(&self) -> Vec<Column> {
+        self.column_names
+            .iter()
+            .map(|name| {
+                let field = RecordField::from_label(name);
+
+                Column {
+                    name: name.clone(),
+                    unit: field
+                        .zip(self.out_units)
+                        .map(|(field, units)| field.unit(units)),
+                    field,
+                }
+            })
+            .collect()
+    }
+
+    /// Epoch of a record's Julian date in the time scale of the table, to request the same
+    /// instant again. `None` if the time scale is not known.
+    pub fn epoch(&self, jd: f64) -> Option<Epoch> {
+        Some(Epoch::from(JulianDate(jd)).with_scale(self.time_scale?))
+    }
+}
+
+/// Splits a response into its header and the lines between `$$SOE` and `$$EOE`
+pub(crate) fn split_ephemeris(text: &str) -> Result<(&str, &str), RecordError<'static>> {
+    let (header, rest) = text
+        .split_once("$$SOE")
+        .ok_or(RecordError::MissingMarker("$$SOE"))?;
+    let (body, _) = rest
+        .split_once("$$EOE")
+        .ok_or(RecordError::MissingMarker("$$EOE"))?;
+
+    Ok((header, body))
+}
+
+pub(crate) fn parse_value<'a>(label: &'a str, value: &'a str) -> Result<f64, RecordError<'a>> {
+    value.trim().parse().map_err(|_| RecordError::InvalidValue {
+        label,
+        value: value.trim(),
+    })
+}
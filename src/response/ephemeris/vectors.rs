@@ -0,0 +1,1186 @@
+#![allow(clippy::module_name_repetitions)]
+
+use core::ops::Deref;
+#[cfg(feature = "std")]
+use std::io::{self, BufRead};
+
+#[cfg(feature = "std")]
+use thiserror::Error;
+
+use crate::response::ephemeris::{parse_value, split_ephemeris, RecordError, Vector3D};
+#[cfg(feature = "std")]
+use crate::{
+    request::ephemeris::{OutUnits, TimeScale},
+    response::{
+        busy::{detect_busy, ServerBusy},
+        ephemeris::{ParseError, TableMeta},
+    },
+    units::DAY,
+};
+
+/// One epoch of a vectors table. Which quantities are present depends on the requested
+/// [`TableFormat`](crate::request::ephemeris::vectors::TableFormat).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StateVectorRecord {
+    /// Julian date, usually in TDB
+    pub jd: f64,
+    pub position: Option<Vector3D<f64>>,
+    pub velocity: Option<Vector3D<f64>>,
+    /// One-way light time
+    pub light_time: Option<f64>,
+    pub range: Option<f64>,
+    pub range_rate: Option<f64>,
+}
+
+impl StateVectorRecord {
+    pub(crate) fn new(jd: f64) -> Self {
+        Self {
+            jd,
+            position: None,
+            velocity: None,
+            light_time: None,
+            range: None,
+            range_rate: None,
+        }
+    }
+
+    /// Specific orbital energy v²/2 - μ/r, for a gravitational parameter `mu` in the units
+    /// of the table (e.g. km³/s² for `KM-S`)
+    #[cfg(feature = "std")]
+    pub fn specific_energy(&self, mu: f64) -> Option<f64> {
+        let (r, v) = (self.position?, self.velocity?);
+        Some(v.dot(&v) / 2. - mu / r.norm())
+    }
+
+    /// Specific angular momentum r × v
+    pub fn angular_momentum(&self) -> Option<Vector3D<f64>> {
+        Some(self.position?.cross(&self.velocity?))
+    }
+
+    fn set<'a>(&mut self, label: &'a str, value: &'a str) -> Result<(), RecordError<'a>> {
+        fn component(v: &mut Option<Vector3D<f64>>, i: usize, value: f64) {
+            let v = v.get_or_insert(Vector3D::new(0., 0., 0.));
+            match i {
+                0 => v.x = value,
+                1 => v.y = value,
+                _ => v.z = value,
+            }
+        }
+
+        let parsed = || parse_value(label, value);
+
+        match RecordField::from_label(label) {
+            Some(RecordField::X) => component(&mut self.position, 0, parsed()?),
+            Some(RecordField::Y) => component(&mut self.position, 1, parsed()?),
+            Some(RecordField::Z) => component(&mut self.position, 2, parsed()?),
+            Some(RecordField::VX) => component(&mut self.velocity, 0, parsed()?),
+            Some(RecordField::VY) => component(&mut self.velocity, 1, parsed()?),
+            Some(RecordField::VZ) => component(&mut self.velocity, 2, parsed()?),
+            Some(RecordField::LightTime) => self.light_time = Some(parsed()?),
+            Some(RecordField::Range) => self.range = Some(parsed()?),
+            Some(RecordField::RangeRate) => self.range_rate = Some(parsed()?),
+            Some(RecordField::Jd) | None => {}
+        }
+
+        Ok(())
+    }
+
+    /// The value of `field`, if present
+    pub fn get(&self, field: RecordField) -> Option<f64> {
+        match field {
+            RecordField::Jd => Some(self.jd),
+            RecordField::X => self.position.map(|p| p.x),
+            RecordField::Y => self.position.map(|p| p.y),
+            RecordField::Z => self.position.map(|p| p.z),
+            RecordField::VX => self.velocity.map(|v| v.x),
+            RecordField::VY => self.velocity.map(|v| v.y),
+            RecordField::VZ => self.velocity.map(|v| v.z),
+            RecordField::LightTime => self.light_time,
+            RecordField::Range => self.range,
+            RecordField::RangeRate => self.range_rate,
+        }
+    }
+}
+
+/// A value of a [`StateVectorRecord`], to read the columns of a table generically
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecordField {
+    Jd,
+    X,
+    Y,
+    Z,
+    VX,
+    VY,
+    VZ,
+    LightTime,
+    Range,
+    RangeRate,
+}
+
+impl RecordField {
+    /// The field of a column named e.g. `VX`, or `JDTDB` for the epoch
+    pub fn from_label(label: &str) -> Option<Self> {
+        Some(match label.trim() {
+            "X" => Self::X,
+            "Y" => Self::Y,
+            "Z" => Self::Z,
+            "VX" => Self::VX,
+            "VY" => Self::VY,
+            "VZ" => Self::VZ,
+            "LT" => Self::LightTime,
+            "RG" => Self::Range,
+            "RR" => Self::RangeRate,
+            // Also named for its time scale, as in `TimeScale::from_label`
+            "JD" | "JDUT" | "JDUTC" | "JDTT" | "JDTDB" => Self::Jd,
+            _ => return None,
+        })
+    }
+
+    /// Unit of the field in a table of `out_units`, e.g. `km/s`
+    #[cfg(feature = "std")]
+    pub fn unit(self, out_units: OutUnits) -> &'static str {
+        let (length, velocity, time) = match out_units {
+            OutUnits::KM_S => ("km", "km/s", "s"),
+            OutUnits::KM_D => ("km", "km/d", "d"),
+            OutUnits::AU_D => ("au", "au/d", "d"),
+        };
+
+        match self {
+            Self::Jd => "d",
+            Self::X | Self::Y | Self::Z | Self::Range => length,
+            Self::VX | Self::VY | Self::VZ | Self::RangeRate => velocity,
+            Self::LightTime => time,
+        }
+    }
+}
+
+/// Parsed output of a [`Vectors`](crate::request::ephemeris::vectors::Vectors) query
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorTable {
+    pub meta: TableMeta,
+    pub records: Vec<StateVectorRecord>,
+}
+
+#[cfg(feature = "std")]
+impl VectorTable {
+    /// Parses the text output of a vectors query made with either `vec_labels` or
+    /// `csv_format` enabled
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let (header, body) = split_ephemeris(text)?;
+        let mut meta = TableMeta::parse(header);
+
+        // Labelled epochs end with their time scale
+        if meta.time_scale.is_none() {
+            meta.time_scale = body
+                .lines()
+                .find(|line| parse_epoch(line).is_some())
+                .and_then(|line| line.split_whitespace().last())
+                .and_then(TimeScale::from_label);
+        }
+
+        let mut records = Vec::new();
+        parse_records(text, &mut records)?;
+
+        if meta.column_names.is_empty() {
+            meta.column_names = labelled_columns(body, meta.time_scale);
+        }
+
+        Ok(Self { meta, records })
+    }
+
+    /// Appends the records of `other` that come after the last record of `self`, so that
+    /// tables of consecutive spans (e.g. from [`Query::split`]) merge without repeating the
+    /// epochs they share
+    ///
+    /// [`Query::split`]: crate::request::Query::split
+    pub fn append(&mut self, other: VectorTable) {
+        let last = self.records.last().map(|record| record.jd);
+
+        self.records.extend(
+            other
+                .records
+                .into_iter()
+                .filter(|record| last.map_or(true, |last| record.jd > last + EPOCH_TOLERANCE)),
+        );
+    }
+
+    /// Converts every record to `out_units`, e.g. after switching a query's units with
+    /// [`Query::set_out_units`], so that old and new tables can be archived together.
+    ///
+    /// Returns `None` if the table does not state its units.
+    ///
+    /// [`Query::set_out_units`]: crate::request::Query::set_out_units
+    pub fn to_units(&self, out_units: OutUnits) -> Option<VectorTable> {
+        let conversion = self.meta.out_units?.conversion_to(out_units);
+
+        let records = self
+            .records
+            .iter()
+            .map(|record| StateVectorRecord {
+                jd: record.jd,
+                position: record.position.map(|r| r * conversion.length),
+                velocity: record.velocity.map(|v| v * conversion.velocity),
+                light_time: record.light_time.map(|lt| lt * conversion.time),
+                range: record.range.map(|rg| rg * conversion.length),
+                range_rate: record.range_rate.map(|rr| rr * conversion.velocity),
+            })
+            .collect();
+
+        Some(VectorTable {
+            meta: TableMeta {
+                out_units: Some(out_units),
+                ..self.meta.clone()
+            },
+            records,
+        })
+    }
+
+    /// Re-centers this table on the target of `center`, e.g. converting a barycentric table
+    /// to heliocentric with a table of the Sun about the same barycenter.
+    ///
+    /// Both tables must share epochs, units and reference frame. Only positions and
+    /// velocities are differenced; light time, range and range-rate describe the original
+    /// center and are dropped. The result is purely geometric: if either table was
+    /// requested with light-time or aberration corrections (`vec_corr`), it is *not*
+    /// what Horizons would return for the new center, since those corrections depend on
+    /// the observer. Re-query if that accuracy matters.
+    pub fn recenter(&self, center: &VectorTable) -> Result<VectorTable, RecenterError> {
+        if self.meta.out_units != center.meta.out_units {
+            return Err(RecenterError::MismatchedUnits);
+        }
+
+        let records = self
+            .records
+            .iter()
+            .map(|record| {
+                let origin = center
+                    .records
+                    .iter()
+                    .find(|c| (c.jd - record.jd).abs() < EPOCH_TOLERANCE)
+                    .ok_or(RecenterError::MissingEpoch(record.jd))?;
+
+                Ok(StateVectorRecord {
+                    position: record.position.zip(origin.position).map(|(a, b)| a - b),
+                    velocity: record.velocity.zip(origin.velocity).map(|(a, b)| a - b),
+                    ..StateVectorRecord::new(record.jd)
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(VectorTable {
+            meta: TableMeta {
+                center: center.meta.target.clone(),
+                ..self.meta.clone()
+            },
+            records,
+        })
+    }
+
+    /// Distance between the positions of this table and `other` at each epoch, in the length
+    /// unit of the tables, e.g. between two orbit solutions of the same small body from
+    /// [`solution_comparison`]. Epochs without a position in either table are skipped.
+    ///
+    /// [`solution_comparison`]: crate::request::presets::solution_comparison
+    pub fn position_differences(
+        &self,
+        other: &VectorTable,
+    ) -> Result<Vec<(f64, f64)>, RecenterError> {
+        Ok(self
+            .recenter(other)?
+            .records
+            .iter()
+            .filter_map(|record| Some((record.jd, record.position?.norm())))
+            .collect())
+    }
+
+    /// How far the two-body invariants wander over the table, relative to the first record
+    /// with both position and velocity. For a target whose motion is dominated by the
+    /// center body, drifts well above round-off point at interpolation or unit problems.
+    ///
+    /// Returns `None` if no record has both position and velocity.
+    pub fn invariant_drift(&self, mu: f64) -> Option<InvariantDrift> {
+        let mut states = self
+            .records
+            .iter()
+            .filter_map(|record| Some((record.specific_energy(mu)?, record.angular_momentum()?)));
+
+        let (energy0, h0) = states.next()?;
+        let mut drift = InvariantDrift {
+            energy: 0.,
+            angular_momentum: 0.,
+            pole: 0.,
+        };
+
+        for (energy, h) in states {
+            drift.energy = drift.energy.max(((energy - energy0) / energy0).abs());
+            drift.angular_momentum = drift
+                .angular_momentum
+                .max(((h.norm() - h0.norm()) / h0.norm()).abs());
+            drift.pole = drift.pole.max(h.cross(&h0).norm().atan2(h.dot(&h0)));
+        }
+
+        Some(drift)
+    }
+
+    /// Estimates the acceleration at each epoch with a velocity by differencing velocities:
+    /// second-order central differences inside the table, which allow uneven steps, and
+    /// first-order one-sided differences at the first and last epoch.
+    ///
+    /// Accelerations are in the length and time units of the table, e.g. km/s² for
+    /// `KM-S`. Tables that do not state their units are taken to be `KM-S`, the
+    /// Horizons default. Fewer than two epochs with velocities yield nothing.
+    pub fn accelerations(&self) -> impl Iterator<Item = (f64, Vector3D<f64>)> + '_ {
+        let units = self.meta.out_units.unwrap_or_default();
+        let days = DAY / units.time_coefficient();
+
+        let states: Vec<(f64, Vector3D<f64>)> = self
+            .records
+            .iter()
+            .filter_map(|record| Some((record.jd * days, record.velocity?)))
+            .collect();
+
+        let count = if states.len() < 2 { 0 } else { states.len() };
+
+        (0..count).map(move |i| {
+            let (time, velocity) = states[i];
+
+            let acceleration = if i == 0 || i == count - 1 {
+                let (before, after) = if i == 0 { (0, 1) } else { (i - 1, i) };
+                (states[after].1 - states[before].1) * (1. / (states[after].0 - states[before].0))
+            } else {
+                let ((t0, v0), (t2, v2)) = (states[i - 1], states[i + 1]);
+                let (h1, h2) = (time - t0, t2 - time);
+
+                ((v2 - velocity) * (h1 * h1) + (velocity - v0) * (h2 * h2))
+                    * (1. / (h1 * h2 * (h1 + h2)))
+            };
+
+            (time / days, acceleration)
+        })
+    }
+}
+
+/// Largest deviations of the two-body invariants from their initial values
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvariantDrift {
+    /// Relative change of the specific orbital energy
+    pub energy: f64,
+    /// Relative change of the magnitude of the specific angular momentum
+    pub angular_momentum: f64,
+    /// Angle between the initial and later orbit poles, in radians
+    pub pole: f64,
+}
+
+/// Epochs closer than this (in days, ~1 ms) are considered equal
+#[cfg(feature = "std")]
+pub(crate) const EPOCH_TOLERANCE: f64 = 1e-8;
+
+#[cfg(feature = "std")]
+#[derive(Error, Debug, Clone, Copy, PartialEq)]
+pub enum RecenterError {
+    #[error("The center table has no record at JD {0}")]
+    MissingEpoch(f64),
+    #[error("The tables have different output units")]
+    MismatchedUnits,
+}
+
+fn parse_epoch(line: &str) -> Option<f64> {
+    line.split(['=', ','])
+        .next()
+        .and_then(|jd| jd.trim().parse().ok())
+}
+
+/// Destination of parsed records
+trait Records {
+    fn push(&mut self, record: StateVectorRecord) -> Result<(), RecordError<'static>>;
+    fn last_mut(&mut self) -> Option<&mut StateVectorRecord>;
+}
+
+#[cfg(feature = "std")]
+impl Records for Vec<StateVectorRecord> {
+    fn push(&mut self, record: StateVectorRecord) -> Result<(), RecordError<'static>> {
+        Vec::push(self, record);
+        Ok(())
+    }
+
+    fn last_mut(&mut self) -> Option<&mut StateVectorRecord> {
+        self.as_mut_slice().last_mut()
+    }
+}
+
+impl<const N: usize> Records for RecordBatch<N> {
+    fn push(&mut self, record: StateVectorRecord) -> Result<(), RecordError<'static>> {
+        let slot = self
+            .records
+            .get_mut(self.len)
+            .ok_or(RecordError::CapacityExceeded(N))?;
+
+        *slot = record;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn last_mut(&mut self) -> Option<&mut StateVectorRecord> {
+        self.records[..self.len].last_mut()
+    }
+}
+
+/// Fixed-capacity storage for up to `N` parsed records, for callers that cannot allocate
+/// one, such as embedded targets parsing short `tlist` responses.
+///
+/// Parsing into a batch never allocates, and is all that remains of the crate without its
+/// `std` feature, for `no_std` targets without `alloc`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecordBatch<const N: usize> {
+    records: [StateVectorRecord; N],
+    len: usize,
+}
+
+impl<const N: usize> RecordBatch<N> {
+    pub fn new() -> Self {
+        Self {
+            records: [StateVectorRecord::new(0.); N],
+            len: 0,
+        }
+    }
+
+    /// Parses the records of a vectors table like `VectorTable::parse`, failing with
+    /// [`RecordError::CapacityExceeded`] if there are more than `N`. The header can be
+    /// parsed separately with `VectorTable::parse` if needed.
+    pub fn parse(text: &str) -> Result<Self, RecordError<'_>> {
+        let mut batch = Self::new();
+        parse_records(text, &mut batch)?;
+        Ok(batch)
+    }
+
+    pub fn as_slice(&self) -> &[StateVectorRecord] {
+        &self.records[..self.len]
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for RecordBatch<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Deref for RecordBatch<N> {
+    type Target = [StateVectorRecord];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+fn parse_records<'a>(text: &'a str, records: &mut impl Records) -> Result<(), RecordError<'a>> {
+    let (header, body) = split_ephemeris(text)?;
+
+    let is_csv = body
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .map_or(false, |line| line.contains(','));
+
+    if is_csv {
+        parse_csv(header, body, records)
+    } else {
+        parse_labelled(body, records)
+    }
+}
+
+/// ```text
+/// 2459819.500000000 = A.D. 2022-Aug-28 00:00:00.0000 TDB
+///  X = 7.353200258814474E+08 Y = 1.639419041092558E+08 Z =-1.714201280124432E+07
+/// ```
+fn parse_labelled<'a>(body: &'a str, records: &mut impl Records) -> Result<(), RecordError<'a>> {
+    for line in body.lines().filter(|line| !line.trim().is_empty()) {
+        parse_labelled_line(line, records)?;
+    }
+
+    Ok(())
+}
+
+/// Starts a record at an epoch line, or sets the values of a line of labels on the last one
+fn parse_labelled_line<'a>(
+    line: &'a str,
+    records: &mut impl Records,
+) -> Result<(), RecordError<'a>> {
+    if let Some(jd) = parse_epoch(line) {
+        return records.push(StateVectorRecord::new(jd));
+    }
+
+    let record = records.last_mut().ok_or(RecordError::InvalidEpoch(line))?;
+
+    // Each `=` separates a label from its value, and each value from the next label
+    let mut segments = line.split('=');
+    let first = segments
+        .next()
+        .map(str::trim)
+        .filter(|label| !label.is_empty() && !label.contains(char::is_whitespace))
+        .ok_or(RecordError::Unlabelled)?;
+    let mut label = Some(first);
+
+    for segment in segments {
+        let mut words = segment.split_whitespace();
+
+        match (label.take(), words.next()) {
+            (Some(label), Some(value)) => record.set(label, value)?,
+            _ => return Err(RecordError::Unlabelled),
+        }
+
+        label = words.next();
+
+        if words.next().is_some() {
+            return Err(RecordError::Unlabelled);
+        }
+    }
+
+    if label.is_some() {
+        return Err(RecordError::Unlabelled);
+    }
+
+    Ok(())
+}
+
+/// The columns of a labelled table, named as in a CSV table: the epoch, its calendar date,
+/// then the labels of the first record
+#[cfg(feature = "std")]
+fn labelled_columns(body: &str, scale: Option<TimeScale>) -> Vec<String> {
+    let mut lines = body
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .skip_while(|line| parse_epoch(line).is_none());
+
+    if lines.next().is_none() {
+        return Vec::new();
+    }
+
+    let mut columns = match scale {
+        Some(scale) => vec![format!("JD{scale}"), format!("Calendar Date ({scale})")],
+        None => vec!["JD".to_owned(), "Calendar Date".to_owned()],
+    };
+
+    // Each label is the first word of a line or the second of a segment between `=`s
+    for line in lines.take_while(|line| parse_epoch(line).is_none()) {
+        let mut segments = line.split('=');
+        columns.extend(segments.next().map(|label| label.trim().to_owned()));
+        columns.extend(
+            segments.filter_map(|segment| segment.split_whitespace().nth(1).map(str::to_owned)),
+        );
+    }
+
+    columns
+}
+
+/// ```text
+///             JDTDB,            Calendar Date (TDB),                      X, ...
+/// 2459819.500000000, A.D. 2022-Aug-28 00:00:00.0000,  7.353200258814474E+08, ...
+/// ```
+fn parse_csv<'a>(
+    header: &'a str,
+    body: &'a str,
+    records: &mut impl Records,
+) -> Result<(), RecordError<'a>> {
+    let columns = csv_columns(header).ok_or(RecordError::Unlabelled)?;
+
+    for line in body.lines().filter(|line| !line.trim().is_empty()) {
+        records.push(parse_csv_line(columns, line)?)?;
+    }
+
+    Ok(())
+}
+
+/// The line of column names in the header of a CSV table
+fn csv_columns(header: &str) -> Option<&str> {
+    header
+        .lines()
+        .rev()
+        .find(|line| line.trim_start().starts_with("JD") && line.contains(','))
+}
+
+fn parse_csv_line<'a>(
+    columns: &'a str,
+    line: &'a str,
+) -> Result<StateVectorRecord, RecordError<'a>> {
+    let jd = parse_epoch(line).ok_or(RecordError::InvalidEpoch(line))?;
+    let mut record = StateVectorRecord::new(jd);
+
+    for (label, value) in columns
+        .split(',')
+        .map(str::trim)
+        .zip(line.split(','))
+        .skip(1)
+    {
+        record.set(label, value)?;
+    }
+
+    Ok(record)
+}
+
+#[cfg(feature = "std")]
+#[derive(Error, Debug)]
+pub enum StreamError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("{0}")]
+    Parse(#[from] ParseError),
+    #[error("{0}")]
+    ServerBusy(#[from] ServerBusy),
+}
+
+/// The records of a vectors table read line by line, e.g. from a response body as it
+/// downloads, so that a long table is never held in memory as a whole
+///
+/// Records are yielded as soon as they are complete, which for a labelled table is when
+/// the next one starts.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct RecordStream<R> {
+    lines: io::Lines<R>,
+    meta: TableMeta,
+    /// The line of column names of a CSV table
+    columns: Option<String>,
+    /// At most the record being read and the one before
+    pending: Vec<StateVectorRecord>,
+    done: bool,
+}
+
+#[cfg(feature = "std")]
+impl<R: BufRead> RecordStream<R> {
+    /// Reads the header of the table from `reader`, up to `$$SOE`, failing with
+    /// [`StreamError::ServerBusy`] if it is a busy message instead (see [`detect_busy`])
+    pub fn new(reader: R) -> Result<Self, StreamError> {
+        let mut lines = reader.lines();
+        let mut header = String::new();
+        let mut marker = false;
+
+        for line in lines.by_ref() {
+            let line = line?;
+            if line.contains("$$SOE") {
+                marker = true;
+                break;
+            }
+            header.push_str(&line);
+            header.push('\n');
+        }
+
+        if let Some(busy) = detect_busy(&header) {
+            return Err(busy.into());
+        }
+
+        if !marker {
+            return Err(ParseError::MissingMarker("$$SOE").into());
+        }
+
+        Ok(Self {
+            lines,
+            meta: TableMeta::parse(&header),
+            columns: csv_columns(&header).map(str::to_owned),
+            pending: Vec::with_capacity(2),
+            done: false,
+        })
+    }
+
+    /// The header of the table. A labelled table states its time scale on the epoch
+    /// lines, so that is only known once the first record has been read.
+    pub fn meta(&self) -> &TableMeta {
+        &self.meta
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<(), ParseError> {
+        if !line.contains(',') {
+            if self.meta.time_scale.is_none() && parse_epoch(line).is_some() {
+                self.meta.time_scale = line
+                    .split_whitespace()
+                    .last()
+                    .and_then(TimeScale::from_label);
+            }
+
+            return Ok(parse_labelled_line(line, &mut self.pending)?);
+        }
+
+        let columns = self.columns.as_deref().ok_or(ParseError::Unlabelled)?;
+        let record = parse_csv_line(columns, line)?;
+        self.pending.push(record);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: BufRead> Iterator for RecordStream<R> {
+    type Item = Result<StateVectorRecord, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.pending.len() > 1 {
+                return Some(Ok(self.pending.remove(0)));
+            }
+
+            if self.done {
+                return self.pending.pop().map(Ok);
+            }
+
+            let error = match self.lines.next() {
+                Some(Ok(line)) if line.contains("$$EOE") => {
+                    self.done = true;
+                    continue;
+                }
+                Some(Ok(line)) if line.trim().is_empty() => continue,
+                Some(Ok(line)) => match self.parse_line(&line) {
+                    Ok(()) => continue,
+                    Err(error) => error.into(),
+                },
+                Some(Err(error)) => error.into(),
+                None => ParseError::MissingMarker("$$EOE").into(),
+            };
+
+            self.done = true;
+            self.pending.clear();
+            return Some(Err(error));
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::{
+        request::ephemeris::{OutUnits, TimeScale},
+        response::ephemeris::{
+            vectors::{
+                RecenterError, RecordBatch, RecordField, RecordStream, StateVectorRecord,
+                StreamError, VectorTable,
+            },
+            Column, ParseError, RecordError, TableMeta, UnexpectedEphemeris, Vector3D,
+        },
+        TestResult,
+    };
+
+    /// A table of the positions `position(t)` only, at `t` days after JD 2460000 for each
+    /// of `days`
+    pub(crate) fn position_table(
+        days: impl IntoIterator<Item = f64>,
+        position: impl Fn(f64) -> Vector3D<f64>,
+    ) -> VectorTable {
+        VectorTable {
+            meta: TableMeta::default(),
+            records: days
+                .into_iter()
+                .map(|t| StateVectorRecord {
+                    position: Some(position(t)),
+                    ..StateVectorRecord::new(2_460_000. + t)
+                })
+                .collect(),
+        }
+    }
+
+    pub(crate) const JUPITER_SSB: &str = "\
+*******************************************************************************
+Target body name: Jupiter (599)                   {source: jup365_merged}
+Center body name: Solar System Barycenter (0)     {source: DE441}
+Center-site name: BODY CENTER
+*******************************************************************************
+Output units    : KM-S
+Reference frame : Ecliptic of J2000.0
+*******************************************************************************
+$$SOE
+2459819.500000000 = A.D. 2022-Aug-28 00:00:00.0000 TDB 
+ X = 7.353200258814474E+08 Y = 1.639419041092558E+08 Z =-1.714201280124432E+07
+ VX=-3.010866096900461E+00 VY= 1.359288482044757E+01 VZ= 1.201041419396966E-02
+ LT= 2.513868784543147E+03 RG= 7.536671209652166E+08 RR= 2.674537995094674E-02
+2459820.500000000 = A.D. 2022-Aug-29 00:00:00.0000 TDB 
+ X = 7.350597575181305E+08 Y = 1.651162657268197E+08 Z =-1.714096798197075E+07
+ VX=-3.013776391029729E+00 VY= 1.358979618163785E+01 VZ= 1.217570380895110E-02
+ LT= 2.513958343139813E+03 RG= 7.536939703586227E+08 RR= 3.541857052928983E-02
+$$EOE
+*******************************************************************************
+";
+
+    pub(crate) const SUN_SSB_CSV: &str = "\
+*******************************************************************************
+Target body name: Sun (10)                        {source: DE441}
+Center body name: Solar System Barycenter (0)     {source: DE441}
+Output units    : KM-S
+*******************************************************************************
+            JDTDB,            Calendar Date (TDB),                      X,                      Y,                      Z,                     VX,                     VY,                     VZ,
+**************************************************************************************************************************************************************************************************
+$$SOE
+2459819.500000000, A.D. 2022-Aug-28 00:00:00.0000, -1.205759778861979E+06,  2.511674521734213E+05,  2.602913799411416E+04, -2.781931660299906E-03, -1.376056109381886E-02,  1.721809016601430E-04,
+2459820.500000000, A.D. 2022-Aug-29 00:00:00.0000, -1.205999992252835E+06,  2.499785169598833E+05,  2.604400685407054E+04, -2.778673587414540E-03, -1.375950465970767E-02,  1.720272333205063E-04,
+$$EOE
+*******************************************************************************
+";
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_parse_labelled() -> TestResult {
+        let table = VectorTable::parse(JUPITER_SSB)?;
+
+        assert_eq!(Some("Jupiter (599)"), table.meta.target.as_deref());
+        assert_eq!(
+            Some("Solar System Barycenter (0)"),
+            table.meta.center.as_deref()
+        );
+        assert_eq!(Some(OutUnits::KM_S), table.meta.out_units);
+        assert_eq!(Some(TimeScale::TDB), table.meta.time_scale);
+        assert_eq!(2, table.records.len());
+
+        assert_eq!(Some("jup365_merged"), table.meta.target_source.as_deref());
+        assert_eq!(Some("DE441"), table.meta.planetary_ephemeris());
+        assert_eq!(None, table.meta.small_body_solution());
+        assert_eq!(Ok(()), table.meta.expect_ephemeris("DE441"));
+        assert_eq!(
+            Err(UnexpectedEphemeris {
+                expected: "DE440".to_owned(),
+                found: Some("DE441".to_owned())
+            }),
+            table.meta.expect_ephemeris("DE440")
+        );
+
+        let record = table.records[0];
+        assert_eq!(2_459_819.5, record.jd);
+        assert_eq!(
+            Some(Vector3D::new(
+                7.353_200_258_814_474E+08,
+                1.639_419_041_092_558E+08,
+                -1.714_201_280_124_432E+07
+            )),
+            record.position
+        );
+        assert_eq!(
+            Some(-3.010_866_096_900_461E+00),
+            record.velocity.map(|v| v.x)
+        );
+        assert_eq!(Some(2.674_537_995_094_674E-02), record.range_rate);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_small_body_solution() {
+        let meta = TableMeta::parse(
+            "\
+Target body name: 433 Eros (A898 PA)              {source: JPL#659}
+Center body name: Sun (10)                        {source: DE441}
+Rec #:433 (+COV) Soln.date: 2023-Jun-24_09:38:17   # obs: 9130 (1893-2023)
+",
+        );
+
+        assert_eq!(Some("433 Eros (A898 PA)"), meta.target.as_deref());
+        assert_eq!(Some("JPL#659"), meta.small_body_solution());
+        assert_eq!(Some("2023-Jun-24_09:38:17"), meta.solution_date.as_deref());
+        assert_eq!(Some("DE441"), meta.planetary_ephemeris());
+
+        assert_eq!(
+            Err(UnexpectedEphemeris {
+                expected: "DE441".to_owned(),
+                found: None
+            }),
+            TableMeta::default().expect_ephemeris("DE441")
+        );
+    }
+
+    #[test]
+    fn test_record_stream() -> TestResult {
+        for text in [JUPITER_SSB, SUN_SSB_CSV] {
+            let stream = RecordStream::new(text.as_bytes())?;
+            let table = VectorTable::parse(text)?;
+            let records = stream.collect::<Result<Vec<_>, _>>()?;
+            assert_eq!(table.records, records);
+        }
+
+        let mut stream = RecordStream::new(JUPITER_SSB.as_bytes())?;
+        assert_eq!(None, stream.meta().time_scale);
+        assert!(stream.next().is_some());
+        assert_eq!(Some(TimeScale::TDB), stream.meta().time_scale);
+
+        // Cut off in the second record
+        let cut = &JUPITER_SSB[..JUPITER_SSB.find(" LT= 2.513958").unwrap_or_default()];
+        let results: Vec<_> = RecordStream::new(cut.as_bytes())?.collect();
+        assert_eq!(2, results.len());
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            results[1],
+            Err(StreamError::Parse(ParseError::MissingMarker("$$EOE")))
+        ));
+
+        assert!(RecordStream::new("No ephemeris".as_bytes()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_columns() -> TestResult {
+        let table = VectorTable::parse(JUPITER_SSB)?;
+        let columns = table.meta.columns();
+
+        assert_eq!(
+            vec![
+                "JDTDB",
+                "Calendar Date (TDB)",
+                "X",
+                "Y",
+                "Z",
+                "VX",
+                "VY",
+                "VZ",
+                "LT",
+                "RG",
+                "RR"
+            ],
+            columns
+                .iter()
+                .map(|column| column.name.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Column {
+                name: "VX".to_owned(),
+                unit: Some("km/s"),
+                field: Some(RecordField::VX),
+            },
+            columns[5]
+        );
+        assert_eq!((None, None), (columns[1].unit, columns[1].field));
+        assert_eq!(Some("s"), columns[8].unit);
+
+        // Every parsed column can be read back generically
+        let record = table.records[1];
+        assert_eq!(
+            Some(record.range_rate),
+            columns[10].field.map(|field| record.get(field))
+        );
+
+        let csv = VectorTable::parse(SUN_SSB_CSV)?;
+        assert_eq!(
+            columns[..8],
+            csv.meta.columns()[..],
+            "the same columns whether labelled or CSV"
+        );
+
+        let converted = table.to_units(OutUnits::AU_D).unwrap_or(table);
+        assert_eq!(Some("au/d"), converted.meta.columns()[5].unit);
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_parse_csv() -> TestResult {
+        let table = VectorTable::parse(SUN_SSB_CSV)?;
+
+        assert_eq!(2, table.records.len());
+        assert_eq!(
+            "JD2459820.5 TDB",
+            table.meta.epoch(table.records[1].jd).unwrap().to_string()
+        );
+        assert_eq!(2_459_820.5, table.records[1].jd);
+        assert_eq!(
+            Some(1.720_272_333_205_063E-04),
+            table.records[1].velocity.map(|v| v.z)
+        );
+        assert_eq!(None, table.records[1].light_time);
+
+        assert_eq!(
+            Err(ParseError::MissingMarker("$$SOE")),
+            VectorTable::parse("No ephemeris")
+        );
+        assert_eq!(
+            Err(ParseError::Unlabelled),
+            VectorTable::parse("$$SOE\n2459819.5 = A.D. 2022-Aug-28\n 1.0 2.0 3.0\n$$EOE")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_batch() -> TestResult {
+        let batch = RecordBatch::<4>::parse(JUPITER_SSB)?;
+
+        assert_eq!(2, batch.len());
+        assert_eq!(4, batch.capacity());
+        assert_eq!(VectorTable::parse(JUPITER_SSB)?.records, batch.as_slice());
+
+        assert_eq!(2, RecordBatch::<2>::parse(SUN_SSB_CSV)?.len());
+        assert_eq!(
+            Err(RecordError::CapacityExceeded(1)),
+            RecordBatch::<1>::parse(SUN_SSB_CSV)
+        );
+
+        // Errors borrow the offending text, and own it once converted
+        let invalid = "$$SOE\n2459819.5 = A.D. 2022-Aug-28\n X = 1.0 Y = oops Z = 3.0\n$$EOE";
+        let error = RecordBatch::<1>::parse(invalid).unwrap_err();
+        assert_eq!(
+            RecordError::InvalidValue {
+                label: "Y",
+                value: "oops"
+            },
+            error
+        );
+        assert_eq!(Err(ParseError::from(error)), VectorTable::parse(invalid));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_units() -> TestResult {
+        let table = VectorTable::parse(JUPITER_SSB)?;
+        let converted = table.to_units(OutUnits::AU_D).ok_or("units are known")?;
+
+        assert_eq!(Some(OutUnits::AU_D), converted.meta.out_units);
+
+        let record = converted.records[0];
+        let position = record.position.ok_or("position")?;
+        assert!((position.x - 4.915_310_776).abs() < 1e-9);
+        assert!((record.light_time.ok_or("light time")? - 0.029_095_704).abs() < 1e-9);
+        assert!((record.velocity.ok_or("velocity")?.y - 7.850_547_9E-03).abs() < 1e-9);
+
+        let back = converted
+            .to_units(OutUnits::KM_S)
+            .ok_or("units are known")?;
+        let error = back.records[1].position.ok_or("position")?
+            - table.records[1].position.ok_or("position")?;
+        assert!(error.norm() < 1e-6);
+
+        let mut unknown = table;
+        unknown.meta.out_units = None;
+        assert_eq!(None, unknown.to_units(OutUnits::KM_D));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_accelerations() -> TestResult {
+        // v = (t², t, 0) in km/s, with t in s and uneven steps
+        let records = [0., 1., 3., 4.]
+            .iter()
+            .map(|&t| StateVectorRecord {
+                velocity: Some(Vector3D::new(t * t, t, 0.)),
+                ..StateVectorRecord::new(t / 86_400.)
+            })
+            .collect();
+        let table = VectorTable {
+            meta: TableMeta::default(),
+            records,
+        };
+
+        let accelerations: Vec<_> = table.accelerations().collect();
+        assert_eq!(4, accelerations.len());
+
+        for (i, (t, expected)) in [(1., 2.), (3., 6.)].into_iter().enumerate() {
+            let (jd, a) = accelerations[i + 1];
+            assert!((jd * 86_400. - t).abs() < 1e-6);
+            assert!((a.x - expected).abs() < 1e-6);
+            assert!((a.y - 1.).abs() < 1e-6);
+        }
+
+        // One-sided at the edges
+        assert!((accelerations[0].1.x - 1.).abs() < 1e-6);
+        assert!((accelerations[3].1.x - 7.).abs() < 1e-6);
+
+        let jupiter = VectorTable::parse(JUPITER_SSB)?;
+        let accelerations: Vec<_> = jupiter.accelerations().collect();
+        assert_eq!(2, accelerations.len());
+        assert_eq!(accelerations[0].1, accelerations[1].1);
+        assert!((accelerations[0].1.x + 2.910_294_13E-3 / 86_400.).abs() < 1e-15);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_recenter() -> TestResult {
+        let (jupiter, sun) = (
+            VectorTable::parse(JUPITER_SSB)?,
+            VectorTable::parse(SUN_SSB_CSV)?,
+        );
+
+        let heliocentric = jupiter.recenter(&sun)?;
+
+        assert_eq!(Some("Sun (10)"), heliocentric.meta.center.as_deref());
+
+        let record = heliocentric.records[0];
+        let position = record.position.unwrap();
+        assert!(
+            (position.x - (7.353_200_258_814_474E+08 + 1.205_759_778_861_979E+06)).abs() < 1e-6
+        );
+        assert_eq!(None, record.range);
+
+        let mut sun = sun;
+        sun.records.pop();
+        assert_eq!(
+            Err(RecenterError::MissingEpoch(2_459_820.5)),
+            jupiter.recenter(&sun)
+        );
+
+        let differences = jupiter.position_differences(&jupiter)?;
+        assert_eq!(jupiter.records.len(), differences.len());
+        #[allow(clippy::float_cmp)]
+        {
+            assert!(differences.iter().all(|&(_, distance)| distance == 0.));
+        }
+        assert_eq!(
+            Err(RecenterError::MissingEpoch(2_459_820.5)),
+            jupiter.position_differences(&sun)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invariants() {
+        // Circular heliocentric orbit at 1 AU, sampled every 10 days
+        const MU: f64 = 1.327_124_400_41E+11;
+        const R: f64 = 1.495_978_707E+08;
+
+        let v = (MU / R).sqrt();
+        let omega = v / R * 86_400.;
+
+        let mut table = VectorTable {
+            meta: TableMeta::default(),
+            records: (0..10)
+                .map(|i| {
+                    let t = f64::from(i) * 10.;
+                    let (s, c) = (omega * t).sin_cos();
+
+                    StateVectorRecord {
+                        position: Some(Vector3D::new(R * c, R * s, 0.)),
+                        velocity: Some(Vector3D::new(-v * s, v * c, 0.)),
+                        ..StateVectorRecord::new(2_451_545. + t)
+                    }
+                })
+                .collect(),
+        };
+
+        let record = table.records[3];
+        assert!((record.specific_energy(MU).unwrap() + MU / (2. * R)).abs() < 1e-6);
+        assert!((record.angular_momentum().unwrap().z - R * v).abs() < 1e-3);
+
+        let drift = table.invariant_drift(MU).unwrap();
+        assert!(drift.energy < 1e-12, "{drift:?}");
+        assert!(drift.angular_momentum < 1e-12, "{drift:?}");
+        assert!(drift.pole < 1e-12, "{drift:?}");
+
+        // Velocities in km/d instead of km/s
+        table.records[5].velocity = table.records[5].velocity.map(|v| v * 86_400.);
+        table.records[7].position = table.records[7]
+            .position
+            .map(|r| r + Vector3D::new(0., 0., R * 1e-3));
+
+        let drift = table.invariant_drift(MU).unwrap();
+        assert!(drift.energy > 1.);
+        assert!(drift.angular_momentum > 1.);
+        assert!((drift.pole - 1e-3).abs() < 1e-6, "{drift:?}");
+
+        table.records.iter_mut().for_each(|r| r.velocity = None);
+        assert_eq!(None, table.invariant_drift(MU));
+    }
+}
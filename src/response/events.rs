@@ -0,0 +1,323 @@
+//! Zero crossings and extrema of scalar functions of ephemeris tables.
+//!
+//! A function of each record (or of matching records of two tables) is sampled into
+//! `(jd, value)` pairs with [`sample`] or [`sample_pair`], and [`crossings`] and [`extrema`]
+//! find the events between samples, refining their times by interpolation. For example,
+//! the closest approach of a target is the minimum of the range, and a conjunction of two
+//! targets seen from the same center is a minimum of the angle between their positions.
+
+use crate::{
+    coords::{equatorial_to_ecliptic, Equatorial},
+    request::bodies::MajorBody,
+    response::ephemeris::{
+        vectors::{StateVectorRecord, VectorTable, EPOCH_TOLERANCE},
+        Vector3D,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// The value crosses zero from below
+    Rising,
+    /// The value crosses zero from above
+    Falling,
+    Minimum,
+    Maximum,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Event {
+    /// Refined Julian date of the event
+    pub jd: f64,
+    /// Interpolated value at `jd`: zero for crossings, the extreme value for extrema
+    pub value: f64,
+    pub kind: EventKind,
+}
+
+/// Samples `f` at each record where it is defined
+pub fn sample(
+    table: &VectorTable,
+    f: impl Fn(&StateVectorRecord) -> Option<f64>,
+) -> Vec<(f64, f64)> {
+    table
+        .records
+        .iter()
+        .filter_map(|record| Some((record.jd, f(record)?)))
+        .collect()
+}
+
+/// Samples `f` at each epoch present in both tables, e.g. for the separation of two
+/// targets in tables with the same center
+pub fn sample_pair(
+    a: &VectorTable,
+    b: &VectorTable,
+    f: impl Fn(&StateVectorRecord, &StateVectorRecord) -> Option<f64>,
+) -> Vec<(f64, f64)> {
+    a.records
+        .iter()
+        .filter_map(|ra| {
+            let rb = b
+                .records
+                .iter()
+                .find(|rb| (rb.jd - ra.jd).abs() < EPOCH_TOLERANCE)?;
+
+            Some((ra.jd, f(ra, rb)?))
+        })
+        .collect()
+}
+
+/// Sign changes between consecutive samples, located by linear interpolation. A sample
+/// that is exactly zero is reported as a crossing at its epoch when the signs on either
+/// side differ, and as nothing when they are the same. Of a run of zero samples, the
+/// first is reported.
+pub fn crossings(samples: &[(f64, f64)]) -> Vec<Event> {
+    let mut events = Vec::new();
+    let mut previous: Option<(f64, f64)> = None;
+    // Epoch of the first zero sample since `previous`
+    let mut zero: Option<f64> = None;
+
+    for &(jd, value) in samples {
+        if value == 0. {
+            zero = zero.or(Some(jd));
+            continue;
+        }
+
+        if let Some((jd0, value0)) = previous {
+            if value0.signum() != value.signum() {
+                let jd = zero.unwrap_or_else(|| jd0 + (jd - jd0) * value0 / (value0 - value));
+                let kind = if value > 0. {
+                    EventKind::Rising
+                } else {
+                    EventKind::Falling
+                };
+
+                events.push(Event {
+                    jd,
+                    value: 0.,
+                    kind,
+                });
+            }
+        }
+
+        previous = Some((jd, value));
+        zero = None;
+    }
+
+    events
+}
+
+/// Local minima and maxima inside the sampled span, refined with a parabola through the
+/// extreme sample and its neighbours. Extrema at the first or last sample are not reported,
+/// since the function may keep decreasing or increasing beyond the table.
+///
+/// For closest approaches, sample the squared distance: it is quadratic in time for
+/// straight-line relative motion, so the refinement is exact near the minimum.
+pub fn extrema(samples: &[(f64, f64)]) -> Vec<Event> {
+    samples
+        .windows(3)
+        .filter_map(|window| {
+            let [(t0, y0), (t1, y1), (t2, y2)] = [window[0], window[1], window[2]];
+
+            let kind = if y1 < y0 && y1 <= y2 {
+                EventKind::Minimum
+            } else if y1 > y0 && y1 >= y2 {
+                EventKind::Maximum
+            } else {
+                return None;
+            };
+
+            // Vertex of the parabola through the three samples
+            let (d0, d2) = ((y1 - y0) / (t1 - t0), (y2 - y1) / (t2 - t1));
+            let curvature = (d2 - d0) / (t2 - t0);
+
+            if curvature == 0. {
+                return Some(Event {
+                    jd: t1,
+                    value: y1,
+                    kind,
+                });
+            }
+
+            let slope = d0 - curvature * (t1 - t0);
+            let jd = t0 - slope / (2. * curvature);
+            let value = y0 + (jd - t0) * (slope + curvature * (jd - t0));
+
+            Some(Event { jd, value, kind })
+        })
+        .collect()
+}
+
+/// Whether the vectors of `table` are equatorial (ICRF) rather than in the ecliptic of
+/// J2000, which is the Horizons default and assumed unless the header names another frame
+pub(crate) fn is_equatorial(table: &VectorTable) -> bool {
+    table.meta.ref_frame.as_deref().map_or(false, |frame| {
+        !frame.to_ascii_lowercase().contains("ecliptic")
+    })
+}
+
+/// A plane through the center of a table, given by its north normal in the ICRF
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    normal: Vector3D<f64>,
+}
+
+impl Plane {
+    /// The plane perpendicular to `pole`
+    pub fn from_pole(pole: Equatorial) -> Self {
+        Self {
+            normal: pole.to_unit_vector().into(),
+        }
+    }
+
+    /// The ecliptic of J2000
+    pub fn ecliptic() -> Self {
+        Self::from_pole(Equatorial::new(
+            270.,
+            90. - crate::coords::J2000_OBLIQUITY / 3600.,
+        ))
+    }
+
+    /// The equator of `body`, which for Saturn is also its ring plane. `None` if the
+    /// pole of the body is not known, see [`MajorBody::north_pole`].
+    pub fn equator_of(body: MajorBody) -> Option<Self> {
+        body.north_pole().map(Self::from_pole)
+    }
+
+    /// Normal in the frame of `table`, see [`is_equatorial`]
+    pub(crate) fn normal_for(&self, table: &VectorTable) -> Vector3D<f64> {
+        if is_equatorial(table) {
+            self.normal
+        } else {
+            let n = self.normal;
+            equatorial_to_ecliptic([n.x, n.y, n.z]).into()
+        }
+    }
+}
+
+/// Crossings of `plane` by the target of `table`: [`EventKind::Rising`] when passing to
+/// the north side. For crossings of a body's plane as seen from elsewhere, such as
+/// Saturn's ring-plane crossings seen from the Earth, use a table of the observer about
+/// the body (e.g. the Earth with center Saturn).
+pub fn plane_crossings(table: &VectorTable, plane: Plane) -> Vec<Event> {
+    let normal = plane.normal_for(table);
+    crossings(&sample(table, |record| Some(record.position?.dot(&normal))))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        request::bodies::MajorBody,
+        response::{
+            ephemeris::{
+                vectors::{tests::position_table, VectorTable},
+                Vector3D,
+            },
+            events::{
+                crossings, extrema, plane_crossings, sample, sample_pair, Event, EventKind, Plane,
+            },
+        },
+        TestResult,
+    };
+
+    /// Hourly for two days
+    fn table(position: impl Fn(f64) -> Vector3D<f64>) -> VectorTable {
+        position_table((0..48).map(|hour| f64::from(hour) / 24.), position)
+    }
+
+    #[test]
+    fn test_crossings() {
+        // z crosses zero at day 0.3 going up and at day 1.3 going down
+        let table = table(|t| Vector3D::new(1., 0., (std::f64::consts::PI * (t - 0.3)).sin()));
+        let events = crossings(&sample(&table, |record| Some(record.position?.z)));
+
+        assert_eq!(2, events.len());
+        assert_eq!(EventKind::Rising, events[0].kind);
+        assert!((events[0].jd - 2_460_000.3).abs() < 1e-4);
+        assert_eq!(EventKind::Falling, events[1].kind);
+        assert!((events[1].jd - 2_460_001.3).abs() < 1e-4);
+
+        let extrema = extrema(&sample(&table, |record| Some(record.position?.z)));
+        assert_eq!(2, extrema.len());
+        assert_eq!(EventKind::Maximum, extrema[0].kind);
+        assert!((extrema[0].jd - 2_460_000.8).abs() < 1e-4);
+        assert!((extrema[0].value - 1.).abs() < 1e-4);
+        assert_eq!(EventKind::Minimum, extrema[1].kind);
+        assert!((extrema[1].jd - 2_460_001.8).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_zero_samples() {
+        let events = crossings(&[(0., -1.), (1., 0.), (2., 3.)]);
+        assert_eq!(
+            vec![Event {
+                jd: 1.,
+                value: 0.,
+                kind: EventKind::Rising,
+            }],
+            events
+        );
+
+        let events = crossings(&[(0., 2.), (1., 0.), (2., 0.), (3., -1.)]);
+        assert_eq!(
+            vec![Event {
+                jd: 1.,
+                value: 0.,
+                kind: EventKind::Falling,
+            }],
+            events
+        );
+
+        // Touching zero without changing sign
+        assert!(crossings(&[(0., 1.), (1., 0.), (2., 1.)]).is_empty());
+        assert!(crossings(&[(0., 0.), (1., 1.), (2., 0.)]).is_empty());
+    }
+
+    #[test]
+    fn test_closest_approach() {
+        // Two targets passing each other at day 1.234 with a miss distance of 5
+        let a = table(|t| Vector3D::new(100. * (t - 1.234), 0., 0.));
+        let b = table(|_| Vector3D::new(0., 5., 0.));
+
+        let separation = sample_pair(&a, &b, |ra, rb| {
+            let d = ra.position? - rb.position?;
+            Some(d.dot(&d))
+        });
+        let minima = extrema(&separation);
+
+        assert_eq!(1, minima.len());
+        assert_eq!(EventKind::Minimum, minima[0].kind);
+        assert!((minima[0].jd - 2_460_001.234).abs() < 1e-6);
+        assert!((minima[0].value.sqrt() - 5.).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_plane_crossings() -> TestResult {
+        let ecliptic = Plane::ecliptic().normal_for(&table(|_| Vector3D::new(0., 0., 0.)));
+        assert!((ecliptic.z - 1.).abs() < 1e-12);
+
+        // Descending through the ecliptic at day 0.75, in an ecliptic table
+        let events = plane_crossings(
+            &table(|t| Vector3D::new(1e8, 0., 1e6 * (0.75 - t))),
+            Plane::ecliptic(),
+        );
+        assert_eq!(1, events.len());
+        assert_eq!(EventKind::Falling, events[0].kind);
+        assert!((events[0].jd - 2_460_000.75).abs() < 1e-9);
+
+        // Rising through Saturn's ring plane at day 1.5, in an ICRF table
+        let saturn = Plane::equator_of(MajorBody::Saturn).ok_or("Saturn's pole is known")?;
+        let across = saturn.normal.cross(&Vector3D::new(1., 0., 0.));
+
+        let mut icrf = table(|t| across * 1e9 + saturn.normal * (1e7 * (t - 1.5)));
+        icrf.meta.ref_frame = Some("ICRF".to_owned());
+
+        let events = plane_crossings(&icrf, saturn);
+        assert_eq!(1, events.len());
+        assert_eq!(EventKind::Rising, events[0].kind);
+        assert!((events[0].jd - 2_460_001.5).abs() < 1e-6);
+
+        assert_eq!(None, Plane::equator_of(MajorBody::Titan));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,265 @@
+//! Ground tracks of Earth satellites: the points of the WGS 84 ellipsoid below the target of
+//! a geocentric (`@399`) vectors table, exported as `GeoJSON` for web maps.
+//!
+//! Positions are precessed and nutated to the true equator of date and rotated by the
+//! Greenwich apparent sidereal time. Polar motion is ignored, which displaces the points by
+//! up to about 15 m.
+
+use std::fmt::Write;
+
+use crate::{
+    coords::{ecliptic_to_equatorial, horizontal::sidereal_time, nutation, precession, Equatorial},
+    request::ephemeris::{from_julian_date, OutUnits, TimeScale},
+    response::{ephemeris::vectors::VectorTable, events::is_equatorial},
+};
+
+/// Equatorial radius of the WGS 84 ellipsoid, in km
+const WGS84_A: f64 = 6_378.137;
+/// Flattening of the WGS 84 ellipsoid
+const WGS84_F: f64 = 1. / 298.257_223_563;
+
+/// The point below the target at one epoch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubPoint {
+    /// Julian date, in the time scale of the table
+    pub jd: f64,
+    /// East longitude in degrees, in [-180, 180)
+    pub lon: f64,
+    /// Geodetic latitude in degrees
+    pub lat: f64,
+    /// Height above the ellipsoid, in km
+    pub alt_km: f64,
+}
+
+/// Geodetic latitude in degrees and height in km of a point at distance `p` from the axis
+/// and `z` above the equator, in km
+fn geodetic(p: f64, z: f64) -> (f64, f64) {
+    let e2 = WGS84_F * (2. - WGS84_F);
+    let mut lat = z.atan2(p * (1. - e2));
+
+    for _ in 0..5 {
+        let n = WGS84_A / (1. - e2 * lat.sin().powi(2)).sqrt();
+        lat = (z + e2 * n * lat.sin()).atan2(p);
+    }
+
+    let n = WGS84_A / (1. - e2 * lat.sin().powi(2)).sqrt();
+    let alt_km = p * lat.cos() + z * lat.sin() - WGS84_A * WGS84_A / n;
+
+    (lat.to_degrees(), alt_km)
+}
+
+/// The points below the target of a geocentric vectors table, at each record with a
+/// position.
+///
+/// Vectors tables are in TDB, while the rotation of the Earth follows UT: `tt_minus_ut` is
+/// ΔT in seconds (about 69 in the 2020s), applied when the time scale of the table is TT or
+/// TDB. Each second of error turns the track by about 0.004° of longitude.
+pub fn sub_points(table: &VectorTable, tt_minus_ut: f64) -> Vec<SubPoint> {
+    let scale = table
+        .meta
+        .out_units
+        .unwrap_or_default()
+        .conversion_to(OutUnits::KM_S)
+        .length;
+    let equatorial = is_equatorial(table);
+    let ut_offset = match table.meta.time_scale {
+        Some(TimeScale::TT | TimeScale::TDB) => tt_minus_ut / 86_400.,
+        _ => 0.,
+    };
+
+    table
+        .records
+        .iter()
+        .filter_map(|record| {
+            let position = record.position? * scale;
+            let [x, y, z] = [position.x, position.y, position.z];
+            let [x, y, z] = if equatorial {
+                [x, y, z]
+            } else {
+                ecliptic_to_equatorial([x, y, z])
+            };
+
+            let epoch = from_julian_date(record.jd - ut_offset)?;
+            let j2000 = Equatorial::new(y.atan2(x).to_degrees(), z.atan2(x.hypot(y)).to_degrees());
+            let of_date = nutation::mean_to_true(precession::from_j2000(j2000, &epoch), &epoch);
+
+            let r = (x * x + y * y + z * z).sqrt();
+            let dec = of_date.dec.to_radians();
+            let (lat, alt_km) = geodetic(r * dec.cos(), r * dec.sin());
+
+            Some(SubPoint {
+                jd: record.jd,
+                lon: (of_date.ra - sidereal_time(&epoch) + 180.).rem_euclid(360.) - 180.,
+                lat,
+                alt_km,
+            })
+        })
+        .collect()
+}
+
+/// Splits the track into lines of `[lon, lat]` wherever it crosses the antimeridian, ending
+/// and starting them at the interpolated crossing
+fn split_at_antimeridian(points: &[SubPoint]) -> Vec<Vec<[f64; 2]>> {
+    let mut lines = Vec::new();
+    let mut line = Vec::new();
+    let mut previous: Option<&SubPoint> = None;
+
+    for point in points {
+        if let Some(previous) = previous {
+            let delta = point.lon - previous.lon;
+
+            if delta.abs() > 180. {
+                // The edge the track leaves by, heading east (+180) or west (-180)
+                let edge = if delta < 0. { 180. } else { -180. };
+                let unwrapped = point.lon + 2. * edge;
+                let fraction = (edge - previous.lon) / (unwrapped - previous.lon);
+                let lat = previous.lat + fraction * (point.lat - previous.lat);
+
+                line.push([edge, lat]);
+                lines.push(std::mem::take(&mut line));
+                line.push([-edge, lat]);
+            }
+        }
+
+        line.push([point.lon, point.lat]);
+        previous = Some(point);
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// A `GeoJSON` (RFC 7946) `Feature` of the track as a `MultiLineString`, split at the
+/// antimeridian so that maps do not draw it across the whole world
+pub fn to_geojson(points: &[SubPoint]) -> String {
+    let mut coordinates = String::new();
+
+    for (i, line) in split_at_antimeridian(points).iter().enumerate() {
+        let line: Vec<String> = line
+            .iter()
+            .map(|[lon, lat]| format!("[{lon:?},{lat:?}]"))
+            .collect();
+
+        // Writing to a String cannot fail
+        let _ = write!(
+            coordinates,
+            "{}[{}]",
+            if i == 0 { "" } else { "," },
+            line.join(",")
+        );
+    }
+
+    format!(
+        r#"{{"type":"Feature","properties":{{}},"geometry":{{"type":"MultiLineString","coordinates":[{coordinates}]}}}}"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        coords::equatorial_to_ecliptic,
+        request::ephemeris::TimeScale,
+        response::{
+            ephemeris::{
+                vectors::{tests::position_table, VectorTable},
+                Vector3D,
+            },
+            groundtrack::{sub_points, to_geojson, SubPoint, WGS84_A, WGS84_F},
+        },
+    };
+
+    /// A geocentric ICRF table at J2000.0 (UT), when the mean sidereal time is 280.46°
+    fn table(positions: &[Vector3D<f64>]) -> VectorTable {
+        let mut table = position_table(positions.iter().map(|_| -8_455.), |_| {
+            Vector3D::new(0., 0., 0.)
+        });
+        for (record, &position) in table.records.iter_mut().zip(positions) {
+            record.position = Some(position);
+        }
+
+        table.meta.ref_frame = Some("ICRF".to_owned());
+        table.meta.time_scale = Some(TimeScale::UT);
+        table
+    }
+
+    fn at(ra: f64, dec: f64, r: f64) -> Vector3D<f64> {
+        let (ra, dec) = (ra.to_radians(), dec.to_radians());
+        Vector3D::new(dec.cos() * ra.cos(), dec.cos() * ra.sin(), dec.sin()) * r
+    }
+
+    #[test]
+    fn test_sub_points() {
+        let points = sub_points(
+            &table(&[
+                at(310.46, 0., WGS84_A + 400.),
+                at(0., 90., 10_000.),
+                at(280.46 - 120., -45., 42_164.),
+            ]),
+            69.2,
+        );
+
+        assert_eq!(3, points.len());
+        assert!((points[0].lon - 30.).abs() < 0.01, "{:?}", points[0]);
+        assert!(points[0].lat.abs() < 0.01, "{:?}", points[0]);
+        assert!((points[0].alt_km - 400.).abs() < 1e-3, "{:?}", points[0]);
+
+        // Above the pole, at the polar radius
+        assert!((points[1].lat - 90.).abs() < 0.01, "{:?}", points[1]);
+        let b = WGS84_A * (1. - WGS84_F);
+        assert!(
+            (points[1].alt_km - (10_000. - b)).abs() < 0.1,
+            "{:?}",
+            points[1]
+        );
+
+        assert!((points[2].lon + 120.).abs() < 0.01, "{:?}", points[2]);
+        // Geodetic latitudes are further from the equator than geocentric ones
+        assert!((-45.05..-45.).contains(&points[2].lat), "{:?}", points[2]);
+
+        // The same position in the ecliptic frame of the Horizons default
+        let mut ecliptic = table(&[at(310.46, 0., WGS84_A + 400.)]);
+        ecliptic.meta.ref_frame = None;
+        let position = ecliptic.records[0].position.unwrap();
+        ecliptic.records[0].position =
+            Some(equatorial_to_ecliptic([position.x, position.y, position.z]).into());
+        let point = sub_points(&ecliptic, 0.)[0];
+        assert!((point.lon - points[0].lon).abs() < 1e-9, "{point:?}");
+        assert!((point.lat - points[0].lat).abs() < 1e-9, "{point:?}");
+
+        // TDB epochs are ΔT ahead of UT, when the Earth had turned less
+        ecliptic.meta.time_scale = Some(TimeScale::TDB);
+        let point = sub_points(&ecliptic, 69.2)[0];
+        let turn = 69.2 / 86_400. * 360.985_647;
+        assert!((point.lon - points[0].lon - turn).abs() < 1e-4, "{point:?}");
+    }
+
+    #[test]
+    fn test_to_geojson() {
+        let track: Vec<SubPoint> = [(170., 0.), (178., 2.), (-176., 5.), (-170., 6.)]
+            .iter()
+            .map(|&(lon, lat)| SubPoint {
+                jd: 0.,
+                lon,
+                lat,
+                alt_km: 400.,
+            })
+            .collect();
+
+        assert_eq!(
+            r#"{"type":"Feature","properties":{},"geometry":{"type":"MultiLineString","coordinates":[[[170.0,0.0],[178.0,2.0],[180.0,3.0]],[[-180.0,3.0],[-176.0,5.0],[-170.0,6.0]]]}}"#,
+            to_geojson(&track)
+        );
+
+        // Westward
+        let reversed: Vec<SubPoint> = track.iter().rev().copied().collect();
+        assert!(to_geojson(&reversed).contains("[-176.0,5.0],[-180.0,3.0]],[[180.0,3.0],"));
+
+        assert_eq!(
+            r#"{"type":"Feature","properties":{},"geometry":{"type":"MultiLineString","coordinates":[]}}"#,
+            to_geojson(&[])
+        );
+    }
+}
@@ -0,0 +1,156 @@
+//! iCalendar (RFC 5545) export of the events found in tables, such as the closest
+//! approaches and conjunctions of [`events`](crate::response::events) or the shadow
+//! crossings of [`eclipse`](crate::response::eclipse), to import into calendar apps.
+//!
+//! Julian dates are written as UTC without converting from the time scale of the table,
+//! so events found in TDB tables are listed about a minute (TT − UT) late.
+
+use crate::{
+    cache::fnv1a,
+    request::ephemeris::from_julian_date,
+    response::eclipse::{ShadowEvent, ShadowEventKind},
+};
+
+/// Longest content line, in octets, before it is folded
+const MAX_LINE: usize = 75;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    pub jd: f64,
+    /// Title of the event, e.g. `Closest approach of Apophis`
+    pub summary: String,
+}
+
+impl CalendarEvent {
+    pub fn new(jd: f64, summary: impl Into<String>) -> Self {
+        Self {
+            jd,
+            summary: summary.into(),
+        }
+    }
+}
+
+impl From<ShadowEvent> for CalendarEvent {
+    fn from(event: ShadowEvent) -> Self {
+        let summary = match event.kind {
+            ShadowEventKind::PenumbraEntry => "Penumbra entry",
+            ShadowEventKind::UmbraEntry => "Umbra entry",
+            ShadowEventKind::UmbraExit => "Umbra exit",
+            ShadowEventKind::PenumbraExit => "Penumbra exit",
+        };
+
+        Self::new(event.jd, summary)
+    }
+}
+
+/// `text` as a TEXT value, with backslashes, separators and newlines escaped
+fn escaped(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Appends `line` and its CRLF, folded onto continuation lines starting with a space
+fn push_line(out: &mut String, line: &str) {
+    let mut length = 0;
+
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE {
+            out.push_str("\r\n ");
+            length = 1;
+        }
+
+        out.push(c);
+        length += c.len_utf8();
+    }
+
+    out.push_str("\r\n");
+}
+
+/// A calendar of `events`, each a `VEVENT` without duration. Events outside the range of
+/// [`DateTime`](chrono::DateTime) are left out.
+///
+/// The output is reproducible: each `UID` is a hash of the time and summary of its event,
+/// and `DTSTAMP` is the time of the event rather than of the export.
+pub fn to_ics(events: &[CalendarEvent]) -> String {
+    let mut out = String::new();
+
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//horizons-rs//EN");
+
+    for event in events {
+        let start = match from_julian_date(event.jd) {
+            Some(start) => start.format("%Y%m%dT%H%M%SZ").to_string(),
+            None => continue,
+        };
+
+        let uid = fnv1a(format!("{} {}", event.jd, event.summary).as_bytes());
+
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{uid:016x}@horizons-rs"));
+        push_line(&mut out, &format!("DTSTAMP:{start}"));
+        push_line(&mut out, &format!("DTSTART:{start}"));
+        push_line(&mut out, &format!("SUMMARY:{}", escaped(&event.summary)));
+        push_line(&mut out, "END:VEVENT");
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::response::{
+        eclipse::{ShadowEvent, ShadowEventKind},
+        ics::{to_ics, CalendarEvent},
+    };
+
+    #[test]
+    fn test_to_ics() {
+        let events = [
+            CalendarEvent::new(2_460_000.5, "Closest approach; Apophis, 0.0003 au"),
+            CalendarEvent::from(ShadowEvent {
+                jd: 2_460_001.25,
+                kind: ShadowEventKind::UmbraEntry,
+            }),
+            CalendarEvent::new(f64::INFINITY, "Never"),
+            CalendarEvent::new(2_460_002., "Long ".repeat(20)),
+        ];
+
+        let ics = to_ics(&events);
+        let lines: Vec<&str> = ics.split_terminator("\r\n").collect();
+
+        assert_eq!(Some(&"BEGIN:VCALENDAR"), lines.first());
+        assert_eq!(Some(&"END:VCALENDAR"), lines.last());
+        assert_eq!(3, ics.matches("BEGIN:VEVENT").count());
+        assert!(!ics.contains("Never"));
+
+        assert!(lines.contains(&"DTSTART:20230225T000000Z"));
+        assert!(lines.contains(&"SUMMARY:Closest approach\\; Apophis\\, 0.0003 au"));
+        assert!(lines.contains(&"DTSTART:20230225T180000Z"));
+        assert!(lines.contains(&"SUMMARY:Umbra entry"));
+
+        // Folded at 75 octets
+        assert!(lines.iter().all(|line| line.len() <= 75));
+        let unfolded = ics.replace("\r\n ", "");
+        assert!(unfolded.contains(&format!("SUMMARY:{}\r\n", "Long ".repeat(20))));
+
+        // The same calendar every time
+        assert_eq!(ics, to_ics(&events));
+        let uids: Vec<&&str> = lines.iter().filter(|l| l.starts_with("UID:")).collect();
+        assert!(uids[0] != uids[1]);
+    }
+}
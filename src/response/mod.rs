@@ -1 +1,43 @@
+#![allow(clippy::must_use_candidate, clippy::missing_errors_doc)]
+
+#[cfg(feature = "std")]
+pub mod bodies;
+#[cfg(feature = "std")]
+pub mod busy;
+#[cfg(feature = "std")]
+pub mod echo;
+#[cfg(feature = "std")]
+pub mod eclipse;
 pub mod ephemeris;
+#[cfg(feature = "std")]
+pub mod events;
+#[cfg(feature = "std")]
+pub mod groundtrack;
+#[cfg(feature = "std")]
+pub mod ics;
+#[cfg(feature = "std")]
+pub mod nbody;
+#[cfg(feature = "std")]
+pub mod periods;
+#[cfg(feature = "std")]
+pub mod photometry;
+#[cfg(feature = "std")]
+pub mod smoothing;
+#[cfg(feature = "std")]
+pub mod spk;
+#[cfg(feature = "std")]
+pub mod staged;
+
+// Parsed tables hold no shared or interior-mutable state, so they can be handed to and
+// shared between worker threads
+#[cfg(feature = "std")]
+crate::assert_send_sync!(
+    ephemeris::ParseError,
+    ephemeris::TableMeta,
+    ephemeris::vectors::VectorTable,
+    ephemeris::vectors::RecordBatch<16>,
+    bodies::MajorBodyRecord,
+    spk::SpkFile,
+    nbody::Snapshot,
+    smoothing::SavitzkyGolay,
+);
@@ -0,0 +1,298 @@
+//! Initial conditions for N-body integrators, assembled from vector tables
+
+use std::fmt::Write;
+
+use thiserror::Error;
+
+use crate::{
+    request::{
+        bodies::{MajorBody, GM_SUN},
+        ephemeris::OutUnits,
+    },
+    response::ephemeris::{
+        vectors::{VectorTable, EPOCH_TOLERANCE},
+        Vector3D,
+    },
+};
+
+/// Kilometres per astronomical unit
+pub const AU_KM: f64 = 149_597_870.7;
+
+/// Newtonian constant of gravitation, in km³/(kg·s²)
+pub const G: f64 = 6.674_30E-20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Particle {
+    pub body: MajorBody,
+    /// Gravitational parameter, in km³/s²
+    pub gm: f64,
+    /// Position in km
+    pub position: Vector3D<f64>,
+    /// Velocity in km/s
+    pub velocity: Vector3D<f64>,
+}
+
+impl Particle {
+    /// Mass in kg
+    pub fn mass(&self) -> f64 {
+        self.gm / G
+    }
+}
+
+/// State of several bodies at one epoch, all relative to the same center and in the same
+/// frame, in km and km/s
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    /// Julian date of the states, in the time scale of the tables (usually TDB)
+    pub jd: f64,
+    pub center: Option<String>,
+    pub ref_frame: Option<String>,
+    pub particles: Vec<Particle>,
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SnapshotError {
+    #[error("No GM is known for {0}")]
+    UnknownGm(MajorBody),
+    #[error("The table for {0} has no state at JD {1}")]
+    MissingState(MajorBody, f64),
+    #[error("The table for {0} does not state its output units")]
+    UnknownUnits(MajorBody),
+    #[error("The table for {0} has a different center or reference frame")]
+    Inconsistent(MajorBody),
+}
+
+impl Snapshot {
+    /// Takes the state at `jd` from each body's table, converting to km and km/s.
+    ///
+    /// The tables must have been requested with a state [`TableFormat`] and share a center
+    /// and reference frame. Masses come from [`MajorBody::gm`].
+    ///
+    /// [`TableFormat`]: crate::request::ephemeris::vectors::TableFormat
+    pub fn from_tables(
+        jd: f64,
+        tables: &[(MajorBody, &VectorTable)],
+    ) -> Result<Self, SnapshotError> {
+        let mut snapshot = Self {
+            jd,
+            center: None,
+            ref_frame: None,
+            particles: Vec::with_capacity(tables.len()),
+        };
+
+        for (i, &(body, table)) in tables.iter().enumerate() {
+            if i == 0 {
+                snapshot.center.clone_from(&table.meta.center);
+                snapshot.ref_frame.clone_from(&table.meta.ref_frame);
+            } else if snapshot.center != table.meta.center
+                || snapshot.ref_frame != table.meta.ref_frame
+            {
+                return Err(SnapshotError::Inconsistent(body));
+            }
+
+            let gm = body.gm().ok_or(SnapshotError::UnknownGm(body))?;
+            let units = table
+                .meta
+                .out_units
+                .ok_or(SnapshotError::UnknownUnits(body))?;
+
+            let (position, velocity) = table
+                .records
+                .iter()
+                .find(|record| (record.jd - jd).abs() < EPOCH_TOLERANCE)
+                .and_then(|record| record.position.zip(record.velocity))
+                .ok_or(SnapshotError::MissingState(body, jd))?;
+
+            let conversion = units.conversion_to(OutUnits::KM_S);
+
+            snapshot.particles.push(Particle {
+                body,
+                gm,
+                position: position * conversion.length,
+                velocity: velocity * conversion.velocity,
+            });
+        }
+
+        Ok(snapshot)
+    }
+}
+
+/// Unit systems for exporting a [`Snapshot`] to an integrator
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ExportUnits {
+    /// km, s and kg, as REBOUND's `sim.units = ('km', 's', 'kg')`
+    #[default]
+    KmSKg,
+    /// AU, days and solar masses, as used by ASSIST and REBOUND's `('AU', 'day', 'Msun')`
+    AuDayMsun,
+}
+
+impl ExportUnits {
+    /// Gravitational constant in these units
+    pub fn gravitational_constant(self) -> f64 {
+        match self {
+            Self::KmSKg => G,
+            Self::AuDayMsun => GM_SUN * 86_400_f64.powi(2) / AU_KM.powi(3),
+        }
+    }
+
+    /// Factors from km, km/s and km³/s² to length, speed and mass in these units
+    fn factors(self) -> (f64, f64, f64) {
+        match self {
+            Self::KmSKg => (1., 1., 1. / G),
+            Self::AuDayMsun => (1. / AU_KM, 86_400. / AU_KM, 1. / GM_SUN),
+        }
+    }
+}
+
+impl Snapshot {
+    /// A Python snippet adding the particles to a REBOUND simulation `sim`.
+    ///
+    /// `sim.G` is set explicitly so that `G * m` reproduces each body's GM exactly.
+    pub fn to_rebound_python(&self, units: ExportUnits) -> String {
+        let (length, speed, mass) = units.factors();
+        let mut out = format!("sim.G = {:?}\n", units.gravitational_constant());
+
+        for p in &self.particles {
+            let (r, v) = (p.position * length, p.velocity * speed);
+
+            // Writing to a String cannot fail
+            let _ = writeln!(
+                out,
+                "sim.add(m={:?}, x={:?}, y={:?}, z={:?}, vx={:?}, vy={:?}, vz={:?}, hash=\"{}\")",
+                p.gm * mass,
+                r.x,
+                r.y,
+                r.z,
+                v.x,
+                v.y,
+                v.z,
+                p.body
+            );
+        }
+
+        out
+    }
+
+    /// A JSON object `{"G": .., "particles": [..]}`, where each particle has the keyword
+    /// arguments of REBOUND's `sim.add`, so it can be loaded with `sim.add(**particle)`
+    pub fn to_rebound_json(&self, units: ExportUnits) -> String {
+        let (length, speed, mass) = units.factors();
+
+        let particles: Vec<String> = self
+            .particles
+            .iter()
+            .map(|p| {
+                let (r, v) = (p.position * length, p.velocity * speed);
+
+                format!(
+                    r#"{{"hash":"{}","m":{:?},"x":{:?},"y":{:?},"z":{:?},"vx":{:?},"vy":{:?},"vz":{:?}}}"#,
+                    p.body,
+                    p.gm * mass,
+                    r.x,
+                    r.y,
+                    r.z,
+                    v.x,
+                    v.y,
+                    v.z
+                )
+            })
+            .collect();
+
+        format!(
+            r#"{{"G":{:?},"particles":[{}]}}"#,
+            units.gravitational_constant(),
+            particles.join(",")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        request::{
+            bodies::{MajorBody, GM_SUN},
+            ephemeris::OutUnits,
+        },
+        response::{
+            ephemeris::{
+                vectors::{
+                    tests::{JUPITER_SSB, SUN_SSB_CSV},
+                    VectorTable,
+                },
+                Vector3D,
+            },
+            nbody::{ExportUnits, Particle, Snapshot, SnapshotError, AU_KM},
+        },
+        TestResult,
+    };
+
+    #[test]
+    fn test_snapshot() -> TestResult {
+        let (jupiter, mut sun) = (
+            VectorTable::parse(JUPITER_SSB)?,
+            VectorTable::parse(SUN_SSB_CSV)?,
+        );
+
+        assert_eq!(
+            Err(SnapshotError::UnknownGm(MajorBody::Jupiter)),
+            Snapshot::from_tables(2_459_819.5, &[(MajorBody::Jupiter, &jupiter)])
+        );
+
+        // Present the Sun's table in AU and AU/d
+        sun.meta.out_units = Some(OutUnits::AU_D);
+        sun.meta.ref_frame.clone_from(&jupiter.meta.ref_frame);
+        for record in &mut sun.records {
+            record.position = record.position.map(|r| r * (1. / AU_KM));
+            record.velocity = record.velocity.map(|v| v * 86_400. * (1. / AU_KM));
+        }
+
+        let snapshot = Snapshot::from_tables(
+            2_459_819.5,
+            &[(MajorBody::Sun, &sun), (MajorBody::JupiterBary, &jupiter)],
+        )?;
+
+        assert_eq!(
+            Some("Solar System Barycenter (0)"),
+            snapshot.center.as_deref()
+        );
+
+        let sun = snapshot.particles[0];
+        assert!((sun.position.x + 1.205_759_778_861_979E+06).abs() < 1e-6);
+        assert!((sun.velocity.y + 1.376_056_109_381_886E-02).abs() < 1e-9);
+        assert!((sun.mass() / 1.988_4E30 - 1.).abs() < 1e-4);
+
+        assert_eq!(
+            Err(SnapshotError::MissingState(MajorBody::Sun, 2_459_821.5)),
+            Snapshot::from_tables(2_459_821.5, &[(MajorBody::Sun, &jupiter)])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rebound_export() {
+        let snapshot = Snapshot {
+            jd: 2_451_545.,
+            center: None,
+            ref_frame: None,
+            particles: vec![Particle {
+                body: MajorBody::Sun,
+                gm: GM_SUN,
+                position: Vector3D::new(AU_KM, 0., -AU_KM / 2.),
+                velocity: Vector3D::new(0., AU_KM / 86_400., 0.),
+            }],
+        };
+
+        assert_eq!(
+            "sim.G = 0.00029591220828411956\n\
+            sim.add(m=1.0, x=1.0, y=0.0, z=-0.5, vx=0.0, vy=1.0, vz=0.0, hash=\"Sun\")\n",
+            snapshot.to_rebound_python(ExportUnits::AuDayMsun)
+        );
+
+        assert_eq!(
+            r#"{"G":6.6743e-20,"particles":[{"hash":"Sun","m":1.9884098713165337e30,"x":149597870.7,"y":0.0,"z":-74798935.35,"vx":0.0,"vy":1731.4568368055554,"vz":0.0}]}"#,
+            snapshot.to_rebound_json(ExportUnits::KmSKg)
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! Empirical orbital periods from the timing of successive revolutions and conjunctions.
+//!
+//! Longitudes are measured in a [`Plane`], usually the ecliptic for heliocentric tables,
+//! and unwrapped between records. The tables must therefore be sampled at less than half a
+//! revolution (or half a synodic period) per step.
+
+use std::f64::consts::TAU;
+
+use crate::response::{
+    ephemeris::{
+        vectors::{VectorTable, EPOCH_TOLERANCE},
+        Vector3D,
+    },
+    events::Plane,
+};
+
+/// Unwrapped longitudes in turns, from `samples` of positions projected on the plane
+/// normal to `normal`
+fn longitudes(
+    samples: impl Iterator<Item = (f64, Vector3D<f64>)>,
+    normal: Vector3D<f64>,
+) -> Vec<(f64, f64)> {
+    // Any axis not parallel to the normal gives a basis of the plane
+    let axis = if normal.x.abs() < 0.9 {
+        Vector3D::new(1., 0., 0.)
+    } else {
+        Vector3D::new(0., 1., 0.)
+    };
+    let u = normal.cross(&axis);
+    let u = u * (1. / u.norm());
+    let v = normal.cross(&u);
+
+    let mut out: Vec<(f64, f64)> = Vec::new();
+
+    for (jd, position) in samples {
+        let turns = position.dot(&v).atan2(position.dot(&u)) / TAU;
+
+        let turns = match out.last() {
+            Some(&(_, previous)) => previous + (turns - previous + 0.5).rem_euclid(1.) - 0.5,
+            None => turns,
+        };
+
+        out.push((jd, turns));
+    }
+
+    out
+}
+
+/// Epochs at which `samples` of a value in turns pass through a whole number, relative to
+/// `origin`, by linear interpolation
+fn whole_turns(samples: &[(f64, f64)], origin: f64) -> Vec<f64> {
+    let mut epochs = Vec::new();
+
+    for pair in samples.windows(2) {
+        let [(t0, f0), (t1, f1)] = [pair[0], pair[1]];
+        let (f0, f1) = (f0 - origin, f1 - origin);
+        let (lo, hi) = (f0.min(f1), f0.max(f1));
+
+        // Whole numbers in (lo, hi], so that a turn landing on a record is counted once
+        let mut k = lo.floor() + 1.;
+        let mut crossed = Vec::new();
+        while k <= hi {
+            crossed.push(t0 + (t1 - t0) * (k - f0) / (f1 - f0));
+            k += 1.;
+        }
+
+        if f1 < f0 {
+            crossed.reverse();
+        }
+
+        epochs.extend(crossed);
+    }
+
+    epochs
+}
+
+/// Mean interval between the first and last of `epochs`
+fn mean_interval(epochs: &[f64]) -> Option<f64> {
+    match epochs {
+        [first, .., last] => {
+            #[allow(clippy::cast_precision_loss)]
+            let intervals = (epochs.len() - 1) as f64;
+            Some((last - first) / intervals)
+        }
+        _ => None,
+    }
+}
+
+/// Epochs at which the target of `table` returns to its longitude at the first record,
+/// i.e. completes a sidereal revolution about the center
+pub fn revolutions(table: &VectorTable, plane: Plane) -> Vec<f64> {
+    let samples = longitudes(
+        table
+            .records
+            .iter()
+            .filter_map(|record| Some((record.jd, record.position?))),
+        plane.normal_for(table),
+    );
+
+    match samples.first() {
+        Some(&(_, origin)) => whole_turns(&samples, origin)
+            .into_iter()
+            .filter(|&jd| jd - samples[0].0 > EPOCH_TOLERANCE)
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Mean sidereal period in days over the whole revolutions covered by `table`, or `None`
+/// if the table spans less than one revolution.
+///
+/// Timing returns to the same longitude makes the estimate independent of the
+/// eccentricity, but it includes any precession of the orbit within the plane.
+pub fn sidereal_period(table: &VectorTable, plane: Plane) -> Option<f64> {
+    let start = table
+        .records
+        .iter()
+        .find(|record| record.position.is_some())?
+        .jd;
+
+    let mut epochs = revolutions(table, plane);
+    epochs.insert(0, start);
+
+    mean_interval(&epochs)
+}
+
+/// Epochs at which the targets of `a` and `b`, which must share a center, have the same
+/// longitude, e.g. heliocentric conjunctions of two planets
+pub fn conjunctions(a: &VectorTable, b: &VectorTable, plane: Plane) -> Vec<f64> {
+    let normal = plane.normal_for(a);
+
+    let pairs = || {
+        a.records.iter().filter_map(|ra| {
+            let rb = b
+                .records
+                .iter()
+                .find(|rb| (rb.jd - ra.jd).abs() < EPOCH_TOLERANCE)?;
+
+            Some((ra.jd, ra.position?, rb.position?))
+        })
+    };
+
+    let (la, lb) = (
+        longitudes(pairs().map(|(jd, pa, _)| (jd, pa)), normal),
+        longitudes(pairs().map(|(jd, _, pb)| (jd, pb)), normal),
+    );
+
+    let difference: Vec<(f64, f64)> = la
+        .iter()
+        .zip(&lb)
+        .map(|(&(jd, a), &(_, b))| (jd, a - b))
+        .collect();
+
+    whole_turns(&difference, 0.)
+}
+
+/// Mean synodic period in days between the conjunctions of `a` and `b` found in the
+/// tables, or `None` if there are fewer than two
+pub fn synodic_period(a: &VectorTable, b: &VectorTable, plane: Plane) -> Option<f64> {
+    mean_interval(&conjunctions(a, b, plane))
+}
+
+/// Synodic period of two bodies with sidereal periods `p1` and `p2` about the same
+/// center, in the same unit. Infinite for equal periods.
+pub fn synodic_from_sidereal(p1: f64, p2: f64) -> f64 {
+    1. / (1. / p1 - 1. / p2).abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use crate::response::{
+        ephemeris::{
+            vectors::{tests::position_table, VectorTable},
+            Vector3D,
+        },
+        events::Plane,
+        periods::{
+            conjunctions, revolutions, sidereal_period, synodic_from_sidereal, synodic_period,
+        },
+    };
+
+    /// Circular orbit in the ecliptic with `period` days, sampled daily for 720 days
+    fn orbit(radius: f64, period: f64, phase: f64) -> VectorTable {
+        position_table((0..=720).map(f64::from), |day| {
+            let angle = TAU * day / period + phase;
+            Vector3D::new(radius * angle.cos(), radius * angle.sin(), 0.)
+        })
+    }
+
+    #[test]
+    fn test_periods() {
+        let (inner, outer) = (orbit(1., 100., 0.3), orbit(2., 300., 0.));
+
+        assert_eq!(7, revolutions(&inner, Plane::ecliptic()).len());
+        let period = sidereal_period(&inner, Plane::ecliptic()).unwrap();
+        assert!((period - 100.).abs() < 1e-6);
+        assert!((sidereal_period(&outer, Plane::ecliptic()).unwrap() - 300.).abs() < 1e-6);
+
+        // Retrograde motion is timed the same way
+        let retrograde = orbit(1., -50., 0.);
+        assert!((sidereal_period(&retrograde, Plane::ecliptic()).unwrap() - 50.).abs() < 1e-6);
+
+        // The inner body starts 0.3 rad ahead and gains a turn every 150 days
+        let expected = 150. * (1. - 0.3 / TAU);
+        let found = conjunctions(&inner, &outer, Plane::ecliptic());
+        assert!((found[0] - 2_460_000. - expected).abs() < 1e-6);
+
+        let synodic = synodic_period(&inner, &outer, Plane::ecliptic()).unwrap();
+        assert!((synodic - 150.).abs() < 1e-6);
+        assert!((synodic_from_sidereal(100., 300.) - 150.).abs() < 1e-9);
+
+        assert_eq!(
+            None,
+            sidereal_period(&orbit(1., 1000., 0.), Plane::ecliptic())
+        );
+    }
+}
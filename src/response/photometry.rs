@@ -0,0 +1,160 @@
+//! Predicted visual magnitudes of asteroids in the IAU H, G system (Bowell et al. 1989),
+//! from the absolute magnitude and slope in the object data of a response and the
+//! geometry of vector tables.
+
+use crate::{
+    request::ephemeris::OutUnits,
+    response::ephemeris::vectors::{VectorTable, EPOCH_TOLERANCE},
+};
+
+/// Slope parameter assumed when the object data gives none
+pub const DEFAULT_SLOPE: f64 = 0.15;
+
+/// Absolute magnitude and slope parameter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HG {
+    pub h: f64,
+    pub g: f64,
+}
+
+/// The value of `key=` in the object data, where `key` starts a word
+fn header_value(header: &str, key: &str) -> Option<f64> {
+    let marker = format!("{key}=");
+
+    header.match_indices(&marker).find_map(|(i, _)| {
+        let starts_word = header[..i]
+            .chars()
+            .next_back()
+            .map_or(true, char::is_whitespace);
+
+        header[i + marker.len()..]
+            .split_whitespace()
+            .next()
+            .filter(|_| starts_word)?
+            .parse()
+            .ok()
+    })
+}
+
+impl HG {
+    /// Reads `H=` and `G=` from the object data of a small-body response, e.g.
+    /// `H= 3.33  G= .120`. `G` defaults to [`DEFAULT_SLOPE`].
+    pub fn parse(header: &str) -> Option<Self> {
+        Some(Self {
+            h: header_value(header, "H")?,
+            g: header_value(header, "G").unwrap_or(DEFAULT_SLOPE),
+        })
+    }
+
+    /// Visual magnitude at heliocentric distance `r` and observer distance `delta` in au,
+    /// and phase angle `phase` in radians, valid up to phase angles of about 120°
+    pub fn magnitude(self, r: f64, delta: f64, phase: f64) -> f64 {
+        let tan = (phase / 2.).tan();
+        let phi1 = (-3.33 * tan.powf(0.63)).exp();
+        let phi2 = (-1.87 * tan.powf(1.22)).exp();
+
+        self.h + 5. * (r * delta).log10() - 2.5 * ((1. - self.g) * phi1 + self.g * phi2).log10()
+    }
+
+    /// Magnitudes at each epoch with positions in both a heliocentric table of the target
+    /// and a table of the target relative to the observer
+    pub fn magnitudes(self, heliocentric: &VectorTable, observer: &VectorTable) -> Vec<(f64, f64)> {
+        let au = |table: &VectorTable| {
+            table
+                .meta
+                .out_units
+                .unwrap_or_default()
+                .conversion_to(OutUnits::AU_D)
+                .length
+        };
+        let (sun_scale, observer_scale) = (au(heliocentric), au(observer));
+
+        heliocentric
+            .records
+            .iter()
+            .filter_map(|record| {
+                let from_sun = record.position? * sun_scale;
+                let from_observer = observer
+                    .records
+                    .iter()
+                    .find(|o| (o.jd - record.jd).abs() < EPOCH_TOLERANCE)?
+                    .position?
+                    * observer_scale;
+
+                let (r, delta) = (from_sun.norm(), from_observer.norm());
+                let phase = (from_sun.dot(&from_observer) / (r * delta))
+                    .clamp(-1., 1.)
+                    .acos();
+
+                Some((record.jd, self.magnitude(r, delta, phase)))
+            })
+            .collect()
+    }
+}
+
+/// Epochs at which `predicted` and `reported` magnitudes, e.g. from [`HG::magnitudes`]
+/// and from Horizons, differ by more than `tolerance`, with the difference
+pub fn divergences(
+    predicted: &[(f64, f64)],
+    reported: &[(f64, f64)],
+    tolerance: f64,
+) -> Vec<(f64, f64)> {
+    predicted
+        .iter()
+        .filter_map(|&(jd, magnitude)| {
+            let &(_, other) = reported
+                .iter()
+                .find(|(other, _)| (other - jd).abs() < EPOCH_TOLERANCE)?;
+
+            let difference = magnitude - other;
+            (difference.abs() > tolerance).then_some((jd, difference))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::request::ephemeris::OutUnits;
+    use crate::response::{
+        ephemeris::{
+            vectors::{tests::position_table, VectorTable},
+            Vector3D,
+        },
+        photometry::{divergences, HG},
+    };
+
+    fn table(position: Vector3D<f64>) -> VectorTable {
+        let mut table = position_table([0.5], |_| position);
+        table.meta.out_units = Some(OutUnits::AU_D);
+        table
+    }
+
+    #[test]
+    fn test_magnitudes() {
+        let ceres = HG::parse(
+            "  EPOCH=  2460000.5 ! 2023-Feb-25.00 (TDB)\n   H= 3.33       G= .120         B-V= .713",
+        )
+        .unwrap();
+        assert_eq!(HG { h: 3.33, g: 0.12 }, ceres);
+        assert_eq!(None, HG::parse("RAD= 469.7  ROTPER= 9.07"));
+
+        // At opposition the phase functions are 1
+        let opposition = ceres.magnitude(2.5, 1.5, 0.);
+        assert!((opposition - (3.33 + 5. * 3.75_f64.log10())).abs() < 1e-12);
+        assert!(ceres.magnitude(2.5, 1.5, 0.3) > opposition);
+
+        // Sun, observer and target in a right angle at the observer
+        let magnitudes = ceres.magnitudes(
+            &table(Vector3D::new(1., 1., 0.)),
+            &table(Vector3D::new(0., 1., 0.)),
+        );
+        let phase = std::f64::consts::FRAC_PI_4;
+        assert!((magnitudes[0].1 - ceres.magnitude(2_f64.sqrt(), 1., phase)).abs() < 1e-12);
+
+        assert_eq!(
+            vec![(2_460_000.5, 0.5)],
+            divergences(&[(2_460_000.5, 7.)], &[(2_460_000.5, 6.5)], 0.1)
+        );
+        assert!(divergences(&[(2_460_000.5, 7.)], &[(2_460_000.5, 6.95)], 0.1).is_empty());
+    }
+}
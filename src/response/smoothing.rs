@@ -0,0 +1,230 @@
+//! Savitzky–Golay smoothing and differentiation of evenly spaced table columns
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SavitzkyGolayError {
+    #[error("The window must have an odd length greater than the polynomial order")]
+    InvalidWindow,
+    #[error("Derivative {0} is above the polynomial order")]
+    InvalidDerivative(usize),
+    #[error("At least {0} values are needed to fill the window")]
+    TooFewValues(usize),
+}
+
+/// Least-squares polynomial filter over a sliding window.
+///
+/// Each value is replaced by the value (or a derivative) at that point of a polynomial of
+/// degree `order` fitted to the `window` values around it. Near the ends the window is
+/// shifted to stay inside the data, so the output has the same length as the input. The
+/// values must be evenly spaced, e.g. a column of a table requested with a fixed step
+/// size:
+///
+/// ```ignore
+/// let range_rate: Vec<f64> = table.records.iter().filter_map(|r| r.range_rate).collect();
+/// let smoothed = SavitzkyGolay::new(11, 3)?.smooth(&range_rate)?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SavitzkyGolay {
+    window: usize,
+    order: usize,
+}
+
+impl SavitzkyGolay {
+    pub fn new(window: usize, order: usize) -> Result<Self, SavitzkyGolayError> {
+        if window % 2 == 0 || window <= order {
+            return Err(SavitzkyGolayError::InvalidWindow);
+        }
+
+        Ok(Self { window, order })
+    }
+
+    /// Smoothed values
+    pub fn smooth(&self, values: &[f64]) -> Result<Vec<f64>, SavitzkyGolayError> {
+        self.filter(values, 0, 1.)
+    }
+
+    /// `derivative`-th derivative of the values, spaced `step` apart (e.g. the step size of
+    /// the table in the time unit wanted)
+    pub fn derivative(
+        &self,
+        values: &[f64],
+        derivative: usize,
+        step: f64,
+    ) -> Result<Vec<f64>, SavitzkyGolayError> {
+        if derivative > self.order {
+            return Err(SavitzkyGolayError::InvalidDerivative(derivative));
+        }
+
+        self.filter(values, derivative, step)
+    }
+
+    fn filter(
+        &self,
+        values: &[f64],
+        derivative: usize,
+        step: f64,
+    ) -> Result<Vec<f64>, SavitzkyGolayError> {
+        if values.len() < self.window {
+            return Err(SavitzkyGolayError::TooFewValues(self.window));
+        }
+
+        let half = self.window / 2;
+        let scale = factorial(derivative) / step.powi(exponent(derivative));
+
+        // Weights depend only on where the point sits in its window, so there is one set
+        // for the centred case and one for each position in the end windows
+        let weights: Vec<Vec<f64>> = (0..self.window)
+            .map(|position| self.weights(position, derivative))
+            .collect();
+
+        let filtered = (0..values.len())
+            .map(|i| {
+                let start = i.saturating_sub(half).min(values.len() - self.window);
+                let window = &values[start..start + self.window];
+
+                weights[i - start]
+                    .iter()
+                    .zip(window)
+                    .map(|(w, v)| w * v)
+                    .sum::<f64>()
+                    * scale
+            })
+            .collect();
+
+        Ok(filtered)
+    }
+
+    /// Weights giving the `derivative`-th polynomial coefficient about the `position`-th
+    /// point of the window, i.e. row `derivative` of (AᵀA)⁻¹Aᵀ with A the Vandermonde
+    /// matrix of the window offsets
+    fn weights(&self, position: usize, derivative: usize) -> Vec<f64> {
+        let terms = self.order + 1;
+        let offsets: Vec<f64> = (0..self.window)
+            .map(|j| as_f64(j) - as_f64(position))
+            .collect();
+
+        let mut gram = vec![vec![0.; terms]; terms];
+        for (r, row) in gram.iter_mut().enumerate() {
+            for (c, value) in row.iter_mut().enumerate() {
+                *value = offsets.iter().map(|x| x.powi(exponent(r + c))).sum();
+            }
+        }
+
+        // Row `derivative` of the inverse Gram matrix, from solving Gᵀ y = e (G is symmetric)
+        let mut unit = vec![0.; terms];
+        unit[derivative] = 1.;
+        let row = solve(gram, unit);
+
+        offsets
+            .iter()
+            .map(|x| {
+                row.iter()
+                    .enumerate()
+                    .map(|(k, y)| y * x.powi(exponent(k)))
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+/// Solves `a x = b` by Gaussian elimination with partial pivoting
+fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Vec<f64> {
+    let n = b.len();
+
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+            .unwrap_or(col);
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        let (upper, lower) = a.split_at_mut(col + 1);
+        let pivot_row = &upper[col];
+
+        for (offset, row) in lower.iter_mut().enumerate() {
+            let factor = row[col] / pivot_row[col];
+            for (value, pivot) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                *value -= factor * pivot;
+            }
+            b[col + 1 + offset] -= factor * b[col];
+        }
+    }
+
+    let mut x = vec![0.; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+
+    x
+}
+
+fn factorial(n: usize) -> f64 {
+    (1..=n).map(as_f64).product()
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn as_f64(n: usize) -> f64 {
+    n as f64
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn exponent(n: usize) -> i32 {
+    n as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        response::smoothing::{SavitzkyGolay, SavitzkyGolayError},
+        TestResult,
+    };
+
+    #[test]
+    fn test_savitzky_golay() -> TestResult {
+        // A cubic is fitted exactly, at the ends too
+        let cubic = |t: f64| 2. * t.powi(3) - t * t + 3.;
+        let (step, values): (f64, Vec<f64>) =
+            (0.5, (0..20).map(|i| cubic(f64::from(i) * 0.5)).collect());
+
+        let filter = SavitzkyGolay::new(7, 3)?;
+        let smoothed = filter.smooth(&values)?;
+        let rate = filter.derivative(&values, 1, step)?;
+        let curvature = filter.derivative(&values, 2, step)?;
+
+        for (i, value) in values.iter().enumerate() {
+            let t = f64::from(u8::try_from(i)?) * step;
+
+            assert!((smoothed[i] - value).abs() < 1e-9);
+            assert!((rate[i] - (6. * t * t - 2. * t)).abs() < 1e-8);
+            assert!((curvature[i] - (12. * t - 2.)).abs() < 1e-7);
+        }
+
+        // Alternating noise is removed from a line
+        let noisy: Vec<f64> = (0..15)
+            .map(|i| f64::from(i) + if i % 2 == 0 { 0.1 } else { -0.1 })
+            .collect();
+        let smoothed = SavitzkyGolay::new(5, 1)?.smooth(&noisy)?;
+        assert!((smoothed[7] - 7.).abs() < 0.03);
+
+        assert_eq!(
+            Err(SavitzkyGolayError::InvalidWindow),
+            SavitzkyGolay::new(4, 2)
+        );
+        assert_eq!(
+            Err(SavitzkyGolayError::InvalidWindow),
+            SavitzkyGolay::new(3, 3)
+        );
+        assert_eq!(
+            Err(SavitzkyGolayError::InvalidDerivative(4)),
+            filter.derivative(&values, 4, step)
+        );
+        assert_eq!(
+            Err(SavitzkyGolayError::TooFewValues(7)),
+            filter.smooth(&values[..6])
+        );
+
+        Ok(())
+    }
+}
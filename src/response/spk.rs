@@ -0,0 +1,129 @@
+#![allow(clippy::module_name_repetitions)]
+
+use std::{fs, io, path::Path};
+
+use thiserror::Error;
+
+use crate::json::Json;
+
+#[derive(Error, Debug)]
+pub enum SpkError {
+    #[error("Response is not valid JSON")]
+    InvalidJson,
+    #[error("Missing field `{0}` in response")]
+    MissingField(&'static str),
+    #[error("Invalid base64 in `spk` field")]
+    InvalidBase64,
+    #[error("{0}")]
+    Io(#[from] io::Error),
+}
+
+/// Binary SPK file returned by an [`crate::request::ephemeris::spk::Spk`] request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpkFile {
+    /// SPK-ID of the object, as reported by the server
+    pub id: String,
+    /// Contents of the `.bsp` file
+    pub data: Vec<u8>,
+}
+
+impl SpkFile {
+    /// Extracts the file from the JSON body of the response
+    pub fn from_json(json: &str) -> Result<Self, SpkError> {
+        let json = Json::parse(json).ok_or(SpkError::InvalidJson)?;
+        let field = |key| {
+            json.get(key)
+                .and_then(Json::as_str)
+                .ok_or(SpkError::MissingField(key))
+        };
+
+        let id = field("spk_file_id")?.to_owned();
+        let data = decode_base64(field("spk")?).ok_or(SpkError::InvalidBase64)?;
+
+        Ok(Self { id, data })
+    }
+
+    /// Writes the binary `.bsp` file to `path`
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), SpkError> {
+        fs::write(path, &self.data)?;
+        Ok(())
+    }
+}
+
+/// Decodes standard base64 with padding, ignoring whitespace
+fn decode_base64(s: &str) -> Option<Vec<u8>> {
+    fn value(b: u8) -> Option<u32> {
+        match b {
+            b'A'..=b'Z' => Some(u32::from(b - b'A')),
+            b'a'..=b'z' => Some(u32::from(b - b'a') + 26),
+            b'0'..=b'9' => Some(u32::from(b - b'0') + 52),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    let bytes: Vec<u8> = s.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+
+    if bytes.len() % 4 != 0 {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(bytes.len() / 4 * 3);
+
+    for (i, chunk) in bytes.chunks(4).enumerate() {
+        let last = i == bytes.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|&&b| b == b'=').count();
+
+        if padding > 2 || (padding > 0 && !last) {
+            return None;
+        }
+
+        let mut n = 0;
+        for &b in &chunk[..4 - padding] {
+            n = n << 6 | value(b)?;
+        }
+        n <<= 6 * padding;
+
+        let [_, b0, b1, b2] = n.to_be_bytes();
+        out.extend_from_slice(&[b0, b1, b2][..3 - padding]);
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        response::spk::{decode_base64, SpkFile},
+        TestResult,
+    };
+
+    #[test]
+    fn test_decode_base64() {
+        assert_eq!(Some(b"DAF/SPK ".to_vec()), decode_base64("REFGL1NQSyA="));
+        assert_eq!(Some(b"ab".to_vec()), decode_base64("YW\nI="));
+        assert_eq!(Some(b"a".to_vec()), decode_base64("YQ=="));
+        assert_eq!(None, decode_base64("YQ="));
+        assert_eq!(None, decode_base64("YQ==YQ=="));
+        assert_eq!(None, decode_base64("Y!=="));
+    }
+
+    #[test]
+    fn test_spk_file() -> TestResult {
+        let json = r#"{"signature":{"version":"1.2","source":"NASA/JPL Horizons API"},
+            "spk_file_id" : "2000433", "spk":"REFGL1NQSyD\/\/w=="}"#;
+
+        assert_eq!(
+            SpkFile {
+                id: "2000433".to_owned(),
+                data: b"DAF/SPK \xff\xff".to_vec(),
+            },
+            SpkFile::from_json(json)?
+        );
+
+        assert!(SpkFile::from_json(r#"{"result":"No SPK"}"#).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+//! References to files that Horizons stages on its file server instead of including them in
+//! the response, as it does for SPK files and very long tables of batch and e-mail jobs.
+//! The files are kept for a limited time; download them with
+//! [`Client::download_staged`](crate::client::Client::download_staged).
+
+/// URL prefixes of the anonymous FTP area of the JPL Solar System Dynamics group, which is
+/// now also, and only reliably, served over HTTPS
+const FTP_MIRRORS: [(&str, &str); 2] = [
+    (
+        "ftp://ssd.jpl.nasa.gov/pub/",
+        "https://ssd.jpl.nasa.gov/ftp/",
+    ),
+    (
+        "http://ssd.jpl.nasa.gov/ftp/",
+        "https://ssd.jpl.nasa.gov/ftp/",
+    ),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StagedFile {
+    /// As given in the response, e.g. `ftp://ssd.jpl.nasa.gov/pub/ssd/wld12345.15`
+    pub url: String,
+}
+
+impl StagedFile {
+    /// Name of the file, e.g. `wld12345.15`
+    pub fn name(&self) -> &str {
+        self.url.rsplit('/').next().unwrap_or_default()
+    }
+
+    /// The URL to download the file from over HTTPS
+    pub fn download_url(&self) -> String {
+        FTP_MIRRORS
+            .iter()
+            .find_map(|(ftp, https)| {
+                self.url
+                    .strip_prefix(ftp)
+                    .map(|path| format!("{https}{path}"))
+            })
+            .unwrap_or_else(|| self.url.clone())
+    }
+}
+
+/// A staged file and its contents, from
+/// [`Client::download_staged`](crate::client::Client::download_staged)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadedFile {
+    pub file: StagedFile,
+    pub data: Vec<u8>,
+}
+
+/// The staged files referred to by a response, in order and without repeats
+pub fn staged_files(text: &str) -> Vec<StagedFile> {
+    let mut files: Vec<StagedFile> = Vec::new();
+
+    let urls = text
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| "\"'()<>[],;".contains(c)))
+        .map(|word| word.trim_end_matches('.'))
+        .filter(|word| {
+            word.starts_with("ftp://")
+                || FTP_MIRRORS.iter().any(|(_, https)| word.starts_with(https))
+        });
+
+    for url in urls {
+        if !files.iter().any(|file| file.url == url) {
+            files.push(StagedFile {
+                url: url.to_owned(),
+            });
+        }
+    }
+
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::response::staged::{staged_files, StagedFile};
+
+    #[test]
+    fn test_staged_files() {
+        let response = "
+ Binary SPK file written for 1P/Halley.
+ It can be retrieved for one hour from:
+    ftp://ssd.jpl.nasa.gov/pub/ssd/wld12345.15
+ (also <ftp://ssd.jpl.nasa.gov/pub/ssd/wld12345.15>, see
+ https://ssd.jpl.nasa.gov/ftp/ssd/wld12346.txt.)
+ Manual: https://ssd.jpl.nasa.gov/horizons/manual.html
+";
+
+        let files = staged_files(response);
+        assert_eq!(
+            vec![
+                StagedFile {
+                    url: "ftp://ssd.jpl.nasa.gov/pub/ssd/wld12345.15".to_owned()
+                },
+                StagedFile {
+                    url: "https://ssd.jpl.nasa.gov/ftp/ssd/wld12346.txt".to_owned()
+                },
+            ],
+            files
+        );
+
+        assert_eq!("wld12345.15", files[0].name());
+        assert_eq!(
+            "https://ssd.jpl.nasa.gov/ftp/ssd/wld12345.15",
+            files[0].download_url()
+        );
+        assert_eq!(files[1].url, files[1].download_url());
+
+        assert!(staged_files("$$SOE\n$$EOE").is_empty());
+    }
+}
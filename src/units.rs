@@ -1,17 +1,112 @@
+//! Unit coefficients for Horizons output, and matching `uom` units with the `uom` feature
+
+/// Velocity units of [`OutUnits`](crate::request::ephemeris::OutUnits)
+#[cfg(feature = "uom")]
+pub mod velocity {
+    unit! {
+        system: uom::si;
+        quantity: uom::si::velocity;
+
+        @au_per_day: crate::units::AU_PER_DAY; "AU/d", "AU per day", "astronomical units per day";
+        @kilometre_per_day: crate::units::KILOMETRE_PER_DAY; "km/d", "km per day", "kilometres per day";
+    }
+}
+
+/// Length units of Horizons tables
+#[cfg(feature = "uom")]
+pub mod length {
+    unit! {
+        system: uom::si;
+        quantity: uom::si::length;
+
+        @astronomical_unit: crate::units::ASTRONOMICAL_UNIT; "AU", "astronomical unit", "astronomical units";
+    }
+}
+
+/// Time units of Horizons tables
+#[cfg(feature = "uom")]
+pub mod time {
+    unit! {
+        system: uom::si;
+        quantity: uom::si::time;
+
+        @julian_day: crate::units::DAY; "d", "Julian day", "Julian days";
+    }
+}
+
+/// Angle units of observer quantities such as RA, DEC and angular diameters
 #[cfg(feature = "uom")]
-unit! {
-    system: uom::si;
-    quantity: uom::si::velocity;
+pub mod angle {
+    unit! {
+        system: uom::si;
+        quantity: uom::si::angle;
 
-    @au_per_day: 1_731_456.9; "AU/d", "AU per day", "astronomical units per day";
-    @kilometre_per_day: 86.4; "km/d", "km per day", "kilometres per day";
+        @arcsecond: crate::units::ARCSECOND; "″", "arcsecond", "arcseconds";
+        @milliarcsecond: crate::units::ARCSECOND / 1000.; "mas", "milliarcsecond", "milliarcseconds";
+    }
 }
 
+/// Coefficient in m
+pub const KILOMETRE: f64 = 1000.;
+
+/// Coefficient in m, as defined by IAU 2012 Resolution B2
+pub const ASTRONOMICAL_UNIT: f64 = 149_597_870_700.;
+
+/// Coefficient in s
+pub const SECOND: f64 = 1.;
+
+/// Coefficient in s
+pub const DAY: f64 = 86_400.;
+
+/// Coefficient in rad
+pub const ARCSECOND: f64 = std::f64::consts::PI / 648_000.;
+
 /// Coefficient in m/s
-pub const AU_PER_DAY: f64 = 1_731_456.9;
+pub const AU_PER_DAY: f64 = ASTRONOMICAL_UNIT / DAY;
 
 /// Coefficient in m/s
-pub const KILOMETRE_PER_SECOND: f64 = 1000.;
+pub const KILOMETRE_PER_SECOND: f64 = KILOMETRE / SECOND;
 
 /// Coefficient in m/s
-pub const KILOMETRE_PER_DAY: f64 = 86.4;
+pub const KILOMETRE_PER_DAY: f64 = KILOMETRE / DAY;
+
+#[cfg(all(test, feature = "uom"))]
+mod tests {
+    use uom::si::{
+        angle::degree,
+        f64::{Angle, Length, Time, Velocity},
+        length::kilometer,
+        time::second,
+        velocity::kilometer_per_second,
+    };
+
+    use crate::{
+        request::ephemeris::OutUnits,
+        units::{
+            angle::arcsecond, length::astronomical_unit, time::julian_day, velocity::au_per_day,
+        },
+    };
+
+    #[test]
+    fn test_uom_units() {
+        let au = Length::new::<astronomical_unit>(1.);
+        assert!((au.get::<kilometer>() - 149_597_870.7).abs() < 1e-6);
+
+        let day = Time::new::<julian_day>(1.);
+        assert!((day.get::<second>() - 86_400.).abs() < 1e-9);
+
+        let speed = Velocity::new::<au_per_day>(1.);
+        assert!(
+            ((au / day).get::<kilometer_per_second>() - speed.get::<kilometer_per_second>()).abs()
+                < 1e-9
+        );
+
+        let converted =
+            OutUnits::AU_D.velocity(1.) - OutUnits::KM_S.velocity(1_731.456_836_805_555_6);
+        assert!(converted.get::<kilometer_per_second>().abs() < 1e-9);
+        assert_eq!(day, OutUnits::KM_D.time(1.));
+
+        let angle = Angle::new::<arcsecond>(3_600.);
+        assert!((angle.get::<degree>() - 1.).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,257 @@
+//! A transport that records the responses of another to fixture files and replays them,
+//! so that tests of code using a [`Client`](crate::client::Client) run without the
+//! network and always see the same responses.
+//!
+//! Each fixture is a file named by a hash of the request. It holds a [`VCR_VERSION`] header
+//! on its first line, the method and URL on its second, and then the body of the response.
+//! Fixtures can be checked in and re-recorded by deleting them. Replaying a fixture of
+//! another version fails, while [`Mode::Auto`] records it again.
+
+#![allow(clippy::must_use_candidate, clippy::missing_errors_doc)]
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{cache::fnv1a, client::HttpTransport};
+
+const EXTENSION: &str = "txt";
+
+/// Version of the format of fixtures, increased when it changes
+pub const VCR_VERSION: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum VcrError<E> {
+    #[error("{0}")]
+    Transport(E),
+    #[error("Fixture: {0}")]
+    Io(#[from] io::Error),
+    #[error("No recorded response to `{0}`")]
+    Missing(String),
+    #[error("Fixture `{0}` is not of version {VCR_VERSION}; record it again")]
+    IncompatibleVersion(PathBuf),
+}
+
+/// The recorded response of a fixture
+enum Fixture {
+    Missing,
+    IncompatibleVersion,
+    Response(Vec<u8>),
+}
+
+/// First line of a fixture, with its newline
+fn header() -> String {
+    format!("horizons-vcr {VCR_VERSION}\n")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Sends every request and records the response, replacing any fixture
+    Record,
+    /// Only replays recorded responses
+    Replay,
+    /// Replays recorded responses, and sends and records the rest
+    Auto,
+}
+
+/// The inner transport of a [`VcrTransport::replay`], which never sends anything
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Offline;
+
+impl HttpTransport for Offline {
+    type Error = io::Error;
+
+    fn send(&self, url: &str, _: Option<&[u8]>) -> Result<Vec<u8>, io::Error> {
+        Err(io::Error::new(
+            io::ErrorKind::NotConnected,
+            format!("Offline transport cannot send `{url}`"),
+        ))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VcrTransport<T> {
+    inner: T,
+    dir: PathBuf,
+    mode: Mode,
+}
+
+impl VcrTransport<Offline> {
+    /// Replays the fixtures in `dir`, failing for requests without one
+    pub fn replay(dir: impl Into<PathBuf>) -> Self {
+        Self::new(Offline, dir, Mode::Replay)
+    }
+}
+
+impl<T: HttpTransport> VcrTransport<T> {
+    /// Fixtures in `dir`, recorded from `inner` as `mode` allows
+    pub fn new(inner: T, dir: impl Into<PathBuf>, mode: Mode) -> Self {
+        Self {
+            inner,
+            dir: dir.into(),
+            mode,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// The first line of the fixture of a request, and the path of the fixture
+    fn fixture(&self, url: &str, body: Option<&[u8]>) -> (String, PathBuf) {
+        let request = match body {
+            Some(_) => format!("POST {url}"),
+            None => format!("GET {url}"),
+        };
+
+        let mut key = request.clone().into_bytes();
+        if let Some(body) = body {
+            key.push(b'\n');
+            key.extend_from_slice(body);
+        }
+
+        let path = self.dir.join(format!("{:016x}.{EXTENSION}", fnv1a(&key)));
+
+        (request, path)
+    }
+
+    /// The recorded response, if its fixture is for `request`
+    fn read(request: &str, path: &Path) -> io::Result<Fixture> {
+        let contents = match fs::read(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Fixture::Missing),
+            Err(error) => return Err(error),
+        };
+
+        let recorded = match contents.strip_prefix(header().as_bytes()) {
+            Some(recorded) => recorded,
+            None => return Ok(Fixture::IncompatibleVersion),
+        };
+
+        let newline = recorded.iter().position(|&byte| byte == b'\n');
+
+        Ok(newline
+            .filter(|&newline| &recorded[..newline] == request.as_bytes())
+            .map_or(Fixture::Missing, |newline| {
+                Fixture::Response(recorded[newline + 1..].to_vec())
+            }))
+    }
+}
+
+impl<T: HttpTransport> HttpTransport for VcrTransport<T> {
+    type Error = VcrError<T::Error>;
+
+    fn send(&self, url: &str, body: Option<&[u8]>) -> Result<Vec<u8>, Self::Error> {
+        let (request, path) = self.fixture(url, body);
+
+        if self.mode != Mode::Record {
+            match (Self::read(&request, &path)?, self.mode) {
+                (Fixture::Response(response), _) => return Ok(response),
+                (Fixture::Missing, Mode::Replay) => return Err(VcrError::Missing(request)),
+                (Fixture::IncompatibleVersion, Mode::Replay) => {
+                    return Err(VcrError::IncompatibleVersion(path))
+                }
+                _ => {}
+            }
+        }
+
+        let response = self.inner.send(url, body).map_err(VcrError::Transport)?;
+
+        let mut fixture = header().into_bytes();
+        fixture.extend_from_slice(request.as_bytes());
+        fixture.push(b'\n');
+        fixture.extend_from_slice(&response);
+
+        fs::create_dir_all(&self.dir)?;
+        fs::write(path, fixture)?;
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use chrono::{TimeZone, Utc};
+
+    use crate::{
+        client::{Client, ClientError, HttpTransport},
+        request::{bodies::MajorBody, ephemeris::TimeSpec, presets::state_vectors},
+        vcr::{Mode, VcrError, VcrTransport, VCR_VERSION},
+        TestResult,
+    };
+
+    #[test]
+    fn test_vcr() -> TestResult {
+        let dir = std::env::temp_dir().join(format!("horizons-vcr-{}", std::process::id()));
+        let sends = Cell::new(0);
+        let server = |url: &str, _: Option<&[u8]>| {
+            sends.set(sends.get() + 1);
+            Ok::<_, &str>(format!("response to {url}").into_bytes())
+        };
+
+        let recorder = VcrTransport::new(&server, &dir, Mode::Record);
+        assert_eq!(b"response to a".to_vec(), recorder.send("a", None)?);
+        assert_eq!(
+            b"response to b".to_vec(),
+            recorder.send("b", Some(b"form"))?
+        );
+
+        let replay = VcrTransport::replay(&dir);
+        assert_eq!(b"response to a".to_vec(), replay.send("a", None)?);
+        assert_eq!(b"response to b".to_vec(), replay.send("b", Some(b"form"))?);
+        assert!(matches!(
+            replay.send("b", None),
+            Err(VcrError::Missing(request)) if request == "GET b"
+        ));
+
+        // Sends only what was not recorded yet
+        let auto = VcrTransport::new(&server, &dir, Mode::Auto);
+        auto.send("a", None)?;
+        auto.send("c", None)?;
+        assert_eq!(3, sends.get());
+        assert_eq!(b"response to c".to_vec(), replay.send("c", None)?);
+
+        // Fixtures of another version are re-recorded, or fail to replay
+        let (_, path) = replay.fixture("a", None);
+        let fixture = std::fs::read_to_string(&path)?;
+        assert_eq!(
+            format!("horizons-vcr {VCR_VERSION}\nGET a\nresponse to a"),
+            fixture
+        );
+        std::fs::write(
+            &path,
+            fixture.replacen("horizons-vcr 1", "horizons-vcr 0", 1),
+        )?;
+        assert!(matches!(
+            replay.send("a", None),
+            Err(VcrError::IncompatibleVersion(stale)) if stale == path
+        ));
+        auto.send("a", None)?;
+        assert_eq!(4, sends.get());
+        assert_eq!(b"response to a".to_vec(), replay.send("a", None)?);
+
+        let query = state_vectors(
+            MajorBody::Mars,
+            MajorBody::Sun,
+            TimeSpec::from_list([Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap()]),
+        )?;
+        let client = Client::new(VcrTransport::replay(&dir));
+        assert!(matches!(
+            client.fetch(&query),
+            Err(ClientError::Transport(VcrError::Missing(_)))
+        ));
+
+        std::fs::remove_dir_all(&dir)?;
+
+        Ok(())
+    }
+}